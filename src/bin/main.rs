@@ -13,8 +13,22 @@ fn main() {
 fn run() -> Result<(), error::ProcessError> {
     let Command::Release(ref release_matches) = Command::parse();
 
+    if let Some(shell) = release_matches.completions {
+        use clap::CommandFactory;
+
+        clap_complete::generate(
+            shell,
+            &mut Command::command(),
+            "cargo-release",
+            &mut std::io::stdout(),
+        );
+        return Ok(());
+    }
+
     let mut builder = get_logging(release_matches.logging.log_level());
     builder.init();
+    ops::cmd::set_print_commands(release_matches.logging.print_commands);
+    ops::cmd::set_quiet(release_matches.logging.log_level() < log::Level::Info);
 
     match &release_matches.step {
         Some(Step::Version(config)) => config.run(),
@@ -23,6 +37,7 @@ fn run() -> Result<(), error::ProcessError> {
         Some(Step::Tag(config)) => config.run(),
         Some(Step::Push(config)) => config.run(),
         Some(Step::Config(config)) => config.run(),
+        Some(Step::Yank(config)) => config.run(),
         None => release_matches.release.run(),
     }
 }
@@ -74,6 +89,10 @@ pub struct ReleaseOpt {
 
     #[command(subcommand)]
     pub step: Option<Step>,
+
+    /// Print a completion script for SHELL
+    #[arg(long, value_enum, value_name = "SHELL", hide = true)]
+    pub completions: Option<clap_complete::Shell>,
 }
 
 #[derive(Clone, Debug, clap::Subcommand)]
@@ -84,12 +103,17 @@ pub enum Step {
     Tag(steps::tag::TagStep),
     Push(steps::push::PushStep),
     Config(steps::config::ConfigStep),
+    Yank(steps::yank::YankStep),
 }
 
 #[derive(clap::Args, Debug, Clone)]
 #[command(next_help_heading = None)]
 pub struct Verbosity {
     /// Pass many times for less log output
+    ///
+    /// By default, it'll report info. Passing `-q` one time only reports
+    /// warnings and errors, `-qq` only errors, also quieting `cargo publish`,
+    /// `cargo package`, and `git push`.  Prompts are still shown.
     #[arg(long, short, action = clap::ArgAction::Count, global = true)]
     quiet: u8,
 
@@ -99,6 +123,10 @@ pub struct Verbosity {
     /// logs, `-vv` adds trace logs.
     #[arg(long, short, action = clap::ArgAction::Count, global = true)]
     verbose: u8,
+
+    /// Show each external command (and its directory) before running it
+    #[arg(long, global = true)]
+    print_commands: bool,
 }
 
 impl Verbosity {
@@ -121,3 +149,34 @@ fn verify_app() {
     use clap::CommandFactory;
     Command::command().debug_assert()
 }
+
+#[test]
+fn quiet_levels() {
+    let level = |args: &[&str]| {
+        let Command::Release(opt) = Command::parse_from(["cargo", "release"].iter().chain(args));
+        opt.logging.log_level()
+    };
+    assert_eq!(level(&[]), log::Level::Info);
+    assert_eq!(level(&["-q"]), log::Level::Warn);
+    assert_eq!(level(&["-qq"]), log::Level::Error);
+    assert_eq!(level(&["-q", "-v"]), log::Level::Info);
+}
+
+#[test]
+fn completions() {
+    use clap::CommandFactory;
+    use clap::ValueEnum;
+
+    for shell in clap_complete::Shell::value_variants() {
+        let mut script = Vec::new();
+        clap_complete::generate(
+            *shell,
+            &mut Command::command(),
+            "cargo-release",
+            &mut script,
+        );
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("execute"), "{}", shell);
+        assert!(script.contains("tag-prefix"), "{}", shell);
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
@@ -15,7 +16,13 @@ pub struct Config {
     pub sign_commit: Option<bool>,
     pub sign_tag: Option<bool>,
     pub push_remote: Option<String>,
+    pub push_branch: Option<String>,
+    pub force_push: Option<bool>,
+    pub github_release: Option<bool>,
+    pub changelog: Option<bool>,
+    pub changelog_path: Option<PathBuf>,
     pub registry: Option<String>,
+    pub allowed_registries: Option<Vec<String>>,
     pub release: Option<bool>,
     pub publish: Option<bool>,
     pub verify: Option<bool>,
@@ -23,22 +30,69 @@ pub struct Config {
     pub push_options: Option<Vec<String>>,
     pub dev_version_ext: Option<String>,
     pub dev_version: Option<bool>,
+    pub dev_version_level: Option<DevVersionLevel>,
+    pub dev_version_commit: Option<bool>,
+    pub squash_dev_version: Option<bool>,
     pub shared_version: Option<bool>,
     pub consolidate_commits: Option<bool>,
     pub consolidate_pushes: Option<bool>,
     pub pre_release_commit_message: Option<String>,
     pub post_release_commit_message: Option<String>,
     pub pre_release_replacements: Option<Vec<Replace>>,
+    pub auto_update: Option<Vec<AutoUpdate>>,
     pub post_release_replacements: Option<Vec<Replace>>,
     pub pre_release_hook: Option<Command>,
+    pub pre_release_hook_dir: Option<String>,
+    pub workspace_pre_release_hook: Option<Command>,
+    pub workspace_post_release_hook: Option<Command>,
     pub tag_message: Option<String>,
     pub tag_prefix: Option<String>,
     pub tag_name: Option<String>,
+    pub template_delimiters: Option<(String, String)>,
     pub tag: Option<bool>,
+    pub allow_existing_tag: Option<bool>,
+    #[serde(alias = "publish-features")]
     pub enable_features: Option<Vec<String>>,
+    #[serde(alias = "publish-all-features")]
     pub enable_all_features: Option<bool>,
+    #[serde(alias = "publish-no-default-features")]
+    pub no_default_features: Option<bool>,
     pub dependent_version: Option<DependentVersion>,
     pub target: Option<String>,
+    pub package_only: Option<bool>,
+    pub package_dir: Option<PathBuf>,
+    pub publish_concurrency: Option<usize>,
+    pub jobs: Option<usize>,
+    pub publish_wait: Option<PublishWait>,
+    pub after_publish_wait_for_deps: Option<bool>,
+    pub dependency_features: Option<DependencyFeatures>,
+    pub verify_published_checksum: Option<bool>,
+    pub changed_since_ignore: Option<Vec<String>>,
+    pub version_edit_allowlist: Option<Vec<String>>,
+    pub skip_dependency_bumps: Option<bool>,
+    pub prerelease_identifier: Option<String>,
+    #[serde(rename = "pre-1.0-breaking")]
+    pub pre_1_0_breaking: Option<BreakingLevel>,
+    pub cargo_bin: Option<String>,
+    pub include_prereleases: Option<bool>,
+    pub amend: Option<bool>,
+    pub hook_env_file: Option<PathBuf>,
+    pub hook_timeout: Option<u64>,
+    pub index_freshness: Option<u64>,
+    pub release_manifest: Option<PathBuf>,
+    pub hook_plan_env: Option<bool>,
+    pub edition_check: Option<bool>,
+    pub placeholder_check: Option<bool>,
+    pub repository_check: Option<bool>,
+    pub registry_newer_check: Option<bool>,
+    pub check_submodules: Option<bool>,
+    pub allow_dirty_lockfile: Option<bool>,
+    pub semver_check: Option<bool>,
+    pub verify_features: Option<VerifyFeatures>,
+    pub verify_commands: Option<VerifyCommands>,
+    pub fail_if_behind: Option<bool>,
+    pub tag_before_publish: Option<bool>,
+    pub lock_only: Option<bool>,
 }
 
 impl Config {
@@ -59,7 +113,13 @@ impl Config {
             sign_commit: Some(empty.sign_commit()),
             sign_tag: Some(empty.sign_tag()),
             push_remote: Some(empty.push_remote().to_owned()),
+            push_branch: None,
+            force_push: Some(empty.force_push()),
+            github_release: Some(empty.github_release()),
+            changelog: Some(empty.changelog()),
+            changelog_path: None,
             registry: empty.registry().map(|s| s.to_owned()),
+            allowed_registries: None,
             release: Some(empty.release()),
             publish: Some(empty.publish()),
             verify: Some(empty.verify()),
@@ -72,22 +132,66 @@ impl Config {
             ),
             dev_version_ext: Some(empty.dev_version_ext().to_owned()),
             dev_version: Some(empty.dev_version()),
+            dev_version_level: Some(empty.dev_version_level()),
+            dev_version_commit: Some(empty.dev_version_commit()),
+            squash_dev_version: Some(empty.squash_dev_version()),
             shared_version: Some(empty.shared_version()),
             consolidate_commits: Some(empty.consolidate_commits()),
             consolidate_pushes: Some(empty.consolidate_pushes()),
-            pre_release_commit_message: Some(empty.pre_release_commit_message().to_owned()),
-            post_release_commit_message: Some(empty.post_release_commit_message().to_owned()),
+            pre_release_commit_message: Some(empty.pre_release_commit_message().into_owned()),
+            post_release_commit_message: Some(empty.post_release_commit_message().into_owned()),
             pre_release_replacements: Some(empty.pre_release_replacements().to_vec()),
+            auto_update: Some(empty.auto_update().to_vec()),
             post_release_replacements: Some(empty.post_release_replacements().to_vec()),
             pre_release_hook: empty.pre_release_hook().cloned(),
-            tag_message: Some(empty.tag_message().to_owned()),
+            pre_release_hook_dir: empty.pre_release_hook_dir().map(|s| s.to_owned()),
+            workspace_pre_release_hook: empty.workspace_pre_release_hook().cloned(),
+            workspace_post_release_hook: empty.workspace_post_release_hook().cloned(),
+            tag_message: Some(empty.tag_message().into_owned()),
             tag_prefix: None, // Skipping, its location dependent
-            tag_name: Some(empty.tag_name().to_owned()),
+            tag_name: Some(empty.tag_name().into_owned()),
+            template_delimiters: Some(empty.template_delimiters())
+                .map(|(open, close)| (open.to_owned(), close.to_owned())),
             tag: Some(empty.tag()),
+            allow_existing_tag: Some(empty.allow_existing_tag()),
             enable_features: Some(empty.enable_features().to_vec()),
             enable_all_features: Some(empty.enable_all_features()),
+            no_default_features: Some(empty.no_default_features()),
             dependent_version: Some(empty.dependent_version()),
             target: None,
+            package_only: Some(empty.package_only()),
+            package_dir: None,
+            publish_concurrency: Some(empty.publish_concurrency()),
+            jobs: None, // Skipping, its machine dependent
+            publish_wait: Some(empty.publish_wait()),
+            after_publish_wait_for_deps: Some(empty.after_publish_wait_for_deps()),
+            dependency_features: Some(empty.dependency_features()),
+            verify_published_checksum: Some(empty.verify_published_checksum()),
+            changed_since_ignore: Some(empty.changed_since_ignore().to_vec()),
+            version_edit_allowlist: None,
+            skip_dependency_bumps: Some(empty.skip_dependency_bumps()),
+            prerelease_identifier: Some(empty.prerelease_identifier().to_owned()),
+            pre_1_0_breaking: Some(empty.pre_1_0_breaking()),
+            cargo_bin: None, // Skipping, its environment dependent
+            include_prereleases: Some(empty.include_prereleases()),
+            amend: Some(empty.amend()),
+            hook_env_file: None,
+            hook_timeout: None,
+            index_freshness: None,
+            release_manifest: None,
+            hook_plan_env: Some(empty.hook_plan_env()),
+            edition_check: Some(empty.edition_check()),
+            placeholder_check: Some(empty.placeholder_check()),
+            repository_check: Some(empty.repository_check()),
+            registry_newer_check: Some(empty.registry_newer_check()),
+            check_submodules: Some(empty.check_submodules()),
+            allow_dirty_lockfile: Some(empty.allow_dirty_lockfile()),
+            semver_check: Some(empty.semver_check()),
+            verify_features: Some(empty.verify_features().clone()),
+            verify_commands: Some(empty.verify_commands().clone()),
+            fail_if_behind: Some(empty.fail_if_behind()),
+            tag_before_publish: Some(empty.tag_before_publish()),
+            lock_only: Some(empty.lock_only()),
         }
     }
 
@@ -104,9 +208,27 @@ impl Config {
         if let Some(push_remote) = source.push_remote.as_deref() {
             self.push_remote = Some(push_remote.to_owned());
         }
+        if let Some(push_branch) = source.push_branch.as_deref() {
+            self.push_branch = Some(push_branch.to_owned());
+        }
+        if let Some(force_push) = source.force_push {
+            self.force_push = Some(force_push);
+        }
+        if let Some(github_release) = source.github_release {
+            self.github_release = Some(github_release);
+        }
+        if let Some(changelog) = source.changelog {
+            self.changelog = Some(changelog);
+        }
+        if let Some(changelog_path) = source.changelog_path.as_deref() {
+            self.changelog_path = Some(changelog_path.to_owned());
+        }
         if let Some(registry) = source.registry.as_deref() {
             self.registry = Some(registry.to_owned());
         }
+        if let Some(allowed_registries) = source.allowed_registries.as_deref() {
+            self.allowed_registries = Some(allowed_registries.to_owned());
+        }
         if let Some(release) = source.release {
             self.release = Some(release);
         }
@@ -128,6 +250,15 @@ impl Config {
         if let Some(dev_version) = source.dev_version {
             self.dev_version = Some(dev_version);
         }
+        if let Some(dev_version_level) = source.dev_version_level {
+            self.dev_version_level = Some(dev_version_level);
+        }
+        if let Some(dev_version_commit) = source.dev_version_commit {
+            self.dev_version_commit = Some(dev_version_commit);
+        }
+        if let Some(squash_dev_version) = source.squash_dev_version {
+            self.squash_dev_version = Some(squash_dev_version);
+        }
         if let Some(shared_version) = source.shared_version {
             self.shared_version = Some(shared_version);
         }
@@ -146,12 +277,24 @@ impl Config {
         if let Some(pre_release_replacements) = source.pre_release_replacements.as_deref() {
             self.pre_release_replacements = Some(pre_release_replacements.to_owned());
         }
+        if let Some(auto_update) = source.auto_update.as_deref() {
+            self.auto_update = Some(auto_update.to_owned());
+        }
         if let Some(post_release_replacements) = source.post_release_replacements.as_deref() {
             self.post_release_replacements = Some(post_release_replacements.to_owned());
         }
         if let Some(pre_release_hook) = source.pre_release_hook.as_ref() {
             self.pre_release_hook = Some(pre_release_hook.to_owned());
         }
+        if let Some(pre_release_hook_dir) = source.pre_release_hook_dir.as_deref() {
+            self.pre_release_hook_dir = Some(pre_release_hook_dir.to_owned());
+        }
+        if let Some(workspace_pre_release_hook) = source.workspace_pre_release_hook.as_ref() {
+            self.workspace_pre_release_hook = Some(workspace_pre_release_hook.to_owned());
+        }
+        if let Some(workspace_post_release_hook) = source.workspace_post_release_hook.as_ref() {
+            self.workspace_post_release_hook = Some(workspace_post_release_hook.to_owned());
+        }
         if let Some(tag_message) = source.tag_message.as_deref() {
             self.tag_message = Some(tag_message.to_owned());
         }
@@ -161,21 +304,169 @@ impl Config {
         if let Some(tag_name) = source.tag_name.as_deref() {
             self.tag_name = Some(tag_name.to_owned());
         }
+        if let Some(template_delimiters) = source.template_delimiters.as_ref() {
+            self.template_delimiters = Some(template_delimiters.to_owned());
+        }
         if let Some(tag) = source.tag {
             self.tag = Some(tag);
         }
+        if let Some(allow_existing_tag) = source.allow_existing_tag {
+            self.allow_existing_tag = Some(allow_existing_tag);
+        }
         if let Some(enable_features) = source.enable_features.as_deref() {
             self.enable_features = Some(enable_features.to_owned());
         }
         if let Some(enable_all_features) = source.enable_all_features {
             self.enable_all_features = Some(enable_all_features);
         }
+        if let Some(no_default_features) = source.no_default_features {
+            self.no_default_features = Some(no_default_features);
+        }
         if let Some(dependent_version) = source.dependent_version {
             self.dependent_version = Some(dependent_version);
         }
         if let Some(target) = source.target.as_deref() {
             self.target = Some(target.to_owned());
         }
+        if let Some(package_only) = source.package_only {
+            self.package_only = Some(package_only);
+        }
+        if let Some(package_dir) = source.package_dir.as_deref() {
+            self.package_dir = Some(package_dir.to_owned());
+        }
+        if let Some(publish_concurrency) = source.publish_concurrency {
+            self.publish_concurrency = Some(publish_concurrency);
+        }
+        if let Some(jobs) = source.jobs {
+            self.jobs = Some(jobs);
+        }
+        if let Some(publish_wait) = source.publish_wait {
+            self.publish_wait = Some(publish_wait);
+        }
+        if let Some(after_publish_wait_for_deps) = source.after_publish_wait_for_deps {
+            self.after_publish_wait_for_deps = Some(after_publish_wait_for_deps);
+        }
+        if let Some(dependency_features) = source.dependency_features {
+            self.dependency_features = Some(dependency_features);
+        }
+        if let Some(verify_published_checksum) = source.verify_published_checksum {
+            self.verify_published_checksum = Some(verify_published_checksum);
+        }
+        if let Some(changed_since_ignore) = source.changed_since_ignore.as_deref() {
+            self.changed_since_ignore = Some(changed_since_ignore.to_owned());
+        }
+        if let Some(version_edit_allowlist) = source.version_edit_allowlist.as_deref() {
+            self.version_edit_allowlist = Some(version_edit_allowlist.to_owned());
+        }
+        if let Some(skip_dependency_bumps) = source.skip_dependency_bumps {
+            self.skip_dependency_bumps = Some(skip_dependency_bumps);
+        }
+        if let Some(prerelease_identifier) = source.prerelease_identifier.as_deref() {
+            self.prerelease_identifier = Some(prerelease_identifier.to_owned());
+        }
+        if let Some(pre_1_0_breaking) = source.pre_1_0_breaking {
+            self.pre_1_0_breaking = Some(pre_1_0_breaking);
+        }
+        if let Some(cargo_bin) = source.cargo_bin.as_deref() {
+            self.cargo_bin = Some(cargo_bin.to_owned());
+        }
+        if let Some(include_prereleases) = source.include_prereleases {
+            self.include_prereleases = Some(include_prereleases);
+        }
+        if let Some(amend) = source.amend {
+            self.amend = Some(amend);
+        }
+        if let Some(hook_env_file) = source.hook_env_file.as_deref() {
+            self.hook_env_file = Some(hook_env_file.to_owned());
+        }
+        if let Some(hook_timeout) = source.hook_timeout {
+            self.hook_timeout = Some(hook_timeout);
+        }
+        if let Some(index_freshness) = source.index_freshness {
+            self.index_freshness = Some(index_freshness);
+        }
+        if let Some(release_manifest) = source.release_manifest.as_deref() {
+            self.release_manifest = Some(release_manifest.to_owned());
+        }
+        if let Some(hook_plan_env) = source.hook_plan_env {
+            self.hook_plan_env = Some(hook_plan_env);
+        }
+        if let Some(edition_check) = source.edition_check {
+            self.edition_check = Some(edition_check);
+        }
+        if let Some(placeholder_check) = source.placeholder_check {
+            self.placeholder_check = Some(placeholder_check);
+        }
+        if let Some(repository_check) = source.repository_check {
+            self.repository_check = Some(repository_check);
+        }
+        if let Some(registry_newer_check) = source.registry_newer_check {
+            self.registry_newer_check = Some(registry_newer_check);
+        }
+        if let Some(check_submodules) = source.check_submodules {
+            self.check_submodules = Some(check_submodules);
+        }
+        if let Some(allow_dirty_lockfile) = source.allow_dirty_lockfile {
+            self.allow_dirty_lockfile = Some(allow_dirty_lockfile);
+        }
+        if let Some(semver_check) = source.semver_check {
+            self.semver_check = Some(semver_check);
+        }
+        if let Some(verify_features) = source.verify_features.as_ref() {
+            self.verify_features = Some(verify_features.clone());
+        }
+        if let Some(verify_commands) = source.verify_commands.as_ref() {
+            self.verify_commands = Some(verify_commands.clone());
+        }
+        if let Some(fail_if_behind) = source.fail_if_behind {
+            self.fail_if_behind = Some(fail_if_behind);
+        }
+        if let Some(tag_before_publish) = source.tag_before_publish {
+            self.tag_before_publish = Some(tag_before_publish);
+        }
+        if let Some(lock_only) = source.lock_only {
+            self.lock_only = Some(lock_only);
+        }
+    }
+
+    /// Expand `${VAR}` and `${VAR:-default}` in the fields that support it
+    ///
+    /// Those are `registry`, `push-remote`, the commit and tag messages, and the hooks and
+    /// `pre-release-hook-dir`.  Replacements are left alone since `${1}` there refers to a
+    /// capture group.
+    pub fn expand_env(
+        &mut self,
+        var: impl Fn(&str) -> Result<String, std::env::VarError>,
+    ) -> Result<(), FatalError> {
+        let var = &var;
+        let expand = |value: &mut Option<String>| -> Result<(), FatalError> {
+            if let Some(value) = value.as_mut() {
+                *value = expand_env(value, var)?;
+            }
+            Ok(())
+        };
+        expand(&mut self.registry)?;
+        expand(&mut self.push_remote)?;
+        expand(&mut self.pre_release_commit_message)?;
+        expand(&mut self.post_release_commit_message)?;
+        expand(&mut self.tag_message)?;
+        expand(&mut self.pre_release_hook_dir)?;
+        for hook in [
+            &mut self.pre_release_hook,
+            &mut self.workspace_pre_release_hook,
+            &mut self.workspace_post_release_hook,
+        ] {
+            match hook {
+                Some(Command::Line(line)) => *line = expand_env(line, var)?,
+                Some(Command::Args(args)) => {
+                    for arg in args {
+                        *arg = expand_env(arg, var)?;
+                    }
+                }
+                None => {}
+            }
+        }
+        Ok(())
     }
 
     pub fn allow_branch(&self) -> impl Iterator<Item = &str> {
@@ -197,16 +488,49 @@ impl Config {
         self.push_remote.as_deref().unwrap_or("origin")
     }
 
+    /// Remote branch to push to, when different from the current branch
+    pub fn push_branch(&self) -> Option<&str> {
+        self.push_branch.as_deref()
+    }
+
+    pub fn force_push(&self) -> bool {
+        self.force_push.unwrap_or(false)
+    }
+
+    pub fn github_release(&self) -> bool {
+        self.github_release.unwrap_or(false)
+    }
+
+    pub fn changelog(&self) -> bool {
+        self.changelog.unwrap_or(true)
+    }
+
+    pub fn changelog_path(&self) -> Option<&Path> {
+        self.changelog_path.as_deref()
+    }
+
     pub fn registry(&self) -> Option<&str> {
         self.registry.as_deref()
     }
 
+    pub fn allowed_registries(&self) -> Option<&[String]> {
+        self.allowed_registries.as_deref()
+    }
+
+    /// Whether `allowed-registries` permits publishing to `registry`, with `crates-io` standing for
+    /// the default registry
+    pub fn is_registry_allowed(&self) -> bool {
+        let registry = self.registry().unwrap_or("crates-io");
+        self.allowed_registries()
+            .map_or(true, |allowed| allowed.iter().any(|r| r == registry))
+    }
+
     pub fn release(&self) -> bool {
         self.release.unwrap_or(true)
     }
 
     pub fn publish(&self) -> bool {
-        self.publish.unwrap_or(true)
+        self.publish.unwrap_or(true) && !self.lock_only()
     }
 
     pub fn verify(&self) -> bool {
@@ -232,6 +556,18 @@ impl Config {
         self.dev_version.unwrap_or(false)
     }
 
+    pub fn dev_version_level(&self) -> DevVersionLevel {
+        self.dev_version_level.unwrap_or_default()
+    }
+
+    pub fn dev_version_commit(&self) -> bool {
+        self.dev_version_commit.unwrap_or(true)
+    }
+
+    pub fn squash_dev_version(&self) -> bool {
+        self.squash_dev_version.unwrap_or(false)
+    }
+
     pub fn shared_version(&self) -> bool {
         self.shared_version.unwrap_or(false)
     }
@@ -244,28 +580,20 @@ impl Config {
         self.consolidate_pushes.unwrap_or(self.is_workspace)
     }
 
-    pub fn pre_release_commit_message(&self) -> &str {
-        self.pre_release_commit_message
-            .as_deref()
-            .unwrap_or_else(|| {
-                if self.consolidate_commits() {
-                    "chore: Release"
-                } else {
-                    "chore: Release {{crate_name}} version {{version}}"
-                }
-            })
+    pub fn pre_release_commit_message(&self) -> Cow<'_, str> {
+        match self.pre_release_commit_message.as_deref() {
+            Some(message) => Cow::Borrowed(message),
+            None if self.consolidate_commits() => Cow::Borrowed("chore: Release"),
+            None => self.default_template("chore: Release {{crate_name}} version {{version}}"),
+        }
     }
 
-    pub fn post_release_commit_message(&self) -> &str {
-        self.post_release_commit_message
-            .as_deref()
-            .unwrap_or_else(|| {
-                if self.consolidate_commits() {
-                    "chore: Start development"
-                } else {
-                    "chore: Start development of {{next_version}}"
-                }
-            })
+    pub fn post_release_commit_message(&self) -> Cow<'_, str> {
+        match self.post_release_commit_message.as_deref() {
+            Some(message) => Cow::Borrowed(message),
+            None if self.consolidate_commits() => Cow::Borrowed("chore: Start development"),
+            None => self.default_template("chore: Start development of {{next_version}}"),
+        }
     }
 
     pub fn pre_release_replacements(&self) -> &[Replace] {
@@ -275,6 +603,50 @@ impl Config {
             .unwrap_or(&[])
     }
 
+    pub fn auto_update(&self) -> &[AutoUpdate] {
+        self.auto_update.as_deref().unwrap_or(&[])
+    }
+
+    /// The replacement `auto_update` stands for in `file`, for the crate `crate_name`
+    pub fn auto_update_replacement(
+        &self,
+        auto_update: AutoUpdate,
+        crate_name: &str,
+        file: PathBuf,
+    ) -> Replace {
+        const VERSION: &str = r#"[0-9]+\.[0-9]+\.[0-9]+(?:-[0-9A-Za-z.-]+)?""#;
+        let name = regex::escape(crate_name);
+        let (search, prerelease) = match auto_update {
+            AutoUpdate::Readme => (
+                format!(
+                    r#"^(\s*{}\s*=\s*(?:\{{[^}}\n]*\bversion\s*=\s*)?"){}"#,
+                    name, VERSION
+                ),
+                ReplacePrerelease::Never,
+            ),
+            AutoUpdate::HtmlRootUrl => (
+                format!(
+                    r#"(#!\[doc\(html_root_url\s*=\s*"https://docs\.rs/{}/){}"#,
+                    name, VERSION
+                ),
+                ReplacePrerelease::Always,
+            ),
+        };
+        Replace {
+            file,
+            search: Some(search),
+            replace: Some(format!(
+                r#"${{1}}{}""#,
+                self.default_template("{{version}}")
+            )),
+            rename: None,
+            min: Some(0),
+            max: None,
+            exactly: None,
+            prerelease,
+        }
+    }
+
     pub fn post_release_replacements(&self) -> &[Replace] {
         self.post_release_replacements
             .as_ref()
@@ -286,27 +658,64 @@ impl Config {
         self.pre_release_hook.as_ref()
     }
 
-    pub fn tag_message(&self) -> &str {
-        self.tag_message
-            .as_deref()
-            .unwrap_or("chore: Release {{crate_name}} version {{version}}")
+    pub fn pre_release_hook_dir(&self) -> Option<&str> {
+        self.pre_release_hook_dir.as_deref()
     }
 
-    pub fn tag_prefix(&self, is_root: bool) -> &str {
-        // crate_name as default tag prefix for multi-crate project
-        self.tag_prefix
-            .as_deref()
-            .unwrap_or(if !is_root { "{{crate_name}}-" } else { "" })
+    pub fn workspace_pre_release_hook(&self) -> Option<&Command> {
+        self.workspace_pre_release_hook.as_ref()
+    }
+
+    pub fn workspace_post_release_hook(&self) -> Option<&Command> {
+        self.workspace_post_release_hook.as_ref()
+    }
+
+    pub fn tag_message(&self) -> Cow<'_, str> {
+        match self.tag_message.as_deref() {
+            Some(message) => Cow::Borrowed(message),
+            None => self.default_template("chore: Release {{crate_name}} version {{version}}"),
+        }
     }
 
-    pub fn tag_name(&self) -> &str {
-        self.tag_name.as_deref().unwrap_or("{{prefix}}v{{version}}")
+    pub fn tag_prefix(&self, is_root: bool) -> Cow<'_, str> {
+        match self.tag_prefix.as_deref() {
+            Some(prefix) => Cow::Borrowed(prefix),
+            // crate_name as default tag prefix for multi-crate project
+            None if !is_root => self.default_template("{{crate_name}}-"),
+            None => Cow::Borrowed(""),
+        }
+    }
+
+    pub fn tag_name(&self) -> Cow<'_, str> {
+        match self.tag_name.as_deref() {
+            Some(name) => Cow::Borrowed(name),
+            None => self.default_template("{{prefix}}v{{version}}"),
+        }
+    }
+
+    pub fn template_delimiters(&self) -> (&str, &str) {
+        self.template_delimiters
+            .as_ref()
+            .map(|(open, close)| (open.as_str(), close.as_str()))
+            .unwrap_or(("{{", "}}"))
+    }
+
+    /// A default `template`, written with `{{ }}`, in the configured delimiters
+    fn default_template(&self, template: &'static str) -> Cow<'static, str> {
+        match self.template_delimiters() {
+            ("{{", "}}") => Cow::Borrowed(template),
+            (open, close) => Cow::Owned(template.replace("{{", open).replace("}}", close)),
+        }
     }
 
     pub fn tag(&self) -> bool {
         self.tag.unwrap_or(true)
     }
 
+    pub fn allow_existing_tag(&self) -> bool {
+        self.allow_existing_tag.unwrap_or(false)
+    }
+
     pub fn enable_features(&self) -> &[String] {
         self.enable_features
             .as_ref()
@@ -318,6 +727,10 @@ impl Config {
         self.enable_all_features.unwrap_or(false)
     }
 
+    pub fn no_default_features(&self) -> bool {
+        self.no_default_features.unwrap_or(false)
+    }
+
     pub fn features(&self) -> cargo::Features {
         if self.enable_all_features() {
             cargo::Features::All
@@ -334,19 +747,228 @@ impl Config {
     pub fn dependent_version(&self) -> DependentVersion {
         self.dependent_version.unwrap_or_default()
     }
+
+    pub fn package_only(&self) -> bool {
+        self.package_only.unwrap_or(false)
+    }
+
+    pub fn package_dir(&self) -> Option<&Path> {
+        self.package_dir.as_deref()
+    }
+
+    pub fn publish_concurrency(&self) -> usize {
+        self.publish_concurrency.unwrap_or(1).max(1)
+    }
+
+    /// Upper bound on parallel work, defaulting to the number of CPUs
+    pub fn jobs(&self) -> usize {
+        self.jobs
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1)
+            })
+            .max(1)
+    }
+
+    pub fn publish_wait(&self) -> PublishWait {
+        self.publish_wait.unwrap_or_default()
+    }
+
+    pub fn after_publish_wait_for_deps(&self) -> bool {
+        self.after_publish_wait_for_deps.unwrap_or(false)
+    }
+
+    pub fn dependency_features(&self) -> DependencyFeatures {
+        self.dependency_features.unwrap_or_default()
+    }
+
+    pub fn verify_published_checksum(&self) -> bool {
+        self.verify_published_checksum.unwrap_or(false)
+    }
+
+    pub fn skip_dependency_bumps(&self) -> bool {
+        self.skip_dependency_bumps.unwrap_or(false)
+    }
+
+    pub fn changed_since_ignore(&self) -> &[String] {
+        self.changed_since_ignore
+            .as_ref()
+            .map(|v| v.as_ref())
+            .unwrap_or(&[])
+    }
+
+    pub fn version_edit_allowlist(&self) -> Option<&[String]> {
+        self.version_edit_allowlist.as_deref()
+    }
+
+    pub fn prerelease_identifier(&self) -> &str {
+        self.prerelease_identifier.as_deref().unwrap_or("alpha")
+    }
+
+    pub fn pre_1_0_breaking(&self) -> BreakingLevel {
+        self.pre_1_0_breaking.unwrap_or_default()
+    }
+
+    pub fn cargo_bin(&self) -> Option<&str> {
+        self.cargo_bin.as_deref()
+    }
+
+    pub fn include_prereleases(&self) -> bool {
+        self.include_prereleases.unwrap_or(false)
+    }
+
+    pub fn amend(&self) -> bool {
+        self.amend.unwrap_or(false)
+    }
+
+    pub fn hook_env_file(&self) -> Option<&Path> {
+        self.hook_env_file.as_deref()
+    }
+
+    pub fn hook_timeout(&self) -> Option<std::time::Duration> {
+        self.hook_timeout.map(std::time::Duration::from_secs)
+    }
+
+    pub fn index_freshness(&self) -> Option<std::time::Duration> {
+        self.index_freshness.map(std::time::Duration::from_secs)
+    }
+
+    pub fn release_manifest(&self) -> Option<&Path> {
+        self.release_manifest.as_deref()
+    }
+
+    pub fn hook_plan_env(&self) -> bool {
+        self.hook_plan_env.unwrap_or(false)
+    }
+
+    pub fn edition_check(&self) -> bool {
+        self.edition_check.unwrap_or(false)
+    }
+
+    pub fn placeholder_check(&self) -> bool {
+        self.placeholder_check.unwrap_or(false)
+    }
+
+    pub fn repository_check(&self) -> bool {
+        self.repository_check.unwrap_or(false)
+    }
+
+    pub fn registry_newer_check(&self) -> bool {
+        self.registry_newer_check.unwrap_or(true)
+    }
+
+    pub fn check_submodules(&self) -> bool {
+        self.check_submodules.unwrap_or(false)
+    }
+
+    pub fn allow_dirty_lockfile(&self) -> bool {
+        self.allow_dirty_lockfile.unwrap_or(false)
+    }
+
+    pub fn semver_check(&self) -> bool {
+        self.semver_check.unwrap_or(false)
+    }
+
+    pub fn verify_features(&self) -> &VerifyFeatures {
+        self.verify_features
+            .as_ref()
+            .unwrap_or(&VerifyFeatures::Enabled(false))
+    }
+
+    pub fn verify_commands(&self) -> &VerifyCommands {
+        self.verify_commands
+            .as_ref()
+            .unwrap_or(&VerifyCommands::Enabled(false))
+    }
+
+    pub fn fail_if_behind(&self) -> bool {
+        self.fail_if_behind.unwrap_or(false)
+    }
+
+    pub fn lock_only(&self) -> bool {
+        self.lock_only.unwrap_or(false)
+    }
+
+    pub fn tag_before_publish(&self) -> bool {
+        self.tag_before_publish.unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Replace {
     pub file: PathBuf,
-    pub search: String,
-    pub replace: String,
+    pub search: Option<String>,
+    pub replace: Option<String>,
+    /// Templated path, relative to `file`'s root, to move `file` to
+    pub rename: Option<String>,
     pub min: Option<usize>,
     pub max: Option<usize>,
     pub exactly: Option<usize>,
     #[serde(default)]
-    pub prerelease: bool,
+    pub prerelease: ReplacePrerelease,
+}
+
+/// Version strings to update without writing `pre-release-replacements` for them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AutoUpdate {
+    /// `name = "x.y.z"` and `name = { version = "x.y.z", .. }` lines in the crate's `README.md`
+    Readme,
+    /// `#![doc(html_root_url = "https://docs.rs/name/x.y.z")]` in the crate's library root
+    HtmlRootUrl,
+}
+
+/// Whether a replacement runs when releasing a pre-release
+///
+/// `true` and `false` are accepted for `always` and `never`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplacePrerelease {
+    /// Run for every release
+    Always,
+    /// Skip pre-releases
+    #[default]
+    Never,
+    /// Only run for pre-releases
+    Only,
+}
+
+impl ReplacePrerelease {
+    pub fn applies(self, prerelease: bool) -> bool {
+        match self {
+            ReplacePrerelease::Always => true,
+            ReplacePrerelease::Never => !prerelease,
+            ReplacePrerelease::Only => prerelease,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ReplacePrerelease {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "kebab-case")]
+        enum Mode {
+            Always,
+            Never,
+            Only,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Bool(bool),
+            Mode(Mode),
+        }
+
+        let mode = match Repr::deserialize(deserializer)? {
+            Repr::Bool(true) | Repr::Mode(Mode::Always) => ReplacePrerelease::Always,
+            Repr::Bool(false) | Repr::Mode(Mode::Never) => ReplacePrerelease::Never,
+            Repr::Mode(Mode::Only) => ReplacePrerelease::Only,
+        };
+        Ok(mode)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -365,20 +987,135 @@ impl Command {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+/// Feature combinations to `cargo check` before releasing
+///
+/// `true` checks `--no-default-features` and `--all-features`; a list checks each set of
+/// features on top of `--no-default-features`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VerifyFeatures {
+    Enabled(bool),
+    Sets(Vec<Vec<String>>),
+}
+
+impl VerifyFeatures {
+    pub fn feature_sets(&self) -> Vec<cargo::Features> {
+        match self {
+            VerifyFeatures::Enabled(false) => vec![],
+            VerifyFeatures::Enabled(true) => vec![cargo::Features::None, cargo::Features::All],
+            VerifyFeatures::Sets(sets) => sets
+                .iter()
+                .map(|set| {
+                    if set.is_empty() {
+                        cargo::Features::None
+                    } else {
+                        cargo::Features::Selective(set.clone())
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Commands to run from the workspace root before releasing
+///
+/// `true` runs `cargo fmt --all --check` and `cargo clippy --workspace`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VerifyCommands {
+    Enabled(bool),
+    Commands(Vec<Command>),
+}
+
+impl VerifyCommands {
+    pub fn commands(&self) -> Vec<Command> {
+        match self {
+            VerifyCommands::Enabled(false) => vec![],
+            VerifyCommands::Enabled(true) => vec![
+                Command::Args(vec![
+                    "cargo".to_owned(),
+                    "fmt".to_owned(),
+                    "--all".to_owned(),
+                    "--check".to_owned(),
+                ]),
+                Command::Args(vec![
+                    "cargo".to_owned(),
+                    "clippy".to_owned(),
+                    "--workspace".to_owned(),
+                ]),
+            ],
+            VerifyCommands::Commands(commands) => commands.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, Default)]
 #[serde(rename_all = "kebab-case")]
 #[value(rename_all = "kebab-case")]
 pub enum DependentVersion {
     Upgrade,
+    #[default]
     Fix,
     Error,
     Warn,
     Ignore,
 }
 
-impl Default for DependentVersion {
-    fn default() -> Self {
-        DependentVersion::Fix
+/// Which part of the released version the next development version increments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum DevVersionLevel {
+    /// x.0.0-<dev-version-ext>
+    Major,
+    /// x.y.0-<dev-version-ext>
+    Minor,
+    /// x.y.z-<dev-version-ext>
+    #[default]
+    Patch,
+}
+
+/// When to wait for a published crate to show up in the index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum, Default)]
+#[serde(rename_all = "kebab-case")]
+#[value(rename_all = "kebab-case")]
+pub enum PublishWait {
+    /// Wait for every crate
+    #[default]
+    Always,
+    /// Only wait for crates that other crates being published depend on
+    Dependencies,
+    /// Never wait
+    Never,
+}
+
+/// Which bump level marks a breaking change for `0.x` crates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum BreakingLevel {
+    /// Bump levels apply as-is, `major` goes to `1.0.0`
+    #[default]
+    Major,
+    /// Shift bump levels down like Cargo's compatibility rules, `major` goes to `0.(x+1).0`
+    Minor,
+}
+
+/// Which features to resolve dependencies with when ordering the release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependencyFeatures {
+    /// Enable all features, so optional dependencies are ordered too
+    #[default]
+    All,
+    /// Only the default features
+    Default,
+}
+
+impl DependencyFeatures {
+    pub fn cargo_opt(self) -> Option<cargo_metadata::CargoOpt> {
+        match self {
+            DependencyFeatures::All => Some(cargo_metadata::CargoOpt::AllFeatures),
+            DependencyFeatures::Default => None,
+        }
     }
 }
 
@@ -419,6 +1156,39 @@ struct CargoMetadata {
     release: Option<Config>,
 }
 
+/// Replace each `${VAR}` in `value` with the variable, or with `default` for `${VAR:-default}`
+/// when it isn't set
+fn expand_env(
+    value: &str,
+    var: impl Fn(&str) -> Result<String, std::env::VarError>,
+) -> Result<String, FatalError> {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        expanded.push_str(&rest[..start]);
+        let reference = &rest[start + 2..end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        match (var(name), default) {
+            (Ok(value), _) => expanded.push_str(&value),
+            (Err(std::env::VarError::NotPresent), Some(default)) => expanded.push_str(default),
+            (Err(err), _) => {
+                log::error!("Config references `${{{}}}` but {}", name, err);
+                return Err(FatalError::VarError(err));
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
 pub fn load_workspace_config(
     args: &ConfigArgs,
     ws_meta: &cargo_metadata::Metadata,
@@ -440,6 +1210,7 @@ pub fn load_workspace_config(
     }
 
     release_config.update(&args.to_config());
+    release_config.expand_env(|name| std::env::var(name))?;
     Ok(release_config)
 }
 
@@ -481,6 +1252,7 @@ pub fn load_package_config(
         release_config.publish = Some(false);
     }
 
+    release_config.expand_env(|name| std::env::var(name))?;
     Ok(release_config)
 }
 
@@ -506,6 +1278,26 @@ pub struct ConfigArgs {
     #[arg(long, overrides_with("sign_commit"), hide(true))]
     pub no_sign_commit: bool,
 
+    /// Amend the last commit with the version bump rather than creating a release commit
+    #[arg(long)]
+    pub amend: bool,
+
+    /// Fail, rather than warn, when `edition` and `rust-version` are inconsistent
+    #[arg(long)]
+    pub edition_check: bool,
+
+    /// Don't count uncommitted changes to `Cargo.lock` files as a dirty working tree
+    #[arg(long)]
+    pub allow_dirty_lockfile: bool,
+
+    /// Don't publish, instead requiring the release commit to update the committed `Cargo.lock`
+    #[arg(long)]
+    pub lock_only: bool,
+
+    /// Stop after the release commit: no publish, tag, push, or dev version
+    #[arg(long, conflicts_with = "dev_version")]
+    pub commit_only: bool,
+
     /// Specify how workspace dependencies on this crate should be handed.
     #[arg(long, value_enum)]
     pub dependent_version: Option<crate::config::DependentVersion>,
@@ -517,13 +1309,22 @@ pub struct ConfigArgs {
     /// Create dev version after release
     #[arg(long, overrides_with("no_dev_version"))]
     pub dev_version: bool,
-    #[arg(long, overrides_with("dev_version"), hide(true))]
+    /// Skip the dev version after release, overriding config
+    #[arg(long, overrides_with("dev_version"))]
     pub no_dev_version: bool,
 
+    /// Leave the dev version uncommitted in the working tree
+    #[arg(long)]
+    pub no_dev_version_commit: bool,
+
     /// Comma-separated globs of branch names a release can happen from
     #[arg(long, value_delimiter = ',')]
     pub allow_branch: Option<Vec<String>>,
 
+    /// The changelog, relative to each crate's root
+    #[arg(long, value_name = "PATH")]
+    pub changelog_path: Option<PathBuf>,
+
     #[command(flatten)]
     pub publish: PublishArgs,
 
@@ -543,12 +1344,27 @@ impl ConfigArgs {
             sign_tag: self.sign(),
             dev_version_ext: self.dev_version_ext.clone(),
             dev_version: resolve_bool_arg(self.dev_version, self.no_dev_version),
+            dev_version_commit: self.no_dev_version_commit.then_some(false),
             dependent_version: self.dependent_version,
+            amend: self.amend.then_some(true),
+            edition_check: self.edition_check.then_some(true),
+            allow_dirty_lockfile: self.allow_dirty_lockfile.then_some(true),
+            lock_only: self.lock_only.then_some(true),
+            changelog_path: self.changelog_path.clone(),
             ..Default::default()
         };
         config.update(&self.publish.to_config());
         config.update(&self.tag.to_config());
         config.update(&self.push.to_config());
+        if self.commit_only {
+            config.update(&crate::config::Config {
+                publish: Some(false),
+                tag: Some(false),
+                push: Some(false),
+                dev_version: Some(false),
+                ..Default::default()
+            });
+        }
         config
     }
 
@@ -580,13 +1396,37 @@ pub struct PublishArgs {
     #[arg(long)]
     features: Vec<String>,
 
-    /// Enable all features via `all-features`. Overrides `features`
+    /// Enable all features via `all-features`
     #[arg(long)]
     all_features: bool,
 
+    /// Do not enable the `default` feature
+    #[arg(long)]
+    no_default_features: bool,
+
     /// Build for the target triple
     #[arg(long)]
     target: Option<String>,
+
+    /// Run `cargo package` rather than `cargo publish`, leaving the `.crate` files behind
+    #[arg(long)]
+    package_only: bool,
+
+    /// Copy the `.crate` files from `--package-only` into this directory
+    #[arg(long, requires = "package_only")]
+    package_dir: Option<PathBuf>,
+
+    /// Don't wait for published crates to show up in the index
+    #[arg(long)]
+    no_wait: bool,
+
+    /// Count pre-releases when looking up the latest version in the registry
+    #[arg(long)]
+    include_prereleases: bool,
+
+    /// Number of crates to publish and wait for at once and of parallel jobs for the verification build
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
 }
 
 impl PublishArgs {
@@ -596,8 +1436,14 @@ impl PublishArgs {
             registry: self.registry.clone(),
             verify: resolve_bool_arg(self.verify, self.no_verify),
             enable_features: (!self.features.is_empty()).then(|| self.features.clone()),
-            enable_all_features: self.all_features.then(|| true),
+            enable_all_features: self.all_features.then_some(true),
+            no_default_features: self.no_default_features.then_some(true),
             target: self.target.clone(),
+            package_only: self.package_only.then_some(true),
+            package_dir: self.package_dir.clone(),
+            publish_wait: self.no_wait.then_some(PublishWait::Never),
+            include_prereleases: self.include_prereleases.then_some(true),
+            jobs: self.jobs,
             ..Default::default()
         }
     }
@@ -625,15 +1471,25 @@ pub struct TagArgs {
     /// The name of the git tag.
     #[arg(long)]
     tag_name: Option<String>,
+
+    /// Reuse a tag that already exists, as long as it points at the release commit
+    #[arg(long)]
+    allow_existing_tag: bool,
 }
 
 impl TagArgs {
+    /// `--tag-name`, if it is a literal name rather than a template opened with `open`
+    pub fn literal_tag_name(&self, open: &str) -> Option<&str> {
+        self.tag_name.as_deref().filter(|name| !name.contains(open))
+    }
+
     pub fn to_config(&self) -> crate::config::Config {
         crate::config::Config {
             tag: resolve_bool_arg(self.tag, self.no_tag),
             sign_tag: resolve_bool_arg(self.sign_tag, self.no_sign_tag),
             tag_prefix: self.tag_prefix.clone(),
             tag_name: self.tag_name.clone(),
+            allow_existing_tag: self.allow_existing_tag.then_some(true),
             ..Default::default()
         }
     }
@@ -651,6 +1507,14 @@ pub struct PushArgs {
     /// Git remote to push
     #[arg(long)]
     push_remote: Option<String>,
+
+    /// Remote branch to push the release to, instead of the current branch
+    #[arg(long)]
+    push_branch: Option<String>,
+
+    /// Allow the push to `--push-branch` to not be a fast-forward
+    #[arg(long)]
+    force: bool,
 }
 
 impl PushArgs {
@@ -658,6 +1522,8 @@ impl PushArgs {
         crate::config::Config {
             push: resolve_bool_arg(self.push, self.no_push),
             push_remote: self.push_remote.clone(),
+            push_branch: self.push_branch.clone(),
+            force_push: self.force.then_some(true),
             ..Default::default()
         }
     }
@@ -704,8 +1570,9 @@ pub fn resolve_custom_config(file_path: &Path) -> Result<Option<Config>, FatalEr
 /// This tries the following sources in order, merging the results:
 /// 1. $HOME/.release.toml
 /// 2. $HOME/.config/cargo-release/release.toml
-/// 3. $(workspace)/release.toml
-/// 3. $(workspace)/Cargo.toml
+/// 3. $(workspace)/.config/release.toml
+/// 4. $(workspace)/release.toml
+/// 5. $(workspace)/Cargo.toml
 pub fn resolve_workspace_config(workspace_root: &Path) -> Result<Config, FatalError> {
     let mut config = Config::default();
 
@@ -727,6 +1594,11 @@ pub fn resolve_workspace_config(workspace_root: &Path) -> Result<Config, FatalEr
     };
 
     // Workspace config
+    let dot_config = workspace_root.join(".config/release.toml");
+    if let Some(cfg) = get_config_from_file(&dot_config)? {
+        config.update(&cfg);
+    };
+
     let default_config = workspace_root.join("release.toml");
     let current_dir_config = get_config_from_file(&default_config)?;
     if let Some(cfg) = current_dir_config {
@@ -747,10 +1619,11 @@ pub fn resolve_workspace_config(workspace_root: &Path) -> Result<Config, FatalEr
 /// This tries the following sources in order, merging the results:
 /// 1. $HOME/.release.toml
 /// 2. $HOME/.config/release.toml
-/// 3. $(workspace)/release.toml
-/// 3. $(workspace)/Cargo.toml `workspace.metadata.release`
-/// 4. $(crate)/release.toml
-/// 5. $(crate)/Cargo.toml `package.metadata.release`
+/// 3. $(workspace)/.config/release.toml
+/// 4. $(workspace)/release.toml
+/// 5. $(workspace)/Cargo.toml `workspace.metadata.release`
+/// 6. $(crate)/release.toml
+/// 7. $(crate)/Cargo.toml `package.metadata.release`
 ///
 /// `$(crate)/Cargo.toml` is a way to differentiate configuration for the root crate and the
 /// workspace.
@@ -777,6 +1650,11 @@ pub fn resolve_config(workspace_root: &Path, manifest_path: &Path) -> Result<Con
     let crate_root = manifest_path.parent().unwrap_or_else(|| Path::new("."));
 
     // Workspace config
+    let dot_config = workspace_root.join(".config/release.toml");
+    if let Some(cfg) = get_config_from_file(&dot_config)? {
+        config.update(&cfg);
+    };
+
     if crate_root != workspace_root {
         let default_config = workspace_root.join("release.toml");
         let current_dir_config = get_config_from_file(&default_config)?;
@@ -828,4 +1706,384 @@ mod test {
             assert!(!release_config.sign_commit());
         }
     }
+
+    mod deserialize {
+        use super::*;
+
+        #[test]
+        fn publish_feature_aliases() {
+            let config: Config = toml_edit::easy::from_str(
+                r#"
+publish-features = ["foo"]
+publish-no-default-features = true
+"#,
+            )
+            .unwrap();
+            assert_eq!(config.enable_features(), ["foo".to_owned()]);
+            assert!(config.no_default_features());
+        }
+
+        #[test]
+        fn replace_prerelease() {
+            let config: Config = toml_edit::easy::from_str(
+                r#"
+pre-release-replacements = [
+  {file="a", search="a", replace="a"},
+  {file="b", search="b", replace="b", prerelease=true},
+  {file="c", search="c", replace="c", prerelease=false},
+  {file="d", search="d", replace="d", prerelease="only"},
+  {file="e", search="e", replace="e", prerelease="always"},
+]
+"#,
+            )
+            .unwrap();
+            let modes: Vec<_> = config
+                .pre_release_replacements()
+                .iter()
+                .map(|r| r.prerelease)
+                .collect();
+            assert_eq!(
+                modes,
+                [
+                    ReplacePrerelease::Never,
+                    ReplacePrerelease::Always,
+                    ReplacePrerelease::Never,
+                    ReplacePrerelease::Only,
+                    ReplacePrerelease::Always,
+                ]
+            );
+        }
+    }
+
+    mod expand_env {
+        use super::*;
+
+        fn var(name: &str) -> Result<String, std::env::VarError> {
+            match name {
+                "REGISTRY" => Ok("internal".to_owned()),
+                _ => Err(std::env::VarError::NotPresent),
+            }
+        }
+
+        #[test]
+        fn set() {
+            assert_eq!(
+                expand_env("to ${REGISTRY}, not $1 or {{version}}", var).unwrap(),
+                "to internal, not $1 or {{version}}"
+            );
+        }
+
+        #[test]
+        fn default() {
+            assert_eq!(
+                expand_env("${MISSING:-origin}/${REGISTRY:-other}", var).unwrap(),
+                "origin/internal"
+            );
+            assert_eq!(expand_env("${MISSING:-}", var).unwrap(), "");
+        }
+
+        #[test]
+        fn missing() {
+            assert!(matches!(
+                expand_env("${MISSING}", var),
+                Err(FatalError::VarError(std::env::VarError::NotPresent))
+            ));
+        }
+
+        #[test]
+        fn fields() {
+            let mut config: Config = toml_edit::easy::from_str(
+                r#"
+registry = "${REGISTRY}"
+pre-release-hook = ["./hook.sh", "${REGISTRY}"]
+pre-release-replacements = [{file="a", search="(a)", replace="${1}"}]
+"#,
+            )
+            .unwrap();
+            config.expand_env(var).unwrap();
+            assert_eq!(config.registry(), Some("internal"));
+            assert_eq!(
+                config.pre_release_hook().unwrap().args(),
+                ["./hook.sh", "internal"]
+            );
+            assert_eq!(
+                config.pre_release_replacements()[0].replace.as_deref(),
+                Some("${1}")
+            );
+        }
+    }
+
+    mod auto_update_replacement {
+        use super::*;
+
+        use crate::ops::replace::Template;
+
+        fn apply(auto_update: AutoUpdate, data: &str) -> String {
+            let replace = Config::default().auto_update_replacement(
+                auto_update,
+                "my-crate",
+                "README.md".into(),
+            );
+            let r = regex::RegexBuilder::new(replace.search.as_deref().unwrap())
+                .multi_line(true)
+                .build()
+                .unwrap();
+            let replacement = Template {
+                version: Some("1.3.0"),
+                ..Default::default()
+            }
+            .render_replacement(replace.replace.as_deref().unwrap());
+            r.replace_all(data, replacement.as_str()).into_owned()
+        }
+
+        #[test]
+        fn readme() {
+            let data = r#"```toml
+[dependencies]
+my-crate = "1.2.3"
+my-crate = { version = "1.2.3-rc.1", features = ["std"] }
+other-my-crate = "1.2.3"
+my-crate = "1.2"
+```
+"#;
+            let expected = r#"```toml
+[dependencies]
+my-crate = "1.3.0"
+my-crate = { version = "1.3.0", features = ["std"] }
+other-my-crate = "1.2.3"
+my-crate = "1.2"
+```
+"#;
+            assert_eq!(apply(AutoUpdate::Readme, data), expected);
+        }
+
+        #[test]
+        fn html_root_url() {
+            let data = r#"#![doc(html_root_url = "https://docs.rs/my-crate/1.2.3")]
+#![doc(html_root_url = "https://docs.rs/other/1.2.3")]
+"#;
+            let expected = r#"#![doc(html_root_url = "https://docs.rs/my-crate/1.3.0")]
+#![doc(html_root_url = "https://docs.rs/other/1.2.3")]
+"#;
+            assert_eq!(apply(AutoUpdate::HtmlRootUrl, data), expected);
+        }
+    }
+
+    mod pre_1_0_breaking {
+        use super::*;
+
+        #[test]
+        fn minor() {
+            let config: Config =
+                toml_edit::easy::from_str(r#""pre-1.0-breaking" = "minor""#).unwrap();
+            assert_eq!(config.pre_1_0_breaking(), BreakingLevel::Minor);
+            assert_eq!(Config::default().pre_1_0_breaking(), BreakingLevel::Major);
+        }
+    }
+
+    mod verify_features {
+        use super::*;
+
+        #[test]
+        fn default() {
+            let config = Config::default();
+            assert!(config.verify_features().feature_sets().is_empty());
+        }
+
+        #[test]
+        fn enabled() {
+            let config: Config = toml_edit::easy::from_str("verify-features = true").unwrap();
+            assert_eq!(
+                config.verify_features().feature_sets(),
+                [cargo::Features::None, cargo::Features::All]
+            );
+        }
+
+        #[test]
+        fn sets() {
+            let config: Config =
+                toml_edit::easy::from_str(r#"verify-features = [[], ["serde", "std"]]"#).unwrap();
+            assert_eq!(
+                config.verify_features().feature_sets(),
+                [
+                    cargo::Features::None,
+                    cargo::Features::Selective(vec!["serde".to_owned(), "std".to_owned()])
+                ]
+            );
+        }
+    }
+
+    mod is_registry_allowed {
+        use super::*;
+
+        #[test]
+        fn unrestricted() {
+            let config = Config::default();
+            assert!(config.is_registry_allowed());
+        }
+
+        #[test]
+        fn default_registry() {
+            let config: Config =
+                toml_edit::easy::from_str(r#"allowed-registries = ["internal"]"#).unwrap();
+            assert!(!config.is_registry_allowed());
+        }
+
+        #[test]
+        fn listed() {
+            let mut config: Config =
+                toml_edit::easy::from_str(r#"allowed-registries = ["internal", "crates-io"]"#)
+                    .unwrap();
+            assert!(config.is_registry_allowed());
+            config.registry = Some("internal".to_owned());
+            assert!(config.is_registry_allowed());
+            config.registry = Some("other".to_owned());
+            assert!(!config.is_registry_allowed());
+        }
+    }
+
+    mod dependency_features {
+        use super::*;
+
+        #[test]
+        fn default() {
+            let config = Config::default();
+            assert_eq!(config.dependency_features(), DependencyFeatures::All);
+            assert!(config.dependency_features().cargo_opt().is_some());
+        }
+
+        #[test]
+        fn default_features() {
+            let config: Config =
+                toml_edit::easy::from_str(r#"dependency-features = "default""#).unwrap();
+            assert_eq!(config.dependency_features(), DependencyFeatures::Default);
+            assert!(config.dependency_features().cargo_opt().is_none());
+        }
+    }
+
+    mod publish_wait {
+        use super::*;
+
+        #[test]
+        fn default() {
+            assert_eq!(Config::default().publish_wait(), PublishWait::Always);
+            assert_eq!(
+                Config::from_defaults().publish_wait,
+                Some(PublishWait::Always)
+            );
+        }
+
+        #[test]
+        fn dependencies() {
+            let config: Config =
+                toml_edit::easy::from_str(r#"publish-wait = "dependencies""#).unwrap();
+            assert_eq!(config.publish_wait(), PublishWait::Dependencies);
+        }
+    }
+
+    mod verify_commands {
+        use super::*;
+
+        #[test]
+        fn enabled() {
+            let config: Config = toml_edit::easy::from_str("verify-commands = true").unwrap();
+            let commands = config.verify_commands().commands();
+            assert_eq!(commands[0].args(), ["cargo", "fmt", "--all", "--check"]);
+            assert_eq!(commands[1].args(), ["cargo", "clippy", "--workspace"]);
+        }
+
+        #[test]
+        fn commands() {
+            let config: Config =
+                toml_edit::easy::from_str(r#"verify-commands = ["./check.sh", ["cargo", "doc"]]"#)
+                    .unwrap();
+            let commands = config.verify_commands().commands();
+            assert_eq!(commands.len(), 2);
+            assert_eq!(commands[0].args(), ["./check.sh"]);
+            assert_eq!(commands[1].args(), ["cargo", "doc"]);
+        }
+    }
+
+    mod template_delimiters {
+        use super::*;
+
+        #[test]
+        fn default() {
+            let config = Config::default();
+            assert_eq!(config.template_delimiters(), ("{{", "}}"));
+            assert_eq!(config.tag_name(), "{{prefix}}v{{version}}");
+        }
+
+        #[test]
+        fn custom() {
+            let config: Config = toml_edit::easy::from_str(
+                r#"
+template-delimiters = ["<<", ">>"]
+tag-message = "Release {{ github.ref }} <<version>>"
+"#,
+            )
+            .unwrap();
+            assert_eq!(config.template_delimiters(), ("<<", ">>"));
+            assert_eq!(config.tag_name(), "<<prefix>>v<<version>>");
+            assert_eq!(config.tag_prefix(false), "<<crate_name>>-");
+            assert_eq!(
+                config.pre_release_commit_message(),
+                "chore: Release <<crate_name>> version <<version>>"
+            );
+            assert_eq!(config.tag_message(), "Release {{ github.ref }} <<version>>");
+        }
+    }
+
+    mod commit_only {
+        use super::*;
+
+        #[test]
+        fn overrides_steps() {
+            let args = ConfigArgs {
+                commit_only: true,
+                push: PushArgs {
+                    push: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let config = args.to_config();
+            assert_eq!(config.publish, Some(false));
+            assert_eq!(config.tag, Some(false));
+            assert_eq!(config.push, Some(false));
+            assert_eq!(config.dev_version, Some(false));
+        }
+    }
+
+    mod literal_tag_name {
+        use super::*;
+
+        #[test]
+        fn literal() {
+            let args = TagArgs {
+                tag_name: Some("v1.2.3".to_owned()),
+                ..Default::default()
+            };
+            assert_eq!(args.literal_tag_name("{{"), Some("v1.2.3"));
+        }
+
+        #[test]
+        fn template() {
+            let args = TagArgs {
+                tag_name: Some("{{crate_name}}-v{{version}}".to_owned()),
+                ..Default::default()
+            };
+            assert_eq!(args.literal_tag_name("{{"), None);
+        }
+
+        #[test]
+        fn custom_delimiters() {
+            let args = TagArgs {
+                tag_name: Some("v<<version>>".to_owned()),
+                ..Default::default()
+            };
+            assert_eq!(args.literal_tag_name("<<"), None);
+            assert_eq!(args.literal_tag_name("{{"), Some("v<<version>>"));
+        }
+    }
 }
@@ -145,6 +145,9 @@ quick_error! {
         NoPackage {
             display("No package in manifest file")
         }
+        InvalidManifestPath(manifest: PathBuf) {
+            display("{} is not a path to a `Cargo.toml`", manifest.display())
+        }
         PackageListFailed(manifest: std::path::PathBuf, err: String) {
             display("Failed to get package content for {}: {}", manifest.display(), err)
         }
@@ -158,7 +161,13 @@ quick_error! {
             display("Support for modifying {} is currently unsupported", req)
         }
         ReplacerConfigError {
-            display("Insufficient replacer config: file, search and replace are required.")
+            display("Insufficient replacer config: file and either search and replace, or rename, are required.")
+        }
+        ReplacerRenameConflict(filename: PathBuf) {
+            display("{} can only be renamed once", filename.display())
+        }
+        ReplacerRenameExists(from: PathBuf, to: PathBuf) {
+            display("Unable to rename {} to {} which already exists", from.display(), to.display())
         }
         ReplacerRegexError(err: RegexError) {
             from()
@@ -179,11 +188,29 @@ quick_error! {
         GitBinError {
             display("git is not found. git is required for cargo-release workflow.")
         }
+        GitHubRemoteError(remote: String) {
+            display("Unable to find the GitHub repository for remote `{}`", remote)
+        }
+        DependencyCycle(crates: String) {
+            display("Releasing crates depend on each other ({}), so there is no order to publish them in", crates)
+        }
+        CommandTimeout(command: String, timeout: std::time::Duration) {
+            display("`{}` was killed after running for {} seconds", command, timeout.as_secs())
+        }
         PublishTimeoutError {
             display("Timeout waiting for crate to be published.")
         }
+        PartialVersion(version: String) {
+            display("`{}` is not a full version; pass one like `1.2.0`, or a requirement with an operator like `^1.2` to pick the lowest unpublished match", version)
+        }
+        UnsatisfiableVersionReq(name: String, req: String) {
+            display("No unpublished version of {} matches `{}`", name, req)
+        }
         DependencyVersionConflict {
             display("Dependency is configured to conflict with new version")
         }
+        NoIncludeMatch(pattern: String) {
+            display("`--include {}` matches no workspace member", pattern)
+        }
     }
 }
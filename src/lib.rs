@@ -4,7 +4,14 @@
 //! crates.io to distribute the binary.  If using this as a library, be sure to pin the version
 //! with a `=` version requirement operator.
 
+// `quick_error!` recurses once per `FatalError` variant, which number more than the default
+// limit of 128 allows
+#![recursion_limit = "256"]
+
 pub mod config;
 pub mod error;
 pub mod ops;
 pub mod steps;
+
+#[cfg(test)]
+mod test_support;
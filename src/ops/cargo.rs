@@ -7,6 +7,7 @@ use crate::error::FatalError;
 use crate::ops::cmd::call;
 
 /// Expresses what features flags should be used
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Features {
     /// None - don't use special features
     None,
@@ -16,19 +17,37 @@ pub enum Features {
     All,
 }
 
-fn cargo() -> String {
-    env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned())
+/// The cargo to run, preferring `$CARGO` (set when run as a cargo subcommand) over `cargo_bin`
+fn cargo(cargo_bin: Option<&str>) -> String {
+    env::var("CARGO")
+        .ok()
+        .or_else(|| cargo_bin.map(ToOwned::to_owned))
+        .unwrap_or_else(|| "cargo".to_owned())
 }
 
-pub fn package_content(manifest_path: &Path) -> Result<Vec<std::path::PathBuf>, FatalError> {
-    let mut cmd = std::process::Command::new(cargo());
+/// Catch `--manifest-path`s that `cargo metadata` would give an unclear error for
+pub fn verify_manifest_path(manifest_path: &Path) -> Result<(), FatalError> {
+    if manifest_path.file_name() != Some(std::ffi::OsStr::new("Cargo.toml"))
+        || !manifest_path.is_file()
+    {
+        return Err(FatalError::InvalidManifestPath(manifest_path.to_owned()));
+    }
+
+    Ok(())
+}
+
+pub fn package_content(
+    manifest_path: &Path,
+    cargo_bin: Option<&str>,
+) -> Result<Vec<std::path::PathBuf>, FatalError> {
+    let mut cmd = std::process::Command::new(cargo(cargo_bin));
     cmd.arg("package");
     cmd.arg("--manifest-path");
     cmd.arg(manifest_path);
     cmd.arg("--list");
     // Not worth passing around allow_dirty to here since we are just getting a file list.
     cmd.arg("--allow-dirty");
-    let output = cmd.output().map_err(FatalError::from)?;
+    let output = crate::ops::cmd::output(&mut cmd).map_err(FatalError::from)?;
 
     let parent = manifest_path
         .parent()
@@ -52,13 +71,16 @@ pub fn package_content(manifest_path: &Path) -> Result<Vec<std::path::PathBuf>,
 pub fn publish(
     dry_run: bool,
     verify: bool,
+    jobs: Option<usize>,
     manifest_path: &Path,
     pkgid: Option<&str>,
     features: &Features,
+    no_default_features: bool,
     registry: Option<&str>,
     target: Option<&str>,
+    cargo_bin: Option<&str>,
 ) -> Result<bool, FatalError> {
-    let cargo = cargo();
+    let cargo = cargo(cargo_bin);
 
     let mut command: Vec<&str> = vec![
         &cargo,
@@ -66,6 +88,9 @@ pub fn publish(
         "--manifest-path",
         manifest_path.to_str().unwrap(),
     ];
+    if crate::ops::cmd::is_quiet() {
+        command.push("--quiet");
+    }
 
     if let Some(pkgid) = pkgid {
         command.push("--package");
@@ -86,6 +111,13 @@ pub fn publish(
         command.push("--no-verify");
     }
 
+    let jobs_arg;
+    if let Some(jobs) = jobs {
+        jobs_arg = jobs.to_string();
+        command.push("--jobs");
+        command.push(&jobs_arg);
+    }
+
     if let Some(target) = target {
         command.push("--target");
         command.push(target);
@@ -103,26 +135,213 @@ pub fn publish(
             command.push("--all-features");
         }
     };
+    if no_default_features {
+        command.push("--no-default-features");
+    }
 
     call(command, false)
 }
 
-pub fn wait_for_publish(
-    index: &mut crates_index::Index,
+/// Run `cargo yank` for `version` of `name`
+pub fn yank(
     name: &str,
     version: &str,
+    registry: Option<&str>,
+    cargo_bin: Option<&str>,
+    dry_run: bool,
+) -> Result<bool, FatalError> {
+    let cargo = cargo(cargo_bin);
+
+    let mut command: Vec<&str> = vec![&cargo, "yank", name, "--version", version];
+
+    if let Some(registry) = registry {
+        command.push("--registry");
+        command.push(registry);
+    }
+
+    call(command, dry_run)
+}
+
+/// `cargo check` a package with only the given features enabled
+pub fn check(
+    manifest_path: &Path,
+    pkgid: Option<&str>,
+    features: &Features,
+    target: Option<&str>,
+    cargo_bin: Option<&str>,
+) -> Result<bool, FatalError> {
+    let cargo = cargo(cargo_bin);
+
+    let mut command: Vec<&str> = vec![
+        &cargo,
+        "check",
+        "--manifest-path",
+        manifest_path.to_str().unwrap(),
+    ];
+
+    if let Some(pkgid) = pkgid {
+        command.push("--package");
+        command.push(pkgid);
+    }
+
+    if let Some(target) = target {
+        command.push("--target");
+        command.push(target);
+    }
+
+    let feature_arg;
+    match features {
+        Features::None => {
+            command.push("--no-default-features");
+        }
+        Features::Selective(vec) => {
+            feature_arg = vec.join(" ");
+            command.push("--no-default-features");
+            command.push("--features");
+            command.push(&feature_arg);
+        }
+        Features::All => {
+            command.push("--all-features");
+        }
+    };
+
+    call(command, false)
+}
+
+/// Check the public API against the last published release with `cargo-semver-checks`
+///
+/// Returns `None` when `cargo-semver-checks` isn't installed.
+pub fn semver_checks(
+    manifest_path: &Path,
+    pkgid: Option<&str>,
+    release_type: &str,
+    cargo_bin: Option<&str>,
+) -> Result<Option<bool>, FatalError> {
+    let cargo = cargo(cargo_bin);
+
+    let installed = crate::ops::cmd::output(
+        std::process::Command::new(&cargo)
+            .arg("semver-checks")
+            .arg("--version"),
+    )
+    .map(|o| o.status.success())
+    .unwrap_or(false);
+    if !installed {
+        return Ok(None);
+    }
+
+    let mut command: Vec<&str> = vec![
+        &cargo,
+        "semver-checks",
+        "check-release",
+        "--manifest-path",
+        manifest_path.to_str().unwrap(),
+        "--release-type",
+        release_type,
+    ];
+
+    if let Some(pkgid) = pkgid {
+        command.push("--package");
+        command.push(pkgid);
+    }
+
+    call(command, false).map(Some)
+}
+
+/// Run `cargo package`, leaving the `.crate` in the target directory
+#[allow(clippy::too_many_arguments)]
+pub fn package(
+    dry_run: bool,
+    verify: bool,
+    jobs: Option<usize>,
+    manifest_path: &Path,
+    pkgid: Option<&str>,
+    features: &Features,
+    no_default_features: bool,
+    target: Option<&str>,
+    cargo_bin: Option<&str>,
+) -> Result<bool, FatalError> {
+    let cargo = cargo(cargo_bin);
+
+    let mut command: Vec<&str> = vec![
+        &cargo,
+        "package",
+        "--manifest-path",
+        manifest_path.to_str().unwrap(),
+    ];
+    if crate::ops::cmd::is_quiet() {
+        command.push("--quiet");
+    }
+
+    if let Some(pkgid) = pkgid {
+        command.push("--package");
+        command.push(pkgid);
+    }
+
+    if dry_run {
+        command.push("--allow-dirty");
+    }
+
+    if !verify {
+        command.push("--no-verify");
+    }
+
+    let jobs_arg;
+    if let Some(jobs) = jobs {
+        jobs_arg = jobs.to_string();
+        command.push("--jobs");
+        command.push(&jobs_arg);
+    }
+
+    if let Some(target) = target {
+        command.push("--target");
+        command.push(target);
+    }
+
+    let feature_arg;
+    match features {
+        Features::None => (),
+        Features::Selective(vec) => {
+            feature_arg = vec.join(" ");
+            command.push("--features");
+            command.push(&feature_arg);
+        }
+        Features::All => {
+            command.push("--all-features");
+        }
+    };
+    if no_default_features {
+        command.push("--no-default-features");
+    }
+
+    call(command, false)
+}
+
+/// Wait for every `(name, version)` of `crates` to show up in the index
+///
+/// They are looked up together, each update of the index serving all of them.  With
+/// `freshness`, the index isn't fetched again while it was fetched within that long, capped at
+/// the polling interval so the wait still sees new versions.
+pub fn wait_for_publish(
+    index: &mut crates_index::Index,
+    crates: &[(&str, &str)],
     timeout: std::time::Duration,
+    freshness: Option<std::time::Duration>,
     dry_run: bool,
 ) -> Result<(), FatalError> {
     if !dry_run {
         let now = std::time::Instant::now();
         let sleep_time = std::time::Duration::from_secs(1);
+        let freshness = freshness.map(|freshness| freshness.min(sleep_time));
         let mut logged = false;
         loop {
-            if let Err(e) = index.update() {
+            if let Err(e) = refresh_index(index, freshness) {
                 log::debug!("Crate index update failed with {}", e);
             }
-            if is_published(index, name, version) {
+            if crates
+                .iter()
+                .all(|(name, version)| is_published(index, name, version))
+            {
                 break;
             } else if timeout < now.elapsed() {
                 return Err(FatalError::PublishTimeoutError);
@@ -139,6 +358,186 @@ pub fn wait_for_publish(
     Ok(())
 }
 
+/// The SHA-256 checksum the index records for `version` of `name`, as hex
+pub fn published_checksum(
+    index: &crates_index::Index,
+    name: &str,
+    version: &str,
+) -> Option<String> {
+    let crate_data = index.crate_(name)?;
+    let published = crate_data
+        .versions()
+        .iter()
+        .find(|v| v.version() == version)?;
+    Some(
+        published
+            .checksum()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    )
+}
+
+/// The SHA-256 checksum of `path`, as hex
+pub fn file_checksum(path: &Path) -> Result<String, FatalError> {
+    use sha2::Digest;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = sha2::Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// The highest version of `name` in the index
+///
+/// Pre-releases are skipped unless `include_prereleases`, like cargo's resolver.
+pub fn max_published_version(
+    index: &crates_index::Index,
+    name: &str,
+    include_prereleases: bool,
+) -> Option<semver::Version> {
+    let crate_data = index.crate_(name)?;
+    crate_data
+        .versions()
+        .iter()
+        .filter_map(|v| semver::Version::parse(v.version()).ok())
+        .filter(|v| include_prereleases || v.pre.is_empty())
+        .max()
+}
+
+/// Every version of `name` in the index, including yanked ones which can't be reused
+pub fn published_versions(index: &crates_index::Index, name: &str) -> Vec<semver::Version> {
+    index
+        .crate_(name)
+        .map(|crate_data| {
+            crate_data
+                .versions()
+                .iter()
+                .filter_map(|v| semver::Version::parse(v.version()).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Open the crates.io index cargo uses
+///
+/// With `freshness`, the index is fetched first unless it was updated within that long.
+pub fn open_index(
+    freshness: Option<std::time::Duration>,
+) -> Result<crates_index::Index, FatalError> {
+    let mut index = crates_index::Index::new_cargo_default()?;
+    if freshness.is_some() {
+        if let Err(e) = refresh_index(&mut index, freshness) {
+            log::warn!("Crate index update failed with {}", e);
+        }
+    }
+    // `crates_index` always opens the index under `$CARGO_HOME`, where cargo keeps it
+    log::debug!("Using crates.io index at {}", index.path().display());
+    Ok(index)
+}
+
+/// Fetch `index`, unless it was fetched within `freshness`
+fn refresh_index(
+    index: &mut crates_index::Index,
+    freshness: Option<std::time::Duration>,
+) -> Result<(), crates_index::Error> {
+    let age = last_updated(index.path()).and_then(|t| t.elapsed().ok());
+    match (freshness, age) {
+        (Some(freshness), Some(age)) if age < freshness => {
+            log::debug!("Index was updated {}s ago, not updating", age.as_secs());
+            Ok(())
+        }
+        _ => {
+            log::debug!("Updating crates.io index");
+            index.update()
+        }
+    }
+}
+
+/// When the git index at `path` was last fetched, by cargo or by us
+fn last_updated(path: &Path) -> Option<std::time::SystemTime> {
+    [
+        path.join(".git").join("FETCH_HEAD"),
+        path.join(".last-updated"),
+    ]
+    .iter()
+    .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+    .max()
+}
+
+/// Look up the index URL of the alternative registry `name` the way cargo does
+///
+/// `CARGO_REGISTRIES_<NAME>_INDEX` takes precedence over `registries.<name>.index` in the cargo
+/// config files from `cwd` up, followed by the one in cargo home.
+pub fn registry_index_url(cwd: &Path, name: &str) -> Result<Option<String>, FatalError> {
+    let env_key = format!(
+        "CARGO_REGISTRIES_{}_INDEX",
+        name.to_uppercase().replace('-', "_")
+    );
+    if let Ok(url) = env::var(env_key) {
+        return Ok(Some(url));
+    }
+
+    let mut dirs: Vec<_> = cwd.ancestors().map(|d| d.join(".cargo")).collect();
+    dirs.extend(home::cargo_home().ok());
+    config_index_url(&dirs, name)
+}
+
+/// Find `registries.<name>.index` in the first of `config_dirs` that sets it
+fn config_index_url(
+    config_dirs: &[std::path::PathBuf],
+    name: &str,
+) -> Result<Option<String>, FatalError> {
+    for dir in config_dirs {
+        for file_name in ["config", "config.toml"] {
+            let path = dir.join(file_name);
+            if !path.is_file() {
+                continue;
+            }
+            let config = parse_cargo_config(&path)?;
+            let url = config
+                .get("registries")
+                .and_then(|r| r.get(name))
+                .and_then(|r| r.get("index"))
+                .and_then(|i| i.as_str());
+            if let Some(url) = url {
+                log::trace!("Found index for registry {} in {}", name, path.display());
+                return Ok(Some(url.to_owned()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Open the index of the alternative registry `name`, if it is a git index we can find
+pub fn open_registry_index(
+    cwd: &Path,
+    name: &str,
+) -> Result<Option<crates_index::Index>, FatalError> {
+    let url = match registry_index_url(cwd, name)? {
+        Some(url) => url,
+        None => {
+            log::debug!("No index configured for registry {}", name);
+            return Ok(None);
+        }
+    };
+    if url.starts_with("sparse+") {
+        log::debug!(
+            "Sparse index for registry {} is unsupported ({})",
+            name,
+            url
+        );
+        return Ok(None);
+    }
+    let index = crates_index::Index::from_url(&url)?;
+    log::debug!("Using {} index at {}", name, index.path().display());
+    Ok(Some(index))
+}
+
 pub fn is_published(index: &crates_index::Index, name: &str, version: &str) -> bool {
     let crate_data = index.crate_(name);
     crate_data
@@ -150,6 +549,7 @@ pub fn is_published(index: &crates_index::Index, name: &str, version: &str) -> b
 pub fn set_package_version(
     manifest_path: &Path,
     version: &str,
+    noisy: bool,
     dry_run: bool,
 ) -> Result<(), FatalError> {
     let original_manifest = std::fs::read_to_string(manifest_path)?;
@@ -158,24 +558,7 @@ pub fn set_package_version(
     let manifest = manifest.to_string();
 
     if dry_run {
-        if manifest != original_manifest {
-            let display_path = manifest_path.display().to_string();
-            let old_lines: Vec<_> = original_manifest
-                .lines()
-                .map(|s| format!("{}\n", s))
-                .collect();
-            let new_lines: Vec<_> = manifest.lines().map(|s| format!("{}\n", s)).collect();
-            let diff = difflib::unified_diff(
-                &old_lines,
-                &new_lines,
-                display_path.as_str(),
-                display_path.as_str(),
-                "original",
-                "updated",
-                0,
-            );
-            log::debug!("Change:\n{}", itertools::join(diff.into_iter(), ""));
-        }
+        log_manifest_diff(manifest_path, &original_manifest, &manifest, noisy);
     } else {
         atomic_write(manifest_path, &manifest)?;
     }
@@ -187,6 +570,7 @@ pub fn set_dependency_version(
     manifest_path: &Path,
     name: &str,
     version: &str,
+    noisy: bool,
     dry_run: bool,
 ) -> Result<(), FatalError> {
     let original_manifest = std::fs::read_to_string(manifest_path)?;
@@ -201,24 +585,7 @@ pub fn set_dependency_version(
     let manifest = manifest.to_string();
 
     if dry_run {
-        if manifest != original_manifest {
-            let display_path = manifest_path.display().to_string();
-            let old_lines: Vec<_> = original_manifest
-                .lines()
-                .map(|s| format!("{}\n", s))
-                .collect();
-            let new_lines: Vec<_> = manifest.lines().map(|s| format!("{}\n", s)).collect();
-            let diff = difflib::unified_diff(
-                &old_lines,
-                &new_lines,
-                display_path.as_str(),
-                display_path.as_str(),
-                "original",
-                "updated",
-                0,
-            );
-            log::debug!("Change:\n{}", itertools::join(diff.into_iter(), ""));
-        }
+        log_manifest_diff(manifest_path, &original_manifest, &manifest, noisy);
     } else {
         atomic_write(manifest_path, &manifest)?;
     }
@@ -226,6 +593,71 @@ pub fn set_dependency_version(
     Ok(())
 }
 
+/// Whether the only differences between the `old` and `new` manifests are dependency version
+/// requirements, like those written by [`set_dependency_version`]
+///
+/// With `package_version`, a change to the package's own version is also allowed.
+pub fn only_dependency_versions_changed(old: &str, new: &str, package_version: bool) -> bool {
+    fn strip(manifest: &str, package_version: bool) -> Option<String> {
+        let mut manifest: toml_edit::Document = manifest.parse().ok()?;
+        if package_version {
+            if let Some(version) = manifest
+                .get_mut("package")
+                .and_then(|p| p.as_table_like_mut())
+                .and_then(|p| p.get_mut("version"))
+            {
+                *version = toml_edit::value("");
+            }
+        }
+        for deps_table in find_dependency_tables(manifest.as_table_mut()) {
+            for (_, dep_item) in deps_table.iter_mut() {
+                if let Some(version) = dep_item
+                    .as_table_like_mut()
+                    .and_then(|dep| dep.get_mut("version"))
+                {
+                    *version = toml_edit::value("");
+                }
+            }
+        }
+        Some(manifest.to_string())
+    }
+
+    match (strip(old, package_version), strip(new, package_version)) {
+        (Some(old), Some(new)) => old == new,
+        _ => false,
+    }
+}
+
+/// Report what a dry-run would have written to `manifest_path`
+///
+/// When `noisy`, the diff is reported at `info` with surrounding context so formatting and
+/// comments around the edit can be checked.
+fn log_manifest_diff(manifest_path: &Path, original: &str, updated: &str, noisy: bool) {
+    if original == updated {
+        log::trace!("{} is unchanged", manifest_path.display());
+        return;
+    }
+
+    let display_path = manifest_path.display().to_string();
+    let old_lines: Vec<_> = original.lines().map(|s| format!("{}\n", s)).collect();
+    let new_lines: Vec<_> = updated.lines().map(|s| format!("{}\n", s)).collect();
+    let (level, context) = if noisy {
+        (log::Level::Info, 3)
+    } else {
+        (log::Level::Debug, 0)
+    };
+    let diff = difflib::unified_diff(
+        &old_lines,
+        &new_lines,
+        display_path.as_str(),
+        display_path.as_str(),
+        "original",
+        "updated",
+        context,
+    );
+    log::log!(level, "Change:\n{}", itertools::join(diff.into_iter(), ""));
+}
+
 fn find_dependency_tables(
     root: &mut toml_edit::Table,
 ) -> impl Iterator<Item = &mut dyn toml_edit::TableLike> + '_ {
@@ -284,15 +716,53 @@ fn version_item_uses_caret(version: &toml_edit::Item) -> bool {
         .unwrap_or(false)
 }
 
-pub fn update_lock(manifest_path: &Path) -> Result<(), FatalError> {
-    cargo_metadata::MetadataCommand::new()
-        .manifest_path(manifest_path)
-        .exec()
-        .map_err(FatalError::from)?;
+pub fn update_lock(manifest_path: &Path, cargo_bin: Option<&str>) -> Result<(), FatalError> {
+    let mut cmd = cargo_metadata::MetadataCommand::new();
+    cmd.cargo_path(cargo(cargo_bin))
+        .manifest_path(manifest_path);
+    crate::ops::cmd::log_command(&cmd.cargo_command());
+    cmd.exec().map_err(FatalError::from)?;
 
     Ok(())
 }
 
+/// Whether `lock_path` records `name` at `version`
+pub fn is_locked(lock_path: &Path, name: &str, version: &str) -> Result<bool, FatalError> {
+    let lock = parse_cargo_config(lock_path)?;
+    let locked = lock
+        .get("package")
+        .and_then(|p| p.as_array())
+        .map(|packages| {
+            packages.iter().any(|p| {
+                p.get("name").and_then(|n| n.as_str()) == Some(name)
+                    && p.get("version").and_then(|v| v.as_str()) == Some(version)
+            })
+        })
+        .unwrap_or(false);
+    Ok(locked)
+}
+
+/// Versions of the local (path) package `name` in the lock file
+pub fn locked_versions(lock_path: &Path, name: &str) -> Result<Vec<String>, FatalError> {
+    let lock = parse_cargo_config(lock_path)?;
+    let versions = lock
+        .get("package")
+        .and_then(|p| p.as_array())
+        .map(|packages| {
+            packages
+                .iter()
+                .filter(|p| {
+                    p.get("name").and_then(|n| n.as_str()) == Some(name)
+                        && p.get("source").is_none()
+                })
+                .filter_map(|p| p.get("version").and_then(|v| v.as_str()))
+                .map(ToOwned::to_owned)
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(versions)
+}
+
 pub fn parse_cargo_config(manifest_path: &Path) -> Result<toml_edit::easy::Value, FatalError> {
     let cargo_file_content = std::fs::read_to_string(manifest_path).map_err(FatalError::from)?;
     cargo_file_content.parse().map_err(FatalError::from)
@@ -368,6 +838,72 @@ fn sort_workspace_inner<'m>(
     sorted.push(pkg_id);
 }
 
+/// A cycle of dependencies among `ids`, listed from a crate back to itself
+///
+/// Unless `dev`, dev-dependencies are left out as they don't constrain the publish order.
+pub fn dependency_cycle<'i>(
+    ws_meta: &cargo_metadata::Metadata,
+    ids: &[&'i cargo_metadata::PackageId],
+    dev: bool,
+) -> Option<Vec<&'i cargo_metadata::PackageId>> {
+    let dep_tree: std::collections::HashMap<_, Vec<_>> = ws_meta
+        .resolve
+        .as_ref()
+        .expect("cargo-metadata resolved deps")
+        .nodes
+        .iter()
+        .filter_map(|n| {
+            let id = ids.iter().find(|id| ***id == n.id)?;
+            let deps = n
+                .deps
+                .iter()
+                .filter(|dep| {
+                    dev || !dep
+                        .dep_kinds
+                        .iter()
+                        .all(|info| info.kind == cargo_metadata::DependencyKind::Development)
+                })
+                .filter_map(|dep| ids.iter().find(|id| ***id == dep.pkg).copied())
+                .collect();
+            Some((*id, deps))
+        })
+        .collect();
+
+    let mut visited = std::collections::HashSet::new();
+    let mut path = Vec::new();
+    ids.iter()
+        .find_map(|id| dependency_cycle_inner(id, &dep_tree, &mut visited, &mut path))
+}
+
+fn dependency_cycle_inner<'i>(
+    pkg_id: &'i cargo_metadata::PackageId,
+    dep_tree: &std::collections::HashMap<
+        &'i cargo_metadata::PackageId,
+        Vec<&'i cargo_metadata::PackageId>,
+    >,
+    visited: &mut std::collections::HashSet<&'i cargo_metadata::PackageId>,
+    path: &mut Vec<&'i cargo_metadata::PackageId>,
+) -> Option<Vec<&'i cargo_metadata::PackageId>> {
+    if let Some(start) = path.iter().position(|id| *id == pkg_id) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(pkg_id);
+        return Some(cycle);
+    }
+    if !visited.insert(pkg_id) {
+        return None;
+    }
+
+    path.push(pkg_id);
+    for dep_id in dep_tree.get(pkg_id).into_iter().flatten() {
+        if let Some(cycle) = dependency_cycle_inner(dep_id, dep_tree, visited, path) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+
+    None
+}
+
 fn atomic_write(path: &Path, data: &str) -> std::io::Result<()> {
     let temp_path = path
         .parent()
@@ -387,6 +923,161 @@ mod test {
     use assert_fs::prelude::*;
     use predicates::prelude::*;
 
+    mod config_index_url {
+        use super::*;
+
+        #[test]
+        fn nearest_wins() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let inner = temp.child("inner/.cargo");
+            inner
+                .child("config.toml")
+                .write_str(
+                    r#"
+[registries.other]
+index = "https://example.com/other"
+"#,
+                )
+                .unwrap();
+            let outer = temp.child(".cargo");
+            outer
+                .child("config")
+                .write_str(
+                    r#"
+[registries.my-registry]
+index = "https://example.com/outer"
+
+[registries.other]
+index = "https://example.com/ignored"
+"#,
+                )
+                .unwrap();
+            let dirs = [inner.to_path_buf(), outer.to_path_buf()];
+
+            assert_eq!(
+                config_index_url(&dirs, "other").unwrap().as_deref(),
+                Some("https://example.com/other")
+            );
+            assert_eq!(
+                config_index_url(&dirs, "my-registry").unwrap().as_deref(),
+                Some("https://example.com/outer")
+            );
+        }
+
+        #[test]
+        fn missing() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let dirs = [temp.child(".cargo").to_path_buf()];
+            assert_eq!(config_index_url(&dirs, "my-registry").unwrap(), None);
+        }
+    }
+
+    mod last_updated {
+        use super::*;
+
+        #[test]
+        fn newest() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            assert_eq!(last_updated(temp.path()), None);
+
+            std::fs::write(temp.path().join(".last-updated"), "").unwrap();
+            let updated = last_updated(temp.path()).unwrap();
+            assert!(updated.elapsed().unwrap() < std::time::Duration::from_secs(60));
+        }
+    }
+
+    mod refresh_index {
+        use super::*;
+
+        #[test]
+        fn fresh() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut index = crate::test_support::index(temp.path(), &[("foo", "0.1.0")]);
+            std::fs::write(temp.path().join(".last-updated"), "").unwrap();
+            // Fetching from the index's made up URL would fail
+            let freshness = std::time::Duration::from_secs(60);
+            refresh_index(&mut index, Some(freshness)).unwrap();
+        }
+    }
+
+    mod wait_for_publish {
+        use super::*;
+
+        #[test]
+        fn all_published() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut index =
+                crate::test_support::index(temp.path(), &[("foo", "0.1.0"), ("bar", "0.2.0")]);
+            let timeout = std::time::Duration::from_secs(0);
+            wait_for_publish(
+                &mut index,
+                &[("foo", "0.1.0"), ("bar", "0.2.0")],
+                timeout,
+                None,
+                false,
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn fresh() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut index = crate::test_support::index(temp.path(), &[("foo", "0.1.0")]);
+            std::fs::write(temp.path().join(".last-updated"), "").unwrap();
+            let timeout = std::time::Duration::from_secs(0);
+            let freshness = Some(std::time::Duration::from_secs(60));
+            wait_for_publish(&mut index, &[("foo", "0.1.0")], timeout, freshness, false).unwrap();
+        }
+
+        #[test]
+        fn one_missing() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut index = crate::test_support::index(temp.path(), &[("foo", "0.1.0")]);
+            let timeout = std::time::Duration::from_secs(0);
+            let crates = [("foo", "0.1.0"), ("bar", "0.2.0")];
+            assert!(wait_for_publish(&mut index, &crates, timeout, None, false).is_err());
+            wait_for_publish(&mut index, &crates, timeout, None, true).unwrap();
+        }
+    }
+
+    mod file_checksum {
+        use super::*;
+
+        #[test]
+        fn sha256() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let file = temp.child("foo-0.1.0.crate");
+            file.write_str("abc").unwrap();
+            assert_eq!(
+                file_checksum(file.path()).unwrap(),
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            );
+        }
+    }
+
+    mod is_locked {
+        use super::*;
+
+        #[test]
+        fn workspace_lock() {
+            let lock_path = Path::new("tests/fixtures/simple/Cargo.lock");
+            assert!(is_locked(lock_path, "simple", "0.1.0").unwrap());
+            assert!(!is_locked(lock_path, "simple", "0.2.0").unwrap());
+            assert!(!is_locked(lock_path, "missing", "0.1.0").unwrap());
+        }
+    }
+
+    mod locked_versions {
+        use super::*;
+
+        #[test]
+        fn workspace_lock() {
+            let lock_path = Path::new("tests/fixtures/simple/Cargo.lock");
+            assert_eq!(locked_versions(lock_path, "simple").unwrap(), ["0.1.0"]);
+            assert!(locked_versions(lock_path, "missing").unwrap().is_empty());
+        }
+    }
+
     mod parse_cargo_config {
         use super::*;
 
@@ -396,6 +1087,63 @@ mod test {
         }
     }
 
+    mod open_index {
+        use super::*;
+
+        const CHILD_VAR: &str = "CARGO_RELEASE_TEST_CARGO_HOME";
+
+        /// `$CARGO_HOME` is process-wide, so the index is opened in a child test process
+        #[test]
+        fn cargo_home_env() {
+            if let Some(cargo_home) = env::var_os(CHILD_VAR) {
+                let index = open_index(None).unwrap();
+                assert!(
+                    index.path().starts_with(&cargo_home),
+                    "{} is outside of {}",
+                    index.path().display(),
+                    Path::new(&cargo_home).display()
+                );
+                return;
+            }
+
+            let temp = assert_fs::TempDir::new().unwrap();
+            let index_path = temp
+                .path()
+                .join("registry/index/github.com-1ecc6299db9ec823");
+            std::fs::create_dir_all(&index_path).unwrap();
+            crate::test_support::index(&index_path, &[]);
+
+            let output = std::process::Command::new(env::current_exe().unwrap())
+                .args(["--exact", "ops::cargo::test::open_index::cargo_home_env"])
+                .env("CARGO_HOME", temp.path())
+                .env(CHILD_VAR, temp.path())
+                .output()
+                .unwrap();
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(output.status.success(), "{}", stdout);
+            assert!(stdout.contains("1 passed"), "{}", stdout);
+        }
+    }
+
+    mod verify_manifest_path {
+        use super::*;
+
+        #[test]
+        fn crate_manifest() {
+            verify_manifest_path(Path::new("tests/fixtures/simple/Cargo.toml")).unwrap();
+        }
+
+        #[test]
+        fn crate_dir() {
+            assert!(verify_manifest_path(Path::new("tests/fixtures/simple")).is_err());
+        }
+
+        #[test]
+        fn missing() {
+            assert!(verify_manifest_path(Path::new("tests/fixtures/missing/Cargo.toml")).is_err());
+        }
+    }
+
     mod set_package_version {
         use super::*;
 
@@ -411,7 +1159,7 @@ mod test {
                 .unwrap();
             assert_eq!(meta.packages[0].version.to_string(), "0.1.0");
 
-            set_package_version(manifest_path.path(), "2.0.0", false).unwrap();
+            set_package_version(manifest_path.path(), "2.0.0", false, false).unwrap();
 
             let meta = cargo_metadata::MetadataCommand::new()
                 .manifest_path(manifest_path.path())
@@ -448,7 +1196,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -494,7 +1242,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -540,7 +1288,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -586,7 +1334,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -636,7 +1384,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -686,7 +1434,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -730,7 +1478,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -775,7 +1523,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "2.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "2.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -824,7 +1572,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "^1.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "^1.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -872,7 +1620,7 @@ mod test {
                 )
                 .unwrap();
 
-            set_dependency_version(manifest_path.path(), "foo", "^1.0", false).unwrap();
+            set_dependency_version(manifest_path.path(), "foo", "^1.0", false, false).unwrap();
 
             manifest_path.assert(
                 predicate::str::diff(
@@ -897,6 +1645,44 @@ mod test {
         }
     }
 
+    mod only_dependency_versions_changed {
+        use super::*;
+
+        const MANIFEST: &str = r#"[package]
+name = "b"
+version = "0.1.0"
+
+[dependencies]
+a = { path = "./a", version = "0.1.0" }
+
+[target.'cfg(unix)'.dev-dependencies.c]
+path = "./c"
+version = "0.1.0"
+"#;
+
+        #[test]
+        fn dependency_versions() {
+            let new = MANIFEST
+                .replace(r#"version = "0.1.0" }"#, r#"version = "0.2.0" }"#)
+                .replace("./c\"\nversion = \"0.1.0\"", "./c\"\nversion = \"0.2.0\"");
+            assert_ne!(new, MANIFEST);
+            assert!(only_dependency_versions_changed(MANIFEST, &new, false));
+        }
+
+        #[test]
+        fn package_version() {
+            let new = MANIFEST.replacen(r#"version = "0.1.0""#, r#"version = "0.1.1-alpha.0""#, 1);
+            assert!(!only_dependency_versions_changed(MANIFEST, &new, false));
+            assert!(only_dependency_versions_changed(MANIFEST, &new, true));
+        }
+
+        #[test]
+        fn other_change() {
+            let new = MANIFEST.replace(r#"a = { path"#, r#"a = { optional = true, path"#);
+            assert!(!only_dependency_versions_changed(MANIFEST, &new, true));
+        }
+    }
+
     mod update_lock {
         use super::*;
 
@@ -907,12 +1693,12 @@ mod test {
             let manifest_path = temp.child("Cargo.toml");
             let lock_path = temp.child("Cargo.lock");
 
-            set_package_version(manifest_path.path(), "2.0.0", false).unwrap();
+            set_package_version(manifest_path.path(), "2.0.0", false, false).unwrap();
             lock_path.assert(predicate::path::eq_file(Path::new(
                 "tests/fixtures/simple/Cargo.lock",
             )));
 
-            update_lock(manifest_path.path()).unwrap();
+            update_lock(manifest_path.path(), None).unwrap();
             lock_path.assert(
                 predicate::path::eq_file(Path::new("tests/fixtures/simple/Cargo.lock")).not(),
             );
@@ -927,12 +1713,12 @@ mod test {
             let manifest_path = temp.child("b/Cargo.toml");
             let lock_path = temp.child("Cargo.lock");
 
-            set_package_version(manifest_path.path(), "2.0.0", false).unwrap();
+            set_package_version(manifest_path.path(), "2.0.0", false, false).unwrap();
             lock_path.assert(predicate::path::eq_file(Path::new(
                 "tests/fixtures/pure_ws/Cargo.lock",
             )));
 
-            update_lock(manifest_path.path()).unwrap();
+            update_lock(manifest_path.path(), None).unwrap();
             lock_path.assert(
                 predicate::path::eq_file(Path::new("tests/fixtures/pure_ws/Cargo.lock")).not(),
             );
@@ -947,12 +1733,12 @@ mod test {
             let manifest_path = temp.child("Cargo.toml");
             let lock_path = temp.child("Cargo.lock");
 
-            set_package_version(manifest_path.path(), "2.0.0", false).unwrap();
+            set_package_version(manifest_path.path(), "2.0.0", false, false).unwrap();
             lock_path.assert(predicate::path::eq_file(Path::new(
                 "tests/fixtures/mixed_ws/Cargo.lock",
             )));
 
-            update_lock(manifest_path.path()).unwrap();
+            update_lock(manifest_path.path(), None).unwrap();
             lock_path.assert(
                 predicate::path::eq_file(Path::new("tests/fixtures/mixed_ws/Cargo.lock")).not(),
             );
@@ -998,4 +1784,44 @@ mod test {
             temp.close().unwrap();
         }
     }
+
+    mod dependency_cycle {
+        use super::*;
+
+        #[test]
+        fn dev_only() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            temp.copy_from("tests/fixtures/mixed_ws", &["**"]).unwrap();
+            temp.child("a/Cargo.toml")
+                .write_str(
+                    r#"
+    [package]
+    name = "a"
+    version = "0.1.0"
+    authors = []
+
+    [dev-dependencies]
+    b = { path = "../" }
+    "#,
+                )
+                .unwrap();
+            let meta = cargo_metadata::MetadataCommand::new()
+                .manifest_path(temp.child("Cargo.toml").path())
+                .exec()
+                .unwrap();
+            let ids: Vec<_> = meta.workspace_members.iter().collect();
+
+            assert_eq!(dependency_cycle(&meta, &ids, false), None);
+            let cycle: Vec<_> = dependency_cycle(&meta, &ids, true)
+                .unwrap()
+                .into_iter()
+                .map(|id| meta[id].name.as_str())
+                .collect();
+            assert_eq!(cycle.len(), 3);
+            assert_eq!(cycle.first(), cycle.last());
+            assert!(cycle.contains(&"a") && cycle.contains(&"b"));
+
+            temp.close().unwrap();
+        }
+    }
 }
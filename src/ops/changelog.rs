@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+/// Changelog file names tried, in order, when `changelog-path` isn't set
+pub const DEFAULT_NAMES: &[&str] = &["CHANGELOG.md", "CHANGES.md", "HISTORY.md"];
+
+/// The existing changelogs for a crate, most relevant first
+///
+/// `path` is relative to the first of `dirs`, the crate root.  Without it, each of
+/// [`DEFAULT_NAMES`] is tried in each of `dirs`.
+pub fn candidates(dirs: &[&Path], path: Option<&Path>) -> Vec<PathBuf> {
+    match path {
+        Some(path) => dirs
+            .first()
+            .map(|dir| dir.join(path))
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect(),
+        None => dirs
+            .iter()
+            .flat_map(|dir| DEFAULT_NAMES.iter().map(move |name| dir.join(name)))
+            .filter(|path| path.is_file())
+            .collect(),
+    }
+}
+
+/// The notes under `version`'s heading in a changelog
+///
+/// The section runs until the next heading of the same or a higher level.  Both `#` and
+/// underlined headings are recognized, with titles like `1.2.3`, `[1.2.3] - 2022-01-01`,
+/// `v1.2.3`, and `Version 1.2.3 (2022-01-01)`.
+pub fn section(changelog: &str, version: &str) -> Option<String> {
+    let lines: Vec<_> = changelog.lines().collect();
+    let mut level = None;
+    let mut section = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let heading = heading(&lines, i);
+        match level {
+            None => {
+                if let Some((heading_level, title, underlined)) = heading {
+                    if is_version_heading(title, version) {
+                        level = Some(heading_level);
+                        if underlined {
+                            i += 1;
+                        }
+                    }
+                }
+            }
+            Some(level) => {
+                if heading.is_some_and(|(heading_level, _, _)| heading_level <= level) {
+                    break;
+                }
+                section.push(lines[i]);
+            }
+        }
+        i += 1;
+    }
+    level?;
+    Some(section.join("\n").trim().to_owned())
+}
+
+/// The level and title of a heading at `lines[i]`, and whether it is underlined
+fn heading<'l>(lines: &[&'l str], i: usize) -> Option<(usize, &'l str, bool)> {
+    let line = lines[i];
+    let hashes = line.chars().take_while(|c| *c == '#').count();
+    if 0 < hashes {
+        let title = &line[hashes..];
+        if hashes <= 6 && (title.is_empty() || title.starts_with(char::is_whitespace)) {
+            return Some((hashes, title, false));
+        }
+        return None;
+    }
+
+    if line.trim().is_empty() {
+        return None;
+    }
+    let underline = lines.get(i + 1)?.trim();
+    if !underline.is_empty() && underline.chars().all(|c| c == '=') {
+        Some((1, line, true))
+    } else if !underline.is_empty() && underline.chars().all(|c| c == '-') {
+        Some((2, line, true))
+    } else {
+        None
+    }
+}
+
+fn is_version_heading(heading: &str, version: &str) -> bool {
+    let title = heading.trim().trim_start_matches('[');
+    let title = match title.get(..8) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("version ") => &title[8..],
+        _ => title,
+    };
+    let title = title
+        .strip_prefix(|c: char| c == 'v' || c == 'V')
+        .unwrap_or(title);
+    title
+        .strip_prefix(version)
+        .map(|rest| {
+            rest.is_empty() || rest.starts_with(|c: char| c == ']' || c == '(' || c.is_whitespace())
+        })
+        .unwrap_or(false)
+}
+
+/// The release notes for `version`, from the first changelog that has them
+pub fn release_notes(dirs: &[&Path], path: Option<&Path>, version: &str) -> String {
+    for path in candidates(dirs, path) {
+        let changelog = match std::fs::read_to_string(&path) {
+            Ok(changelog) => changelog,
+            Err(_) => continue,
+        };
+        if let Some(notes) = section(&changelog, version) {
+            log::debug!("Using release notes from {}", path.display());
+            return notes;
+        }
+        log::debug!("No section for {} in {}", version, path.display());
+    }
+    String::new()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod section {
+        use super::*;
+
+        const CHANGELOG: &str = "# Changelog
+
+## [Unreleased] - ReleaseDate
+
+## [1.2.0] - 2022-10-01
+
+### Features
+
+- Added things
+
+## [1.1.0] - 2022-09-01
+
+- Older things
+";
+
+        #[test]
+        fn keepachangelog() {
+            assert_eq!(
+                section(CHANGELOG, "1.2.0").as_deref(),
+                Some("### Features\n\n- Added things")
+            );
+            assert_eq!(
+                section(CHANGELOG, "1.1.0").as_deref(),
+                Some("- Older things")
+            );
+            assert_eq!(section(CHANGELOG, "Unreleased").as_deref(), Some(""));
+        }
+
+        #[test]
+        fn bare_heading() {
+            assert_eq!(
+                section("# v0.1.0\n\nFirst\n", "0.1.0").as_deref(),
+                Some("First")
+            );
+        }
+
+        #[test]
+        fn plain() {
+            let changelog = "Version 1.2.0 (2022-10-01)
+==========================
+
+Added things
+
+1.1.0
+=====
+
+Older things
+";
+            assert_eq!(section(changelog, "1.2.0").as_deref(), Some("Added things"));
+            assert_eq!(section(changelog, "1.1.0").as_deref(), Some("Older things"));
+        }
+
+        #[test]
+        fn prefix_of_other_version() {
+            assert_eq!(section("## 1.2.0-rc.1\n\nRC\n", "1.2.0"), None);
+        }
+
+        #[test]
+        fn missing() {
+            assert_eq!(section(CHANGELOG, "2.0.0"), None);
+        }
+    }
+
+    mod candidates {
+        use super::*;
+
+        #[test]
+        fn default_names() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            std::fs::write(temp.path().join("HISTORY.md"), "").unwrap();
+            assert_eq!(
+                candidates(&[temp.path()], None),
+                [temp.path().join("HISTORY.md")]
+            );
+            assert!(candidates(&[temp.path()], Some(Path::new("NEWS.md"))).is_empty());
+        }
+    }
+}
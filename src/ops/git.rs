@@ -5,17 +5,19 @@ use std::process::Command;
 use bstr::ByteSlice;
 
 use crate::error::FatalError;
+use crate::ops::cmd;
 use crate::ops::cmd::call_on_path;
 
 pub fn fetch(dir: &Path, remote: &str, branch: &str) -> Result<(), FatalError> {
-    Command::new("git")
-        .arg("fetch")
-        .arg(remote)
-        .arg(branch)
-        .current_dir(dir)
-        .output()
-        .map(|_| ())
-        .map_err(|_| FatalError::GitBinError)
+    cmd::output(
+        Command::new("git")
+            .arg("fetch")
+            .arg(remote)
+            .arg(branch)
+            .current_dir(dir),
+    )
+    .map(|_| ())
+    .map_err(|_| FatalError::GitBinError)
 }
 
 pub fn is_behind_remote(dir: &Path, remote: &str, branch: &str) -> Result<bool, FatalError> {
@@ -45,6 +47,133 @@ pub fn is_behind_remote(dir: &Path, remote: &str, branch: &str) -> Result<bool,
     Ok(behind)
 }
 
+/// Whether HEAD has already been pushed to `remote`/`branch`
+pub fn is_head_pushed(dir: &Path, remote: &str, branch: &str) -> Result<bool, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+
+    let head_id = repo.head()?.peel_to_commit()?.id();
+
+    let remote_branch = format!("{}/{}", remote, branch);
+    let pushed = match repo.revparse_single(&remote_branch) {
+        Ok(o) => {
+            let remote_branch_id = o.peel_to_commit()?.id();
+            remote_branch_id == head_id || repo.graph_descendant_of(remote_branch_id, head_id)?
+        }
+        Err(err) => {
+            log::trace!("Error {}", err);
+            false
+        }
+    };
+
+    Ok(pushed)
+}
+
+/// The URL configured for `remote`, if any
+pub fn remote_url(dir: &Path, remote: &str) -> Result<Option<String>, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+    let url = match repo.find_remote(remote) {
+        Ok(remote) => remote.url().map(ToOwned::to_owned),
+        Err(err) => {
+            log::debug!("Remote `{}` not found: {}", remote, err);
+            None
+        }
+    };
+    Ok(url)
+}
+
+/// `host/path` of a remote or repository URL, for comparing URLs written in different styles
+///
+/// Drops the scheme, user, port, `.git` suffix, and trailing slashes, and lowercases the host, so
+/// `git@github.com:owner/repo.git` and `https://github.com/owner/repo` compare equal.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let (authority, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').unwrap_or((rest, "")),
+        // scp-like `user@host:path`
+        None => match url.split_once(':') {
+            Some((authority, path)) if !authority.contains('/') => (authority, path),
+            _ => ("", url),
+        },
+    };
+    let host = authority.rsplit('@').next().unwrap_or(authority);
+    let host = host.split(':').next().unwrap_or(host).to_lowercase();
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() {
+        path.to_owned()
+    } else {
+        format!("{}/{}", host, path)
+    }
+}
+
+/// Whether pushing `HEAD` to `remote`'s `branch` would be a fast-forward
+///
+/// A branch missing from the remote counts as a fast-forward.
+pub fn is_fast_forward(dir: &Path, remote: &str, branch: &str) -> Result<bool, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+
+    let head_id = repo.head()?.peel_to_commit()?.id();
+
+    let remote_branch = format!("{}/{}", remote, branch);
+    let fast_forward = match repo.revparse_single(&remote_branch) {
+        Ok(o) => {
+            let remote_branch_id = o.id();
+            repo.merge_base(remote_branch_id, head_id)? == remote_branch_id
+        }
+        Err(err) => {
+            log::debug!("Push target `{}` doesn't exist: {}", remote_branch, err);
+            true
+        }
+    };
+
+    Ok(fast_forward)
+}
+
+/// The commit id of `HEAD`
+pub fn head_id(dir: &Path) -> Result<String, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+    let id = repo.head()?.peel_to_commit()?.id();
+    Ok(id.to_string())
+}
+
+/// The message of the commit at `HEAD`
+pub fn head_message(dir: &Path) -> Result<String, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+    let commit = repo.head()?.peel_to_commit()?;
+    Ok(String::from_utf8_lossy(commit.message_bytes()).into_owned())
+}
+
+/// The value of the last `key: value` trailer in a commit message
+///
+/// Trailers are read from the message's last paragraph; `key` is matched case-insensitively.
+pub fn trailer<'m>(message: &'m str, key: &str) -> Option<&'m str> {
+    let message = message.trim_end();
+    let paragraph = match message.rfind("\n\n") {
+        Some(start) => &message[start + 2..],
+        // A lone subject line has no trailers
+        None => return None,
+    };
+    paragraph
+        .lines()
+        .rev()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(k, _)| k.trim().eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.trim())
+}
+
+/// The commit id `rev` refers to, if any
+pub fn rev_id(dir: &Path, rev: &str) -> Result<Option<String>, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+    let id = match repo.revparse_single(rev).and_then(|o| o.peel_to_commit()) {
+        Ok(commit) => Some(commit.id().to_string()),
+        Err(err) => {
+            log::debug!("Cannot resolve `{}`: {}", rev, err);
+            None
+        }
+    };
+    Ok(id)
+}
+
 pub fn current_branch(dir: &Path) -> Result<String, FatalError> {
     let repo = git2::Repository::discover(dir)?;
 
@@ -53,52 +182,90 @@ pub fn current_branch(dir: &Path) -> Result<String, FatalError> {
     Ok(name.to_owned())
 }
 
+/// Submodules with uncommitted changes or not checked out at the commit recorded in `HEAD`
+pub fn dirty_submodules(dir: &Path) -> Result<Vec<(PathBuf, &'static str)>, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+
+    let mut dirty = Vec::new();
+    for submodule in repo.submodules()? {
+        let name = match submodule.name() {
+            Some(name) => name,
+            None => continue,
+        };
+        let status = repo.submodule_status(name, git2::SubmoduleIgnore::None)?;
+        let path = submodule.path().to_owned();
+        if status
+            .intersects(git2::SubmoduleStatus::WD_MODIFIED | git2::SubmoduleStatus::INDEX_MODIFIED)
+        {
+            dirty.push((path, "is not at the recorded commit"));
+        } else if status.intersects(
+            git2::SubmoduleStatus::WD_INDEX_MODIFIED
+                | git2::SubmoduleStatus::WD_WD_MODIFIED
+                | git2::SubmoduleStatus::WD_UNTRACKED,
+        ) {
+            dirty.push((path, "has uncommitted changes"));
+        }
+    }
+    Ok(dirty)
+}
+
 pub fn is_dirty(dir: &Path) -> Result<bool, FatalError> {
-    let output = Command::new("git")
-        .arg("diff")
-        .arg("HEAD")
-        .arg("--exit-code")
-        .arg("--name-only")
-        .arg("--")
-        .arg(".")
-        .current_dir(dir)
-        .output()
-        .map_err(FatalError::from)?;
-    let tracked_unclean = !output.status.success();
-    if tracked_unclean {
-        let tracked = String::from_utf8_lossy(&output.stdout);
+    Ok(!dirty_files(dir)?.is_empty())
+}
+
+/// Tracked files with uncommitted changes and untracked files, excluding ignored ones
+pub fn dirty_files(dir: &Path) -> Result<Vec<PathBuf>, FatalError> {
+    let output = cmd::output(
+        Command::new("git")
+            .arg("diff")
+            .arg("HEAD")
+            .arg("--exit-code")
+            .arg("--name-only")
+            .arg("--")
+            .arg(".")
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
+    let tracked = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
         log::debug!("Dirty because of:\n{}", tracked.trim());
     }
 
-    let output = Command::new("git")
-        .arg("ls-files")
-        .arg("--exclude-standard")
-        .arg("--others")
-        .current_dir(dir)
-        .output()
-        .map_err(FatalError::from)?;
-    let untracked_files = String::from_utf8_lossy(&output.stdout);
-    let untracked = !untracked_files.as_ref().trim().is_empty();
-    if untracked {
-        log::debug!("Dirty because of:\n{}", untracked_files.trim());
+    let output = cmd::output(
+        Command::new("git")
+            .arg("ls-files")
+            .arg("--exclude-standard")
+            .arg("--others")
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
+    let untracked = String::from_utf8_lossy(&output.stdout);
+    if !untracked.trim().is_empty() {
+        log::debug!("Dirty because of:\n{}", untracked.trim());
     }
 
-    Ok(tracked_unclean || untracked)
+    Ok(tracked
+        .lines()
+        .chain(untracked.lines())
+        .filter(|line| !line.trim().is_empty())
+        .map(PathBuf::from)
+        .collect())
 }
 
 pub fn changed_files(dir: &Path, tag: &str) -> Result<Option<Vec<PathBuf>>, FatalError> {
     let root = top_level(dir)?;
 
-    let output = Command::new("git")
-        .arg("diff")
-        .arg(&format!("{}..HEAD", tag))
-        .arg("--name-only")
-        .arg("--exit-code")
-        .arg("--")
-        .arg(".")
-        .current_dir(dir)
-        .output()
-        .map_err(FatalError::from)?;
+    let output = cmd::output(
+        Command::new("git")
+            .arg("diff")
+            .arg(&format!("{}..HEAD", tag))
+            .arg("--name-only")
+            .arg("--exit-code")
+            .arg("--")
+            .arg(".")
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
     match output.status.code() {
         Some(0) => Ok(Some(Vec::new())),
         Some(1) => {
@@ -113,6 +280,69 @@ pub fn changed_files(dir: &Path, tag: &str) -> Result<Option<Vec<PathBuf>>, Fata
     }
 }
 
+/// The contents of `path`, relative to `dir`, as of `rev`
+pub fn file_at(dir: &Path, rev: &str, path: &Path) -> Result<Option<String>, FatalError> {
+    let output = cmd::output(
+        Command::new("git")
+            .arg("show")
+            .arg(format!("{}:./{}", rev, path.display()))
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(String::from_utf8(output.stdout).ok())
+}
+
+/// Whether `path` is committed to the repository
+pub fn is_tracked(dir: &Path, path: &Path) -> Result<bool, FatalError> {
+    let output = cmd::output(
+        Command::new("git")
+            .arg("ls-files")
+            .arg("--error-unmatch")
+            .arg("--")
+            .arg(path)
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
+    Ok(output.status.success())
+}
+
+/// The tracked files that differ from `HEAD`, with the hash of their contents (`None` if deleted)
+pub fn tracked_changes(
+    dir: &Path,
+) -> Result<std::collections::BTreeMap<PathBuf, Option<git2::Oid>>, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+    let workdir = repo.workdir().unwrap_or(dir).to_owned();
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    let statuses = repo.statuses(Some(&mut opts))?;
+    let mut changes = std::collections::BTreeMap::new();
+    for entry in statuses.iter() {
+        let path = match entry.path() {
+            Some(path) => workdir.join(path),
+            None => continue,
+        };
+        let oid = git2::Oid::hash_file(git2::ObjectType::Blob, &path).ok();
+        changes.insert(path, oid);
+    }
+    Ok(changes)
+}
+
+/// Whether [`commit_all`] would have anything to commit, i.e. tracked files differ from `HEAD`
+pub fn has_changes_to_commit(dir: &Path) -> Result<bool, FatalError> {
+    let output = cmd::output(
+        Command::new("git")
+            .arg("diff")
+            .arg("HEAD")
+            .arg("--quiet")
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
+    Ok(!output.status.success())
+}
+
 pub fn commit_all(dir: &Path, msg: &str, sign: bool, dry_run: bool) -> Result<bool, FatalError> {
     call_on_path(
         vec!["git", "commit", if sign { "-S" } else { "" }, "-am", msg],
@@ -121,6 +351,29 @@ pub fn commit_all(dir: &Path, msg: &str, sign: bool, dry_run: bool) -> Result<bo
     )
 }
 
+/// Stage `paths`, like new files that `commit_all` would otherwise miss
+pub fn add(dir: &Path, paths: &[&Path], dry_run: bool) -> Result<bool, FatalError> {
+    let mut cmd = vec!["git".to_owned(), "add".to_owned(), "--".to_owned()];
+    cmd.extend(paths.iter().map(|p| p.display().to_string()));
+    call_on_path(cmd, dir, dry_run)
+}
+
+/// Fold all changes into HEAD, keeping its message
+pub fn amend_all(dir: &Path, sign: bool, dry_run: bool) -> Result<bool, FatalError> {
+    call_on_path(
+        vec![
+            "git",
+            "commit",
+            if sign { "-S" } else { "" },
+            "-a",
+            "--amend",
+            "--no-edit",
+        ],
+        dir,
+        dry_run,
+    )
+}
+
 pub fn tag(
     dir: &Path,
     name: &str,
@@ -145,6 +398,40 @@ pub fn tag_exists(dir: &Path, name: &str) -> Result<bool, FatalError> {
     Ok(!names.is_empty())
 }
 
+/// Whether `git verify-tag` accepts the signature on tag `name`
+///
+/// Which keys are trusted is up to git: GPG's trust database, or `gpg.ssh.allowedSignersFile`.
+pub fn is_tag_signed(dir: &Path, name: &str) -> Result<bool, FatalError> {
+    let output = cmd::output(
+        Command::new("git")
+            .arg("verify-tag")
+            .arg(name)
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
+    if !output.status.success() {
+        log::debug!(
+            "`git verify-tag {}`: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.status.success())
+}
+
+pub fn tag_points_at_head(dir: &Path, name: &str) -> Result<bool, FatalError> {
+    let repo = git2::Repository::discover(dir)?;
+
+    let tag_commit = repo
+        .revparse_single(&format!("refs/tags/{}", name))?
+        .peel_to_commit()?;
+    let head_commit = repo.head()?.peel_to_commit()?;
+    Ok(tag_commit.id() == head_commit.id())
+}
+
+/// Find the tag matching `glob` on the most recent commit in HEAD's first-parent history
+///
+/// When a commit has several matching tags, the one with the highest version wins.
 pub fn find_last_tag(dir: &Path, glob: &globset::GlobMatcher) -> Option<String> {
     let repo = git2::Repository::discover(dir).ok()?;
     let mut tags: std::collections::HashMap<git2::Oid, String> = Default::default();
@@ -153,11 +440,13 @@ pub fn find_last_tag(dir: &Path, glob: &globset::GlobMatcher) -> Option<String>
         let name = name.strip_prefix("refs/tags/").unwrap_or(&name);
         if glob.is_match(&name) {
             let name = name.to_owned();
-            let tag = repo.find_tag(id);
-            let target = tag.and_then(|t| t.target());
-            let commit = target.and_then(|t| t.peel_to_commit());
+            // Lightweight tags point directly at the commit
+            let commit = repo.find_object(id, None).and_then(|o| o.peel_to_commit());
             if let Ok(commit) = commit {
-                tags.insert(commit.id(), name);
+                let existing = tags.entry(commit.id()).or_insert_with(|| name.clone());
+                if tag_version(existing) < tag_version(&name) {
+                    *existing = name;
+                }
             }
         }
         true
@@ -176,6 +465,13 @@ pub fn find_last_tag(dir: &Path, glob: &globset::GlobMatcher) -> Option<String>
     Some(name)
 }
 
+/// Parse the version out of a tag name like `foo-v1.2.3`
+fn tag_version(name: &str) -> Option<semver::Version> {
+    name.char_indices()
+        .filter(|(_, c)| c.is_ascii_digit())
+        .find_map(|(i, _)| semver::Version::parse(&name[i..]).ok())
+}
+
 pub fn push<'s>(
     dir: &Path,
     remote: &str,
@@ -184,6 +480,9 @@ pub fn push<'s>(
     dry_run: bool,
 ) -> Result<bool, FatalError> {
     let mut command = vec!["git", "push"];
+    if crate::ops::cmd::is_quiet() {
+        command.push("--quiet");
+    }
 
     for option in options {
         command.push("--push-option");
@@ -205,12 +504,13 @@ pub fn push<'s>(
 }
 
 pub fn top_level(dir: &Path) -> Result<PathBuf, FatalError> {
-    let output = Command::new("git")
-        .arg("rev-parse")
-        .arg("--show-toplevel")
-        .current_dir(dir)
-        .output()
-        .map_err(FatalError::from)?;
+    let output = cmd::output(
+        Command::new("git")
+            .arg("rev-parse")
+            .arg("--show-toplevel")
+            .current_dir(dir),
+    )
+    .map_err(FatalError::from)?;
     let path = std::str::from_utf8(&output.stdout)
         .map_err(FatalError::from)?
         .trim_end();
@@ -218,9 +518,532 @@ pub fn top_level(dir: &Path) -> Result<PathBuf, FatalError> {
 }
 
 pub fn git_version() -> Result<(), FatalError> {
-    Command::new("git")
-        .arg("--version")
-        .output()
+    cmd::output(Command::new("git").arg("--version"))
         .map(|_| ())
         .map_err(|_| FatalError::GitBinError)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_support::{configure_repo, git, init_repo};
+
+    mod tag_version {
+        use super::*;
+
+        #[test]
+        fn bare() {
+            assert_eq!(tag_version("1.2.3"), semver::Version::parse("1.2.3").ok());
+        }
+
+        #[test]
+        fn prefixed() {
+            assert_eq!(
+                tag_version("foo2-v1.10.0-rc.1"),
+                semver::Version::parse("1.10.0-rc.1").ok()
+            );
+        }
+
+        #[test]
+        fn semver_order() {
+            assert!(tag_version("v1.2.3") < tag_version("v1.10.0"));
+        }
+
+        #[test]
+        fn no_version() {
+            assert_eq!(tag_version("latest"), None);
+        }
+    }
+
+    mod worktree {
+        use super::*;
+
+        use assert_fs::prelude::*;
+
+        /// A repo with one commit on `main` and a worktree checked out to `feature`
+        fn init(temp: &assert_fs::TempDir) -> (PathBuf, PathBuf) {
+            let main = temp.child("main");
+            main.create_dir_all().unwrap();
+            main.child("file.txt").write_str("one\n").unwrap();
+            init_repo(main.path());
+            git(main.path(), &["add", "."]);
+            git(main.path(), &["commit", "-m", "Initial"]);
+
+            let worktree = temp.child("worktree");
+            git(
+                main.path(),
+                &[
+                    "worktree",
+                    "add",
+                    "-b",
+                    "feature",
+                    worktree.path().to_str().unwrap(),
+                ],
+            );
+            (
+                main.path().canonicalize().unwrap(),
+                worktree.path().canonicalize().unwrap(),
+            )
+        }
+
+        #[test]
+        fn branch_and_top_level() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let (main, worktree) = init(&temp);
+
+            assert_eq!(current_branch(&main).unwrap(), "main");
+            assert_eq!(current_branch(&worktree).unwrap(), "feature");
+            assert_eq!(top_level(&worktree).unwrap(), worktree);
+        }
+
+        #[test]
+        fn dirty() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let (main, worktree) = init(&temp);
+
+            assert!(!is_dirty(&worktree).unwrap());
+            std::fs::write(worktree.join("file.txt"), "two\n").unwrap();
+            assert!(is_dirty(&worktree).unwrap());
+            assert!(!is_dirty(&main).unwrap());
+        }
+
+        #[test]
+        fn squash_after_tag() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let (_main, worktree) = init(&temp);
+            let base = head_id(&worktree).unwrap();
+
+            std::fs::write(worktree.join("file.txt"), "1.2.0\n").unwrap();
+            assert!(commit_all(&worktree, "Release 1.2.0", false, false).unwrap());
+            assert!(tag(&worktree, "v1.2.0", "", false, false).unwrap());
+            let release = head_id(&worktree).unwrap();
+
+            std::fs::write(worktree.join("file.txt"), "1.2.1-alpha.0\n").unwrap();
+            assert!(amend_all(&worktree, false, false).unwrap());
+
+            // The branch has a single commit on top of `base`, while the tag is left on its sibling
+            let squashed = head_id(&worktree).unwrap();
+            assert_ne!(squashed, release);
+            assert_eq!(
+                rev_id(&worktree, "v1.2.0").unwrap().as_deref(),
+                Some(release.as_str())
+            );
+            assert_eq!(rev_id(&worktree, "HEAD~").unwrap(), Some(base.clone()));
+            assert_eq!(rev_id(&worktree, "v1.2.0~").unwrap(), Some(base));
+            assert_eq!(head_message(&worktree).unwrap().trim(), "Release 1.2.0");
+            assert_eq!(
+                file_at(&worktree, "v1.2.0", Path::new("file.txt")).unwrap(),
+                Some("1.2.0\n".to_owned())
+            );
+            assert!(!is_dirty(&worktree).unwrap());
+        }
+
+        #[test]
+        fn commit_and_tag() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let (main, worktree) = init(&temp);
+
+            std::fs::write(worktree.join("file.txt"), "two\n").unwrap();
+            assert!(commit_all(&worktree, "Release", false, false).unwrap());
+            assert!(!is_dirty(&worktree).unwrap());
+            assert!(tag(&worktree, "v1.0.0", "", false, false).unwrap());
+
+            // Tags are shared with the main checkout but point at the worktree's HEAD
+            assert!(tag_exists(&main, "v1.0.0").unwrap());
+            assert!(tag_points_at_head(&worktree, "v1.0.0").unwrap());
+            assert!(!tag_points_at_head(&main, "v1.0.0").unwrap());
+            assert!(!is_tag_signed(&worktree, "v1.0.0").unwrap());
+
+            let glob = globset::Glob::new("v*").unwrap().compile_matcher();
+            assert_eq!(find_last_tag(&worktree, &glob).as_deref(), Some("v1.0.0"));
+            assert_eq!(find_last_tag(&main, &glob), None);
+        }
+    }
+
+    /// A repo with `file.txt` committed, ignoring `target`
+    fn init_committed(temp: &assert_fs::TempDir) -> PathBuf {
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::write(root.join("file.txt"), "one\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "target\n").unwrap();
+        init_repo(&root);
+        git(&root, &["add", "."]);
+        git(&root, &["commit", "-m", "Initial"]);
+        root
+    }
+
+    mod tracked_changes {
+        use super::*;
+
+        #[test]
+        fn clean() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init_committed(&temp);
+            assert!(tracked_changes(&root).unwrap().is_empty());
+        }
+
+        #[test]
+        fn modified() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init_committed(&temp);
+
+            std::fs::write(root.join("file.txt"), "two\n").unwrap();
+            let before = tracked_changes(&root).unwrap();
+            assert_eq!(before.keys().collect::<Vec<_>>(), [&root.join("file.txt")]);
+
+            // Editing an already changed file is still noticed
+            std::fs::write(root.join("file.txt"), "three\n").unwrap();
+            assert_ne!(tracked_changes(&root).unwrap(), before);
+        }
+
+        #[test]
+        fn deleted() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init_committed(&temp);
+
+            std::fs::remove_file(root.join("file.txt")).unwrap();
+            let changes = tracked_changes(&root).unwrap();
+            assert_eq!(changes.get(&root.join("file.txt")), Some(&None));
+        }
+
+        #[test]
+        fn untracked() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init_committed(&temp);
+
+            std::fs::write(root.join("new.txt"), "").unwrap();
+            assert!(tracked_changes(&root).unwrap().is_empty());
+        }
+    }
+
+    mod dirty_files {
+        use super::*;
+
+        #[test]
+        fn clean() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init_committed(&temp);
+            assert!(dirty_files(&root).unwrap().is_empty());
+        }
+
+        #[test]
+        fn modified_and_untracked() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init_committed(&temp);
+
+            std::fs::write(root.join("file.txt"), "two\n").unwrap();
+            std::fs::write(root.join("Cargo.lock"), "").unwrap();
+            assert_eq!(
+                dirty_files(&root).unwrap(),
+                [PathBuf::from("file.txt"), PathBuf::from("Cargo.lock")]
+            );
+        }
+
+        #[test]
+        fn ignored() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init_committed(&temp);
+
+            std::fs::create_dir(root.join("target")).unwrap();
+            std::fs::write(root.join("target/out"), "").unwrap();
+            assert!(dirty_files(&root).unwrap().is_empty());
+        }
+    }
+
+    mod is_head_pushed {
+        use super::*;
+
+        use assert_fs::prelude::*;
+
+        fn init(temp: &assert_fs::TempDir) -> PathBuf {
+            let local = temp.child("local");
+            let remote = temp.child("remote.git");
+            local.create_dir_all().unwrap();
+            remote.create_dir_all().unwrap();
+            crate::test_support::init_pushed_repo(local.path(), remote.path());
+            local.path().to_owned()
+        }
+
+        #[test]
+        fn pushed() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            assert!(is_head_pushed(&local, "origin", "main").unwrap());
+        }
+
+        #[test]
+        fn unpushed() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            std::fs::write(local.join("file.txt"), "two\n").unwrap();
+            assert!(commit_all(&local, "Release", false, false).unwrap());
+            assert!(!is_head_pushed(&local, "origin", "main").unwrap());
+        }
+
+        #[test]
+        fn behind_remote() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            std::fs::write(local.join("file.txt"), "two\n").unwrap();
+            assert!(commit_all(&local, "Release", false, false).unwrap());
+            git(&local, &["push", "origin", "main"]);
+            git(&local, &["reset", "--hard", "HEAD~"]);
+            assert!(is_head_pushed(&local, "origin", "main").unwrap());
+        }
+
+        #[test]
+        fn missing_upstream() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            assert!(!is_head_pushed(&local, "origin", "feature").unwrap());
+            assert!(!is_head_pushed(&local, "upstream", "main").unwrap());
+        }
+    }
+
+    mod normalize_url {
+        use super::*;
+
+        #[test]
+        fn same_repo() {
+            let expected = "github.com/crate-ci/cargo-release";
+            for url in [
+                "https://github.com/crate-ci/cargo-release",
+                "https://github.com/crate-ci/cargo-release.git",
+                "https://GitHub.com/crate-ci/cargo-release/",
+                "git@github.com:crate-ci/cargo-release.git",
+                "ssh://git@github.com:22/crate-ci/cargo-release.git",
+            ] {
+                assert_eq!(normalize_url(url), expected, "{}", url);
+            }
+        }
+
+        #[test]
+        fn different_repo() {
+            assert_ne!(
+                normalize_url("https://github.com/crate-ci/cargo-release"),
+                normalize_url("https://github.com/crate-ci/typos")
+            );
+            assert_ne!(
+                normalize_url("https://github.com/crate-ci/cargo-release"),
+                normalize_url("https://gitlab.com/crate-ci/cargo-release")
+            );
+        }
+
+        #[test]
+        fn local_path() {
+            assert_eq!(normalize_url("/tmp/repo.git/"), "tmp/repo");
+        }
+    }
+
+    mod file_at {
+        use super::*;
+
+        use assert_fs::prelude::*;
+
+        #[test]
+        fn tagged() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let repo = temp.path();
+            init_repo(repo);
+            temp.child("sub/file.txt").write_str("old").unwrap();
+            git(repo, &["add", "."]);
+            git(repo, &["commit", "-m", "old"]);
+            git(repo, &["tag", "v1"]);
+            temp.child("sub/file.txt").write_str("new").unwrap();
+
+            let sub = temp.child("sub");
+            assert_eq!(
+                file_at(sub.path(), "v1", Path::new("file.txt"))
+                    .unwrap()
+                    .as_deref(),
+                Some("old")
+            );
+            assert_eq!(
+                file_at(sub.path(), "v1", Path::new("missing.txt")).unwrap(),
+                None
+            );
+        }
+    }
+
+    mod has_changes_to_commit {
+        use super::*;
+
+        use assert_fs::prelude::*;
+
+        #[test]
+        fn tracked_only() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let repo = temp.path();
+            init_repo(repo);
+            temp.child("sub/file.txt").write_str("old").unwrap();
+            git(repo, &["add", "."]);
+            git(repo, &["commit", "-m", "old"]);
+
+            let sub = temp.child("sub");
+            temp.child("untracked.txt").write_str("new").unwrap();
+            assert!(!has_changes_to_commit(sub.path()).unwrap());
+            temp.child("sub/file.txt").write_str("new").unwrap();
+            assert!(has_changes_to_commit(repo).unwrap());
+        }
+    }
+
+    mod trailer {
+        use super::*;
+
+        #[test]
+        fn last_paragraph() {
+            let message =
+                "Fix parsing\n\nRelease-As: patch\n\nSigned-off-by: A <a@b.c>\nRelease-As: minor\n";
+            assert_eq!(trailer(message, "release-as"), Some("minor"));
+        }
+
+        #[test]
+        fn body_only() {
+            let message = "Fix parsing\n\nRelease-As: minor\n\nMore details";
+            assert_eq!(trailer(message, "Release-As"), None);
+        }
+
+        #[test]
+        fn subject_only() {
+            assert_eq!(trailer("Release-As: minor", "Release-As"), None);
+        }
+    }
+
+    mod rev_id {
+        use super::*;
+
+        #[test]
+        fn head_and_tag() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let repo = temp.path();
+            init_repo(repo);
+            git(repo, &["commit", "--allow-empty", "-m", "one"]);
+            git(repo, &["tag", "reviewed"]);
+            let reviewed = head_id(repo).unwrap();
+            git(repo, &["commit", "--allow-empty", "-m", "two"]);
+
+            assert_eq!(rev_id(repo, "reviewed").unwrap(), Some(reviewed.clone()));
+            assert_eq!(rev_id(repo, &reviewed[..8]).unwrap(), Some(reviewed));
+            assert_eq!(rev_id(repo, "HEAD").unwrap(), Some(head_id(repo).unwrap()));
+            assert_eq!(rev_id(repo, "missing").unwrap(), None);
+        }
+    }
+
+    mod is_fast_forward {
+        use super::*;
+
+        use assert_fs::prelude::*;
+
+        fn commit(dir: &Path, content: &str) {
+            std::fs::write(dir.join("file.txt"), content).unwrap();
+            git(dir, &["add", "."]);
+            git(dir, &["commit", "-m", content]);
+        }
+
+        /// `main` and `side` diverge after one shared commit
+        fn init(temp: &assert_fs::TempDir) -> PathBuf {
+            let repo = temp.child("repo");
+            repo.create_dir_all().unwrap();
+            init_repo(repo.path());
+            commit(repo.path(), "one");
+            git(repo.path(), &["branch", "side"]);
+            commit(repo.path(), "two");
+            git(repo.path(), &["checkout", "side"]);
+            commit(repo.path(), "three");
+            git(repo.path(), &["checkout", "main"]);
+            repo.path().to_owned()
+        }
+
+        #[test]
+        fn missing() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let repo = init(&temp);
+            assert!(is_fast_forward(&repo, "origin", "release").unwrap());
+        }
+
+        #[test]
+        fn ancestor() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let repo = init(&temp);
+            git(
+                &repo,
+                &["update-ref", "refs/remotes/origin/release", "main~1"],
+            );
+            assert!(is_fast_forward(&repo, "origin", "release").unwrap());
+        }
+
+        #[test]
+        fn diverged() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let repo = init(&temp);
+            git(
+                &repo,
+                &["update-ref", "refs/remotes/origin/release", "side"],
+            );
+            assert!(!is_fast_forward(&repo, "origin", "release").unwrap());
+        }
+    }
+    mod dirty_submodules {
+        use super::*;
+
+        use assert_fs::prelude::*;
+
+        /// A repo with the submodule `sub` checked out at its recorded commit
+        fn init(temp: &assert_fs::TempDir) -> PathBuf {
+            let sub = temp.child("sub");
+            sub.create_dir_all().unwrap();
+            sub.child("file.txt").write_str("one\n").unwrap();
+            init_repo(sub.path());
+            git(sub.path(), &["add", "."]);
+            git(sub.path(), &["commit", "-m", "Initial"]);
+
+            let main = temp.child("main");
+            main.create_dir_all().unwrap();
+            init_repo(main.path());
+            git(
+                main.path(),
+                &[
+                    "-c",
+                    "protocol.file.allow=always",
+                    "submodule",
+                    "add",
+                    sub.path().to_str().unwrap(),
+                    "sub",
+                ],
+            );
+            git(main.path(), &["commit", "-m", "Add submodule"]);
+            main.path().to_owned()
+        }
+
+        #[test]
+        fn clean() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let main = init(&temp);
+            assert!(dirty_submodules(&main).unwrap().is_empty());
+        }
+
+        #[test]
+        fn uncommitted() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let main = init(&temp);
+            std::fs::write(main.join("sub/file.txt"), "two\n").unwrap();
+            assert_eq!(
+                dirty_submodules(&main).unwrap(),
+                [(PathBuf::from("sub"), "has uncommitted changes")]
+            );
+        }
+
+        #[test]
+        fn moved() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let main = init(&temp);
+            let sub = main.join("sub");
+            configure_repo(&sub);
+            git(&sub, &["commit", "--allow-empty", "-m", "Moved"]);
+            assert_eq!(
+                dirty_submodules(&main).unwrap(),
+                [(PathBuf::from("sub"), "is not at the recorded commit")]
+            );
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::error::FatalError;
+
+pub const TOKEN_VAR: &str = "GITHUB_TOKEN";
+
+/// `owner/repo` for a GitHub remote URL
+pub fn repo_from_url(url: &str) -> Option<String> {
+    let path = if let Some(path) = url.strip_prefix("git@github.com:") {
+        path
+    } else {
+        let (_, rest) = url.split_once("://")?;
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        let host = host.split(':').next().unwrap_or(host);
+        if host != "github.com" {
+            return None;
+        }
+        path
+    };
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, repo) = path.split_once('/')?;
+    if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some(format!("{}/{}", owner, repo))
+}
+
+/// Whether `curl`, which creates the releases, can be run
+pub fn curl_available() -> bool {
+    crate::ops::cmd::output(Command::new("curl").arg("--version")).is_ok()
+}
+
+/// Create a GitHub release for the already pushed `tag_name`
+pub fn create_release(
+    repo: &str,
+    tag_name: &str,
+    body: &str,
+    prerelease: bool,
+    dry_run: bool,
+) -> Result<bool, FatalError> {
+    if dry_run {
+        log::info!(
+            "Would create GitHub release {} for {} ({} characters of notes)",
+            tag_name,
+            repo,
+            body.len()
+        );
+        return Ok(true);
+    }
+    let token = std::env::var(TOKEN_VAR)?;
+
+    let data = serde_json::json!({
+        "tag_name": tag_name,
+        "name": tag_name,
+        "body": body,
+        "prerelease": prerelease,
+    })
+    .to_string();
+    let url = format!("https://api.github.com/repos/{}/releases", repo);
+    let mut cmd = Command::new("curl");
+    cmd.args(["--fail", "--silent", "--show-error"])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        // Keep the token and the notes out of the process arguments
+        .args(["--config", "-"])
+        .arg(&url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped());
+    crate::ops::cmd::log_command(&cmd);
+    let mut child = cmd.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(curl_config(&token, &data).as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Ok(false);
+    }
+
+    let html_url = serde_json::from_slice::<serde_json::Value>(&output.stdout)
+        .ok()
+        .and_then(|v| v.get("html_url")?.as_str().map(ToOwned::to_owned));
+    match html_url {
+        Some(html_url) => log::info!("Created GitHub release {}", html_url),
+        None => log::info!("Created GitHub release {} for {}", tag_name, repo),
+    }
+    Ok(true)
+}
+
+/// The `curl --config` setting the authorization `token` and the request `data`
+fn curl_config(token: &str, data: &str) -> String {
+    let quote = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(
+        "header = \"Authorization: Bearer {}\"\ndata-binary = \"{}\"\n",
+        quote(token),
+        quote(data)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod curl_config {
+        use super::*;
+
+        #[test]
+        fn quoted() {
+            let data = serde_json::json!({ "body": "Fixed \"quotes\"\nand C:\\paths" }).to_string();
+            assert_eq!(
+                curl_config("t\"k", &data),
+                concat!(
+                    "header = \"Authorization: Bearer t\\\"k\"\n",
+                    r#"data-binary = "{\"body\":\"Fixed \\\"quotes\\\"\\nand C:\\\\paths\"}""#,
+                    "\n"
+                )
+            );
+        }
+    }
+
+    mod repo_from_url {
+        use super::*;
+
+        #[test]
+        fn ssh() {
+            assert_eq!(
+                repo_from_url("git@github.com:crate-ci/cargo-release.git").as_deref(),
+                Some("crate-ci/cargo-release")
+            );
+            assert_eq!(
+                repo_from_url("ssh://git@github.com/crate-ci/cargo-release").as_deref(),
+                Some("crate-ci/cargo-release")
+            );
+        }
+
+        #[test]
+        fn https() {
+            assert_eq!(
+                repo_from_url("https://github.com/crate-ci/cargo-release.git").as_deref(),
+                Some("crate-ci/cargo-release")
+            );
+            assert_eq!(
+                repo_from_url("https://token@github.com/crate-ci/cargo-release/").as_deref(),
+                Some("crate-ci/cargo-release")
+            );
+        }
+
+        #[test]
+        fn other_host() {
+            assert_eq!(
+                repo_from_url("https://gitlab.com/crate-ci/cargo-release.git"),
+                None
+            );
+            assert_eq!(repo_from_url("/tmp/cargo-release"), None);
+        }
+    }
+}
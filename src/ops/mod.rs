@@ -1,6 +1,8 @@
 pub mod cargo;
+pub mod changelog;
 pub mod cmd;
 pub mod git;
+pub mod github;
 pub mod replace;
 pub mod shell;
 pub mod version;
@@ -23,44 +23,114 @@ pub struct Template<'a> {
     pub tag_name: Option<&'a str>,
     pub next_version: Option<&'a str>,
     pub next_metadata: Option<&'a str>,
+
+    /// Opening and closing delimiters of placeholders, `{{` and `}}` when unset
+    pub delimiters: Option<(&'a str, &'a str)>,
 }
 
 impl<'a> Template<'a> {
     pub fn render(&self, input: &str) -> String {
+        let (open, close) = self.delimiters.unwrap_or(("{{", "}}"));
+        let var = |name: &str| format!("{}{}{}", open, name, close);
         let mut s = input.to_string();
         if let Some(prev_version) = self.prev_version {
-            s = s.replace("{{prev_version}}", prev_version);
+            s = render_version_parts(s, "prev_version", prev_version, open, close);
+            s = s.replace(&var("prev_version"), prev_version);
         }
         if let Some(prev_metadata) = self.prev_metadata {
-            s = s.replace("{{prev_metadata}}", prev_metadata);
+            s = s.replace(&var("prev_metadata"), prev_metadata);
         }
         if let Some(version) = self.version {
-            s = s.replace("{{version}}", version);
+            s = render_version_parts(s, "version", version, open, close);
+            s = s.replace(&var("version"), version);
         }
         if let Some(metadata) = self.metadata {
-            s = s.replace("{{metadata}}", metadata);
+            s = s.replace(&var("metadata"), metadata);
         }
         if let Some(crate_name) = self.crate_name {
-            s = s.replace("{{crate_name}}", crate_name);
+            s = s.replace(&var("crate_name"), crate_name);
         }
         if let Some(date) = self.date {
-            s = s.replace("{{date}}", date);
+            s = s.replace(&var("date"), date);
         }
 
         if let Some(prefix) = self.prefix {
-            s = s.replace("{{prefix}}", prefix);
+            s = s.replace(&var("prefix"), prefix);
         }
         if let Some(tag_name) = self.tag_name {
-            s = s.replace("{{tag_name}}", tag_name);
+            s = s.replace(&var("tag_name"), tag_name);
         }
         if let Some(next_version) = self.next_version {
-            s = s.replace("{{next_version}}", next_version);
+            s = render_version_parts(s, "next_version", next_version, open, close);
+            s = s.replace(&var("next_version"), next_version);
         }
         if let Some(next_metadata) = self.next_metadata {
-            s = s.replace("{{next_metadata}}", next_metadata);
+            s = s.replace(&var("next_metadata"), next_metadata);
         }
         s
     }
+
+    /// Render a regex replacement string
+    ///
+    /// Placeholders are substituted first, with any `$` in their values escaped, so only the
+    /// `$1` / `${name}` references written in `input` refer to capture groups.
+    pub fn render_replacement(&self, input: &str) -> String {
+        let escape = |value: Option<&str>| value.map(|value| value.replace('$', "$$"));
+        let prev_version = escape(self.prev_version);
+        let prev_metadata = escape(self.prev_metadata);
+        let version = escape(self.version);
+        let metadata = escape(self.metadata);
+        let crate_name = escape(self.crate_name);
+        let date = escape(self.date);
+        let prefix = escape(self.prefix);
+        let tag_name = escape(self.tag_name);
+        let next_version = escape(self.next_version);
+        let next_metadata = escape(self.next_metadata);
+        Template {
+            prev_version: prev_version.as_deref(),
+            prev_metadata: prev_metadata.as_deref(),
+            version: version.as_deref(),
+            metadata: metadata.as_deref(),
+            crate_name: crate_name.as_deref(),
+            date: date.as_deref(),
+            prefix: prefix.as_deref(),
+            tag_name: tag_name.as_deref(),
+            next_version: next_version.as_deref(),
+            next_metadata: next_metadata.as_deref(),
+            delimiters: self.delimiters,
+        }
+        .render(input)
+    }
+}
+
+/// Render `{{<name>.major}}`, `{{<name>.minor}}`, `{{<name>.patch}}`, and `{{<name>.pre}}`
+fn render_version_parts(
+    mut s: String,
+    name: &str,
+    version: &str,
+    open: &str,
+    close: &str,
+) -> String {
+    if !s.contains(&format!("{}{}.", open, name)) {
+        return s;
+    }
+    let version = match semver::Version::parse(version) {
+        Ok(version) => version,
+        Err(err) => {
+            log::debug!("Not rendering parts of `{}`: {}", version, err);
+            return s;
+        }
+    };
+    let parts = [
+        ("major", version.major.to_string()),
+        ("minor", version.minor.to_string()),
+        ("patch", version.patch.to_string()),
+        ("pre", version.pre.as_str().to_owned()),
+    ];
+    for (part, value) in parts {
+        s = s.replace(&format!("{}{}.{}{}", open, name, part, close), &value);
+    }
+    s
 }
 
 pub fn do_file_replacements(
@@ -86,14 +156,32 @@ pub fn do_file_replacements(
         }
         let data = std::fs::read_to_string(&file)?;
         let mut replaced = data.clone();
+        let mut rename = None;
 
         for replace in replaces {
-            if prerelease && !replace.prerelease {
-                log::debug!("Pre-release, not replacing {}", replace.search);
+            if !replace.prerelease.applies(prerelease) {
+                let search = replace.search.as_deref().unwrap_or_default();
+                if prerelease {
+                    log::debug!("Pre-release, not replacing {}", search);
+                } else {
+                    log::debug!("Not a pre-release, not replacing {}", search);
+                }
                 continue;
             }
 
-            let pattern = replace.search.as_str();
+            if let Some(to) = replace.rename.as_deref() {
+                if rename.is_some() {
+                    return Err(FatalError::ReplacerRenameConflict(file));
+                }
+                rename = Some(template.render(to));
+            }
+            let (pattern, to_replace) =
+                match (replace.search.as_deref(), replace.replace.as_deref()) {
+                    (Some(pattern), Some(to_replace)) => (pattern, to_replace),
+                    (None, None) if replace.rename.is_some() => continue,
+                    _ => return Err(FatalError::ReplacerConfigError),
+                };
+
             let r = regex::RegexBuilder::new(pattern)
                 .multi_line(true)
                 .build()
@@ -116,8 +204,7 @@ pub fn do_file_replacements(
                 ));
             }
 
-            let to_replace = replace.replace.as_str();
-            let replacer = template.render(to_replace);
+            let replacer = template.render_replacement(to_replace);
 
             replaced = r.replace_all(&replaced, replacer.as_str()).into_owned();
         }
@@ -148,6 +235,296 @@ pub fn do_file_replacements(
         } else {
             log::trace!("{} is unchanged", file.display());
         }
+
+        if let Some(to) = rename {
+            let target = cwd.join(&to);
+            if target == file {
+                log::trace!("{} is already named {}", path.display(), to);
+                continue;
+            }
+            if target.exists() {
+                return Err(FatalError::ReplacerRenameExists(file, target));
+            }
+            log::info!("Renaming {} to {}", path.display(), to);
+            if !dry_run {
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::rename(&file, &target)?;
+                // The removal of the old name is picked up by `commit -a` but not the new name
+                if !crate::ops::git::add(cwd, &[Path::new(&to)], dry_run)? {
+                    return Ok(false);
+                }
+            }
+        }
     }
     Ok(true)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod render {
+        use super::*;
+
+        #[test]
+        fn version_parts() {
+            let template = Template {
+                version: Some("1.2.3"),
+                ..Default::default()
+            };
+            assert_eq!(
+                template.render(
+                    "{{version.major}}.{{version.minor}}.{{version.patch}}-{{version.pre}}"
+                ),
+                "1.2.3-"
+            );
+        }
+
+        #[test]
+        fn prerelease_parts() {
+            let template = Template {
+                version: Some("1.2.3-alpha.1+git.123"),
+                next_version: Some("1.2.4-alpha.0"),
+                ..Default::default()
+            };
+            assert_eq!(
+                template.render("v{{version.major}} {{version.pre}} {{next_version.patch}}"),
+                "v1 alpha.1 4"
+            );
+        }
+
+        #[test]
+        fn unset_version_parts() {
+            let template = Template::default();
+            assert_eq!(template.render("{{version.major}}"), "{{version.major}}");
+        }
+
+        #[test]
+        fn custom_delimiters() {
+            let template = Template {
+                version: Some("1.2.3"),
+                crate_name: Some("foo"),
+                delimiters: Some(("<<", ">>")),
+                ..Default::default()
+            };
+            assert_eq!(
+                template.render(
+                    "<<crate_name>> <<version>> v<<version.major>> ${{ github.ref }} {{version}}"
+                ),
+                "foo 1.2.3 v1 ${{ github.ref }} {{version}}"
+            );
+        }
+    }
+
+    mod do_file_replacements {
+        use super::*;
+
+        use crate::test_support::init_repo;
+        use assert_fs::prelude::*;
+
+        fn rename(file: &str, to: &str) -> Replace {
+            Replace {
+                file: file.into(),
+                search: None,
+                replace: None,
+                rename: Some(to.to_owned()),
+                min: None,
+                max: None,
+                exactly: None,
+                prerelease: Default::default(),
+            }
+        }
+
+        fn replace(file: &str, search: &str, replace: &str) -> Replace {
+            Replace {
+                file: file.into(),
+                search: Some(search.to_owned()),
+                replace: Some(replace.to_owned()),
+                rename: None,
+                min: None,
+                max: None,
+                exactly: None,
+                prerelease: Default::default(),
+            }
+        }
+
+        fn replaced(content: &str, replaces: &[Replace], template: &Template<'_>) -> String {
+            let temp = assert_fs::TempDir::new().unwrap();
+            temp.child("file.txt").write_str(content).unwrap();
+            assert!(
+                do_file_replacements(replaces, template, temp.path(), false, false, false).unwrap()
+            );
+            std::fs::read_to_string(temp.child("file.txt").path()).unwrap()
+        }
+
+        #[test]
+        fn rename_staged() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            temp.child("schema-v1.json").write_str("{}").unwrap();
+            init_repo(temp.path());
+
+            let template = Template {
+                version: Some("2.0.0"),
+                ..Default::default()
+            };
+            let replaces = [rename("schema-v1.json", "schema-v{{version.major}}.json")];
+            assert!(
+                do_file_replacements(&replaces, &template, temp.path(), false, false, false)
+                    .unwrap()
+            );
+
+            temp.child("schema-v1.json")
+                .assert(predicates::path::missing());
+            temp.child("schema-v2.json").assert("{}");
+            let output = std::process::Command::new("git")
+                .args(["diff", "--cached", "--name-only"])
+                .current_dir(temp.path())
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), "schema-v2.json\n");
+        }
+
+        #[test]
+        fn rename_dry_run() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            temp.child("schema-v1.json").write_str("{}").unwrap();
+
+            let template = Template {
+                version: Some("2.0.0"),
+                ..Default::default()
+            };
+            let replaces = [rename("schema-v1.json", "schema-v{{version.major}}.json")];
+            do_file_replacements(&replaces, &template, temp.path(), false, false, true).unwrap();
+
+            temp.child("schema-v1.json").assert("{}");
+            temp.child("schema-v2.json")
+                .assert(predicates::path::missing());
+        }
+
+        #[test]
+        fn rename_existing_target() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            temp.child("schema-v1.json").write_str("{}").unwrap();
+            temp.child("schema-v2.json").write_str("[]").unwrap();
+
+            let template = Template {
+                version: Some("2.0.0"),
+                ..Default::default()
+            };
+            let replaces = [rename("schema-v1.json", "schema-v{{version.major}}.json")];
+            let err = do_file_replacements(&replaces, &template, temp.path(), false, false, true)
+                .unwrap_err();
+            assert!(matches!(err, FatalError::ReplacerRenameExists(..)));
+            temp.child("schema-v2.json").assert("[]");
+        }
+
+        #[test]
+        fn search_without_replace() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            temp.child("file.txt").write_str("version\n").unwrap();
+
+            let replaces: Vec<Replace> =
+                toml_edit::easy::from_str::<std::collections::BTreeMap<String, Vec<Replace>>>(
+                    r#"replacements = [{file="file.txt", search="version"}]"#,
+                )
+                .unwrap()
+                .remove("replacements")
+                .unwrap();
+            let template = Template::default();
+            let err = do_file_replacements(&replaces, &template, temp.path(), false, false, false)
+                .unwrap_err();
+            assert!(matches!(err, FatalError::ReplacerConfigError));
+            temp.child("file.txt").assert("version\n");
+
+            let replaces = [Replace {
+                search: None,
+                ..replace("file.txt", "", "{{version}}")
+            }];
+            let err = do_file_replacements(&replaces, &template, temp.path(), false, false, false)
+                .unwrap_err();
+            assert!(matches!(err, FatalError::ReplacerConfigError));
+        }
+
+        #[test]
+        fn template_version() {
+            let template = Template {
+                version: Some("1.2.0"),
+                ..Default::default()
+            };
+            let replaces = [replace(
+                "file.txt",
+                "version = \"(.*)\"",
+                "version = \"{{version}}\"",
+            )];
+            assert_eq!(
+                replaced("version = \"1.1.0\"\n", &replaces, &template),
+                "version = \"1.2.0\"\n"
+            );
+        }
+
+        #[test]
+        fn capture_groups() {
+            let template = Template {
+                version: Some("1.2.0"),
+                ..Default::default()
+            };
+            let replaces = [replace(
+                "file.txt",
+                "(?P<name>[a-z]+) = \"(.*)\"",
+                "${name} = \"{{version}}\" # was $2",
+            )];
+            assert_eq!(
+                replaced("foo = \"1.1.0\"\n", &replaces, &template),
+                "foo = \"1.2.0\" # was 1.1.0\n"
+            );
+        }
+
+        #[test]
+        fn literal_placeholder() {
+            let template = Template {
+                tag_name: Some("v$1"),
+                ..Default::default()
+            };
+            let replaces = [replace("file.txt", "(HEAD)", "{{tag_name}} $1")];
+            assert_eq!(replaced("HEAD\n", &replaces, &template), "v$1 HEAD\n");
+        }
+
+        #[test]
+        fn prerelease_modes() {
+            use crate::config::ReplacePrerelease;
+
+            let template = Template {
+                version: Some("1.2.0"),
+                ..Default::default()
+            };
+            let cases = [
+                (ReplacePrerelease::Always, false, true),
+                (ReplacePrerelease::Always, true, true),
+                (ReplacePrerelease::Never, false, true),
+                (ReplacePrerelease::Never, true, false),
+                (ReplacePrerelease::Only, false, false),
+                (ReplacePrerelease::Only, true, true),
+            ];
+            for (mode, prerelease, applied) in cases {
+                let temp = assert_fs::TempDir::new().unwrap();
+                temp.child("README.md")
+                    .write_str("stable: 1.1.0\n")
+                    .unwrap();
+                let replaces = [Replace {
+                    prerelease: mode,
+                    ..replace("README.md", "stable: .*", "stable: {{version}}")
+                }];
+                do_file_replacements(&replaces, &template, temp.path(), prerelease, false, false)
+                    .unwrap();
+                let expected = if applied {
+                    "stable: 1.2.0\n"
+                } else {
+                    "stable: 1.1.0\n"
+                };
+                temp.child("README.md").assert(expected);
+            }
+        }
+    }
+}
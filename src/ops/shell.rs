@@ -1,4 +1,4 @@
-use std::io::{stdin, stdout, Write};
+use std::io::{stdin, stdout, IsTerminal, Write};
 
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
@@ -23,3 +23,60 @@ pub fn confirm(prompt: &str) -> bool {
 
     input.trim().to_lowercase() == "y"
 }
+
+pub fn is_interactive() -> bool {
+    stdin().is_terminal()
+}
+
+/// Let the user pick from `items`, returning the selected indices
+///
+/// An empty response selects everything.
+pub fn select(prompt: &str, items: &[String]) -> Vec<usize> {
+    loop {
+        let mut input = String::new();
+
+        console_println(prompt, None, true);
+        for (i, item) in items.iter().enumerate() {
+            console_println(&format!("  {}) {}", i + 1, item), None, false);
+        }
+        console_println(
+            "Enter numbers separated by spaces or commas [all] ",
+            None,
+            true,
+        );
+
+        stdout().flush().unwrap();
+        stdin().read_line(&mut input).expect("selection required");
+
+        let input = input.trim();
+        if input.is_empty() || input.eq_ignore_ascii_case("all") {
+            return (0..items.len()).collect();
+        }
+
+        let selected: Result<Vec<_>, _> = input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| match s.parse::<usize>() {
+                Ok(i) if 1 <= i && i <= items.len() => Ok(i - 1),
+                _ => Err(s),
+            })
+            .collect();
+        match selected {
+            Ok(selected) => return selected,
+            Err(invalid) => {
+                console_println(&format!("Invalid selection `{}`", invalid), None, false);
+            }
+        }
+    }
+}
+
+pub fn input(prompt: &str) -> String {
+    let mut input = String::new();
+
+    console_println(prompt, None, true);
+
+    stdout().flush().unwrap();
+    stdin().read_line(&mut input).expect("input required");
+
+    input.trim().to_owned()
+}
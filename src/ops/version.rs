@@ -6,18 +6,70 @@ use crate::error::FatalError;
 pub enum TargetVersion {
     Relative(BumpLevel),
     Absolute(semver::Version),
+    /// The lowest unpublished version matching the requirement, see [`TargetVersion::resolve`]
+    Matching(semver::VersionReq),
 }
 
 impl TargetVersion {
+    /// Parse `--set-version`, which takes either a version or a version requirement
+    pub fn parse_set_version(s: &str) -> Result<Self, FatalError> {
+        if let Ok(version) = semver::Version::parse(s) {
+            Ok(TargetVersion::Absolute(version))
+        } else {
+            let req = semver::VersionReq::parse(s)?;
+            // A bare `1.2` reads like a version but would be taken as `^1.2`
+            if s.split(',')
+                .any(|c| c.trim_start().starts_with(|c: char| c.is_ascii_digit()))
+            {
+                return Err(FatalError::PartialVersion(s.to_owned()));
+            }
+            Ok(TargetVersion::Matching(req))
+        }
+    }
+
+    /// Whether [`TargetVersion::resolve`] needs the versions already published
+    pub fn is_matching(&self) -> bool {
+        matches!(self, TargetVersion::Matching(_))
+    }
+
+    /// Pick the lowest version of `name` matching the requirement that isn't in `published`
+    pub fn resolve(&self, name: &str, published: &[semver::Version]) -> Result<Self, FatalError> {
+        let req = match self {
+            TargetVersion::Matching(req) => req,
+            _ => return Ok(self.clone()),
+        };
+        let mut candidate = req
+            .comparators
+            .iter()
+            .filter_map(lower_bound)
+            .max()
+            .unwrap_or_else(|| semver::Version::new(0, 0, 0));
+        while req.matches(&candidate) {
+            if !published.contains(&candidate) {
+                return Ok(TargetVersion::Absolute(candidate));
+            }
+            candidate.increment_patch();
+        }
+        Err(FatalError::UnsatisfiableVersionReq(
+            name.to_owned(),
+            req.to_string(),
+        ))
+    }
+
     pub fn bump(
         &self,
         current: &semver::Version,
         metadata: Option<&str>,
+        prerelease_identifier: &str,
     ) -> Result<Option<Version>, FatalError> {
         let bumped = match self {
             TargetVersion::Relative(bump_level) => {
                 let mut potential_version = current.to_owned();
-                if bump_level.bump_version(&mut potential_version, metadata)? {
+                if bump_level.bump_version(
+                    &mut potential_version,
+                    metadata,
+                    prerelease_identifier,
+                )? {
                     let full_version = potential_version;
                     let version = Version::from(full_version);
                     Some(version)
@@ -41,6 +93,11 @@ impl TargetVersion {
                     None
                 }
             }
+            TargetVersion::Matching(_) => {
+                // Nothing published to avoid
+                self.resolve("", &[])?
+                    .bump(current, metadata, prerelease_identifier)?
+            }
         };
         Ok(bumped)
     }
@@ -61,6 +118,9 @@ impl std::fmt::Display for TargetVersion {
             TargetVersion::Absolute(version) => {
                 write!(f, "{}", version)
             }
+            TargetVersion::Matching(req) => {
+                write!(f, "{}", req)
+            }
         }
     }
 }
@@ -159,6 +219,8 @@ pub enum BumpLevel {
     Beta,
     /// Increase the alpha pre-version (x.y.z-alpha.M)
     Alpha,
+    /// Increase the pre-version using `prerelease-identifier` (x.y.z-<ID>.M)
+    Prerelease,
     /// Remove the pre-version (x.y.z)
     Release,
 }
@@ -190,10 +252,27 @@ impl std::str::FromStr for BumpLevel {
 }
 
 impl BumpLevel {
+    /// The level to bump `version` by when `0.x` crates treat `breaking` as their breaking level
+    ///
+    /// With [`crate::config::BreakingLevel::Minor`], `major` bumps `0.x` to `0.(x+1).0` and
+    /// `minor` bumps `0.x.y` to `0.x.(y+1)`.
+    pub fn pre_1_0(
+        self,
+        version: &semver::Version,
+        breaking: crate::config::BreakingLevel,
+    ) -> Self {
+        match (breaking, version.major, self) {
+            (crate::config::BreakingLevel::Minor, 0, BumpLevel::Major) => BumpLevel::Minor,
+            (crate::config::BreakingLevel::Minor, 0, BumpLevel::Minor) => BumpLevel::Patch,
+            _ => self,
+        }
+    }
+
     pub fn bump_version(
         self,
         version: &mut semver::Version,
         metadata: Option<&str>,
+        prerelease_identifier: &str,
     ) -> Result<bool, FatalError> {
         let mut need_commit = false;
         match self {
@@ -225,6 +304,10 @@ impl BumpLevel {
                 version.increment_alpha()?;
                 need_commit = true;
             }
+            BumpLevel::Prerelease => {
+                version.bump_prerelease(prerelease_identifier)?;
+                need_commit = true;
+            }
             BumpLevel::Release => {
                 if version.is_prerelease() {
                     version.pre = semver::Prerelease::EMPTY;
@@ -248,6 +331,8 @@ pub trait VersionExt {
     fn increment_alpha(&mut self) -> Result<(), FatalError>;
     fn increment_beta(&mut self) -> Result<(), FatalError>;
     fn increment_rc(&mut self) -> Result<(), FatalError>;
+    fn increment_prerelease(&mut self, id: &str) -> Result<(), FatalError>;
+    fn bump_prerelease(&mut self, id: &str) -> Result<(), FatalError>;
     fn prerelease_id_version(&self) -> Result<Option<(String, Option<u64>)>, FatalError>;
     fn metadata(&mut self, metadata: &str) -> Result<(), FatalError>;
     fn is_prerelease(&self) -> bool;
@@ -291,58 +376,63 @@ impl VersionExt for semver::Version {
     }
 
     fn increment_alpha(&mut self) -> Result<(), FatalError> {
-        if let Some((pre_ext, pre_ext_ver)) = self.prerelease_id_version()? {
-            if pre_ext == VERSION_BETA || pre_ext == VERSION_RC {
-                Err(FatalError::InvalidReleaseLevel(VERSION_ALPHA.to_owned()))
-            } else {
-                let new_ext_ver = if pre_ext == VERSION_ALPHA {
-                    pre_ext_ver.unwrap_or(0) + 1
-                } else {
-                    1
-                };
-                self.pre = semver::Prerelease::new(&format!("{}.{}", VERSION_ALPHA, new_ext_ver))?;
-                Ok(())
-            }
+        let pre_ext = prerelease_id(self);
+        if pre_ext == VERSION_BETA || pre_ext == VERSION_RC {
+            Err(FatalError::InvalidReleaseLevel(VERSION_ALPHA.to_owned()))
         } else {
-            self.increment_patch();
-            self.pre = semver::Prerelease::new(&format!("{}.1", VERSION_ALPHA))?;
-            Ok(())
+            self.increment_prerelease(VERSION_ALPHA)
         }
     }
 
     fn increment_beta(&mut self) -> Result<(), FatalError> {
-        if let Some((pre_ext, pre_ext_ver)) = self.prerelease_id_version()? {
-            if pre_ext == VERSION_RC {
-                Err(FatalError::InvalidReleaseLevel(VERSION_BETA.to_owned()))
-            } else {
-                let new_ext_ver = if pre_ext == VERSION_BETA {
-                    pre_ext_ver.unwrap_or(0) + 1
-                } else {
-                    1
-                };
-                self.pre = semver::Prerelease::new(&format!("{}.{}", VERSION_BETA, new_ext_ver))?;
-                Ok(())
-            }
+        let pre_ext = prerelease_id(self);
+        if pre_ext == VERSION_RC {
+            Err(FatalError::InvalidReleaseLevel(VERSION_BETA.to_owned()))
         } else {
-            self.increment_patch();
-            self.pre = semver::Prerelease::new(&format!("{}.1", VERSION_BETA))?;
-            Ok(())
+            self.increment_prerelease(VERSION_BETA)
         }
     }
 
     fn increment_rc(&mut self) -> Result<(), FatalError> {
-        if let Some((pre_ext, pre_ext_ver)) = self.prerelease_id_version()? {
-            let new_ext_ver = if pre_ext == VERSION_RC {
-                pre_ext_ver.unwrap_or(0) + 1
+        self.increment_prerelease(VERSION_RC)
+    }
+
+    fn increment_prerelease(&mut self, id: &str) -> Result<(), FatalError> {
+        if self.is_prerelease() {
+            // Switching identifiers starts the count over, whatever the old scheme was
+            let new_ext_ver = if prerelease_id(self) == id {
+                self.prerelease_id_version()?
+                    .and_then(|(_, pre_ext_ver)| pre_ext_ver)
+                    .unwrap_or(0)
+                    + 1
             } else {
                 1
             };
-            self.pre = semver::Prerelease::new(&format!("{}.{}", VERSION_RC, new_ext_ver))?;
-            Ok(())
+            self.pre = semver::Prerelease::new(&format!("{}.{}", id, new_ext_ver))?;
         } else {
             self.increment_patch();
-            self.pre = semver::Prerelease::new(&format!("{}.1", VERSION_RC))?;
-            Ok(())
+            self.pre = semver::Prerelease::new(&format!("{}.1", id))?;
+        }
+        Ok(())
+    }
+
+    /// Increment the pre-release counter, or start an `id` one
+    ///
+    /// Switching to `id` restarts the count at `1`, unless that would sort before the current
+    /// pre-release, in which case the current identifier is kept.
+    fn bump_prerelease(&mut self, id: &str) -> Result<(), FatalError> {
+        match self.prerelease_id_version() {
+            Ok(Some((current, Some(_)))) if current != id => {
+                let mut switched = self.clone();
+                switched.increment_prerelease(id)?;
+                if switched > *self {
+                    *self = switched;
+                    Ok(())
+                } else {
+                    self.increment_prerelease(&current)
+                }
+            }
+            _ => self.increment_prerelease(id),
         }
     }
 
@@ -356,10 +446,56 @@ impl VersionExt for semver::Version {
     }
 }
 
+/// The identifier part of the pre-release, like `alpha` in `alpha.1`
+fn prerelease_id(version: &semver::Version) -> &str {
+    version.pre.as_str().split('.').next().unwrap_or_default()
+}
+
+/// The lowest version `comparator` allows, if it has a lower bound
+fn lower_bound(comparator: &semver::Comparator) -> Option<semver::Version> {
+    let major = comparator.major;
+    let minor = comparator.minor.unwrap_or(0);
+    let patch = comparator.patch.unwrap_or(0);
+    match comparator.op {
+        semver::Op::Exact
+        | semver::Op::GreaterEq
+        | semver::Op::Tilde
+        | semver::Op::Caret
+        | semver::Op::Wildcard => {
+            let mut version = semver::Version::new(major, minor, patch);
+            version.pre = comparator.pre.clone();
+            Some(version)
+        }
+        semver::Op::Greater => match (comparator.minor, comparator.patch) {
+            (Some(_), Some(_)) => Some(semver::Version::new(major, minor, patch + 1)),
+            (Some(_), None) => Some(semver::Version::new(major, minor + 1, 0)),
+            _ => Some(semver::Version::new(major + 1, 0, 0)),
+        },
+        _ => None,
+    }
+}
+
 static VERSION_ALPHA: &str = "alpha";
 static VERSION_BETA: &str = "beta";
 static VERSION_RC: &str = "rc";
 
+/// The kind of API change `next` is allowed to make relative to `prev`
+///
+/// Follows cargo's interpretation of semver, where the left-most non-zero field is the breaking
+/// one.
+pub fn release_type(prev: &semver::Version, next: &semver::Version) -> &'static str {
+    if prev.major != next.major
+        || (next.major == 0 && prev.minor != next.minor)
+        || (next.major == 0 && next.minor == 0 && prev.patch != next.patch)
+    {
+        "major"
+    } else if prev.minor != next.minor {
+        "minor"
+    } else {
+        "patch"
+    }
+}
+
 pub fn set_requirement(
     req: &semver::VersionReq,
     version: &semver::Version,
@@ -503,6 +639,50 @@ mod test {
             assert_eq!(v3, semver::Version::parse("1.0.1-rc.2").unwrap());
         }
 
+        #[test]
+        fn prerelease() {
+            let mut v = semver::Version::parse("1.0.0").unwrap();
+            v.increment_prerelease("rc").unwrap();
+            assert_eq!(v, semver::Version::parse("1.0.1-rc.1").unwrap());
+
+            let mut v2 = semver::Version::parse("1.0.1-rc.1").unwrap();
+            v2.increment_prerelease("rc").unwrap();
+            assert_eq!(v2, semver::Version::parse("1.0.1-rc.2").unwrap());
+
+            let mut v3 = semver::Version::parse("1.0.1-alpha.1").unwrap();
+            v3.increment_prerelease("rc").unwrap();
+            assert_eq!(v3, semver::Version::parse("1.0.1-rc.1").unwrap());
+
+            let mut v4 = semver::Version::parse("1.0.1-alpha.x.2").unwrap();
+            v4.increment_prerelease("rc").unwrap();
+            assert_eq!(v4, semver::Version::parse("1.0.1-rc.1").unwrap());
+        }
+
+        #[test]
+        fn bump_prerelease() {
+            let mut v = semver::Version::parse("1.0.0").unwrap();
+            v.bump_prerelease("rc").unwrap();
+            assert_eq!(v, semver::Version::parse("1.0.1-rc.1").unwrap());
+
+            let mut v2 = semver::Version::parse("1.0.0-rc.1").unwrap();
+            v2.bump_prerelease("rc").unwrap();
+            assert_eq!(v2, semver::Version::parse("1.0.0-rc.2").unwrap());
+
+            // Switching identifiers restarts the count
+            let mut v5 = semver::Version::parse("1.0.0-alpha.1").unwrap();
+            v5.bump_prerelease("rc").unwrap();
+            assert_eq!(v5, semver::Version::parse("1.0.0-rc.1").unwrap());
+
+            // Keeps the current identifier rather than going backwards
+            let mut v3 = semver::Version::parse("1.0.0-rc.1").unwrap();
+            v3.bump_prerelease("alpha").unwrap();
+            assert_eq!(v3, semver::Version::parse("1.0.0-rc.2").unwrap());
+
+            let mut v4 = semver::Version::parse("1.0.1-dev").unwrap();
+            v4.bump_prerelease("alpha").unwrap();
+            assert_eq!(v4, semver::Version::parse("1.0.1-alpha.1").unwrap());
+        }
+
         #[test]
         fn metadata() {
             let mut v = semver::Version::parse("1.0.0").unwrap();
@@ -511,6 +691,134 @@ mod test {
         }
     }
 
+    mod pre_1_0 {
+        use super::*;
+
+        use crate::config::BreakingLevel;
+
+        fn bump(level: BumpLevel, breaking: BreakingLevel) -> semver::Version {
+            let mut version = semver::Version::parse("0.2.3").unwrap();
+            level
+                .pre_1_0(&version, breaking)
+                .bump_version(&mut version, None, "alpha")
+                .unwrap();
+            version
+        }
+
+        #[test]
+        fn major() {
+            assert_eq!(
+                bump(BumpLevel::Major, BreakingLevel::Major).to_string(),
+                "1.0.0"
+            );
+            assert_eq!(
+                bump(BumpLevel::Minor, BreakingLevel::Major).to_string(),
+                "0.3.0"
+            );
+            assert_eq!(
+                bump(BumpLevel::Patch, BreakingLevel::Major).to_string(),
+                "0.2.4"
+            );
+        }
+
+        #[test]
+        fn minor() {
+            assert_eq!(
+                bump(BumpLevel::Major, BreakingLevel::Minor).to_string(),
+                "0.3.0"
+            );
+            assert_eq!(
+                bump(BumpLevel::Minor, BreakingLevel::Minor).to_string(),
+                "0.2.4"
+            );
+            assert_eq!(
+                bump(BumpLevel::Patch, BreakingLevel::Minor).to_string(),
+                "0.2.4"
+            );
+        }
+
+        #[test]
+        fn stable() {
+            let mut version = semver::Version::parse("1.2.3").unwrap();
+            BumpLevel::Major
+                .pre_1_0(&version, BreakingLevel::Minor)
+                .bump_version(&mut version, None, "alpha")
+                .unwrap();
+            assert_eq!(version.to_string(), "2.0.0");
+        }
+    }
+
+    mod resolve {
+        use super::*;
+
+        fn resolve(req: &str, published: &[&str]) -> Result<TargetVersion, FatalError> {
+            let published: Vec<_> = published
+                .iter()
+                .map(|v| semver::Version::parse(v).unwrap())
+                .collect();
+            TargetVersion::parse_set_version(req)
+                .unwrap()
+                .resolve("foo", &published)
+        }
+
+        fn assert_resolved(req: &str, published: &[&str], expected: &str) {
+            match resolve(req, published).unwrap() {
+                TargetVersion::Absolute(actual) => assert_eq!(actual.to_string(), expected),
+                actual => panic!("unresolved {}", actual),
+            }
+        }
+
+        #[test]
+        fn exact_version() {
+            assert_resolved("1.2.3", &["1.2.3"], "1.2.3");
+        }
+
+        #[test]
+        fn lowest_unpublished() {
+            assert_resolved(">=1.2, <1.3", &[], "1.2.0");
+            assert_resolved(">=1.2, <1.3", &["1.2.0", "1.2.1", "1.3.0"], "1.2.2");
+            assert_resolved(">1.2.1", &["1.2.3"], "1.2.2");
+            assert_resolved("~1.4", &["1.4.0"], "1.4.1");
+        }
+
+        #[test]
+        fn partial_version() {
+            assert!(TargetVersion::parse_set_version("1.2").is_err());
+            assert!(TargetVersion::parse_set_version(">=1.2, 1.3").is_err());
+            assert_resolved("^1.2", &["1.2.0"], "1.2.1");
+        }
+
+        #[test]
+        fn unsatisfiable() {
+            assert!(resolve("=1.2.0", &["1.2.0"]).is_err());
+            assert!(resolve(">=1.2.0, <1.2.2", &["1.2.0", "1.2.1"]).is_err());
+        }
+    }
+
+    mod release_type {
+        use super::*;
+
+        fn assert_release_type(prev: &str, next: &str, expected: &str) {
+            let prev = semver::Version::parse(prev).unwrap();
+            let next = semver::Version::parse(next).unwrap();
+            assert_eq!(release_type(&prev, &next), expected);
+        }
+
+        #[test]
+        fn stable() {
+            assert_release_type("1.0.0", "2.0.0", "major");
+            assert_release_type("1.0.0", "1.1.0", "minor");
+            assert_release_type("1.0.0", "1.0.1", "patch");
+        }
+
+        #[test]
+        fn unstable() {
+            assert_release_type("0.1.0", "0.2.0", "major");
+            assert_release_type("0.1.0", "0.1.1", "patch");
+            assert_release_type("0.0.1", "0.0.2", "major");
+        }
+    }
+
     mod set_requirement {
         use super::*;
 
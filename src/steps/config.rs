@@ -6,6 +6,7 @@ use crate::config::Config;
 use crate::config::ConfigArgs;
 use crate::error::FatalError;
 use crate::error::ProcessError;
+use crate::steps::plan;
 
 /// Dump workspace configuration
 #[derive(Debug, Clone, clap::Args)]
@@ -19,18 +20,24 @@ pub struct ConfigStep {
 
     #[command(flatten)]
     config: ConfigArgs,
+
+    #[command(subcommand)]
+    action: Option<ConfigAction>,
+}
+
+#[derive(Debug, Clone, clap::Subcommand)]
+enum ConfigAction {
+    Init(InitStep),
 }
 
 impl ConfigStep {
     pub fn run(&self) -> Result<(), ProcessError> {
+        if let Some(ConfigAction::Init(init)) = &self.action {
+            return init.run();
+        }
+
         log::trace!("Initializing");
-        let ws_meta = self
-            .manifest
-            .metadata()
-            // When evaluating dependency ordering, we need to consider optional depednencies
-            .features(cargo_metadata::CargoOpt::AllFeatures)
-            .exec()
-            .map_err(FatalError::from)?;
+        let ws_meta = super::load_metadata(&self.manifest, &self.config)?;
 
         let release_config =
             if let Some(root_id) = ws_meta.resolve.as_ref().and_then(|r| r.root.as_ref()) {
@@ -60,3 +67,192 @@ impl ConfigStep {
         Ok(())
     }
 }
+
+/// Write a commented `release.toml` to the workspace root
+#[derive(Debug, Clone, clap::Args)]
+pub struct InitStep {
+    #[command(flatten)]
+    manifest: clap_cargo::Manifest,
+
+    /// Overwrite an existing `release.toml`
+    #[arg(long)]
+    force: bool,
+}
+
+impl InitStep {
+    pub fn run(&self) -> Result<(), ProcessError> {
+        let ws_meta = self
+            .manifest
+            .metadata()
+            .no_deps()
+            .exec()
+            .map_err(FatalError::from)?;
+        let path = ws_meta.workspace_root.as_std_path().join("release.toml");
+        if path.exists() && !self.force {
+            log::error!(
+                "{} already exists, pass `--force` to overwrite it",
+                path.display()
+            );
+            return Err(101.into());
+        }
+
+        std::fs::write(&path, INIT_TEMPLATE)?;
+        log::info!("Wrote {}", path.display());
+        Ok(())
+    }
+}
+
+/// The `release.toml` written by `cargo release config init`
+///
+/// Settings left at their default are commented out with a bare `#`, explanations with `# `.
+const INIT_TEMPLATE: &str = r#"# cargo-release configuration, see
+# https://github.com/crate-ci/cargo-release/blob/master/docs/reference.md
+
+# Branches releases may be made from
+#allow-branch = ["*", "!HEAD"]
+
+# Sign release commits and tags with GPG
+#sign-commit = true
+#sign-tag = true
+
+# In a workspace, use one commit for all crates' version bumps rather than one per crate
+#consolidate-commits = true
+
+# Commit message and tag templates, see "Placeholders" in the reference
+#pre-release-commit-message = "chore: Release {{crate_name}} version {{version}}"
+#tag-message = "chore: Release {{crate_name}} version {{version}}"
+#tag-name = "{{prefix}}v{{version}}"
+
+# Bump to a development version, like `1.2.1-alpha.0`, after releasing
+#dev-version = true
+#post-release-commit-message = "chore: Start development of {{next_version}}"
+
+# Whether to publish to crates.io and push to the remote
+#publish = true
+#push = true
+#push-remote = "origin"
+
+# Run a command in the crate's root before the release commit
+#pre-release-hook = ["./release-hook.sh"]
+
+# Update version strings in other files, see "{Pre,Post}-release Replacements" in the reference
+#[[pre-release-replacements]]
+#file = "CHANGELOG.md"
+#search = "Unreleased"
+#replace = "{{version}}"
+#[[pre-release-replacements]]
+#file = "CHANGELOG.md"
+#search = "ReleaseDate"
+#replace = "{{date}}"
+"#;
+
+/// Output format of `--dump-config`
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum DumpFormat {
+    Toml,
+    Json,
+}
+
+#[derive(serde::Serialize)]
+struct Dump<'p> {
+    workspace: Config,
+    package: std::collections::BTreeMap<&'p str, Config>,
+}
+
+/// The fully-resolved config of the workspace and of each package, defaults included
+pub fn dump<'p>(
+    ws_config: &Config,
+    pkgs: impl IntoIterator<Item = &'p plan::PackageRelease>,
+    format: DumpFormat,
+) -> Result<String, FatalError> {
+    let resolved = |config: &Config| {
+        let mut resolved = Config::from_defaults();
+        resolved.update(config);
+        resolved
+    };
+    let package = pkgs
+        .into_iter()
+        .map(|pkg| {
+            let mut config = resolved(&pkg.config);
+            config.tag_prefix = Some(pkg.config.tag_prefix(pkg.is_root).into_owned());
+            (pkg.meta.name.as_str(), config)
+        })
+        .collect();
+    let dump = Dump {
+        workspace: resolved(ws_config),
+        package,
+    };
+    let output = match format {
+        DumpFormat::Toml => toml_edit::easy::to_string_pretty(&dump)?,
+        DumpFormat::Json => {
+            let mut output = serde_json::to_string_pretty(&dump).map_err(std::io::Error::from)?;
+            output.push('\n');
+            output
+        }
+    };
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod init_template {
+        use super::*;
+
+        #[test]
+        fn valid() {
+            let commented: Config = toml_edit::easy::from_str(INIT_TEMPLATE).unwrap();
+            assert!(commented.sign_commit.is_none());
+
+            let uncommented: String = INIT_TEMPLATE
+                .lines()
+                .map(|line| match line.strip_prefix('#') {
+                    Some(setting) if !setting.starts_with(' ') && !setting.is_empty() => setting,
+                    _ => line,
+                })
+                .map(|line| format!("{}\n", line))
+                .collect();
+            let config: Config = toml_edit::easy::from_str(&uncommented).unwrap();
+            assert_eq!(config.sign_commit, Some(true));
+            assert_eq!(config.pre_release_replacements().len(), 2);
+        }
+    }
+
+    mod dump {
+        use super::*;
+
+        fn pkgs() -> Vec<plan::PackageRelease> {
+            crate::test_support::pkgs("simple")
+                .into_iter()
+                .map(|mut pkg| {
+                    pkg.config.sign_commit = Some(true);
+                    pkg
+                })
+                .collect()
+        }
+
+        #[test]
+        fn toml() {
+            let output = dump(&Config::default(), &pkgs(), DumpFormat::Toml).unwrap();
+            let value: toml_edit::easy::Value = toml_edit::easy::from_str(&output).unwrap();
+            assert_eq!(value["workspace"]["sign-commit"].as_bool(), Some(false));
+            assert_eq!(
+                value["package"]["simple"]["sign-commit"].as_bool(),
+                Some(true)
+            );
+            assert_eq!(
+                value["package"]["simple"]["tag-name"].as_str(),
+                Some("{{prefix}}v{{version}}")
+            );
+        }
+
+        #[test]
+        fn json() {
+            let output = dump(&Config::default(), &pkgs(), DumpFormat::Json).unwrap();
+            let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+            assert_eq!(value["workspace"]["sign-commit"], false);
+            assert_eq!(value["package"]["simple"]["sign-commit"], true);
+        }
+    }
+}
@@ -6,30 +6,162 @@ pub mod release;
 pub mod replace;
 pub mod tag;
 pub mod version;
+pub mod yank;
+
+/// Load the workspace metadata, resolving dependencies with the configured `dependency-features`
+pub fn load_metadata(
+    manifest: &clap_cargo::Manifest,
+    args: &crate::config::ConfigArgs,
+) -> Result<cargo_metadata::Metadata, crate::error::FatalError> {
+    let ws_meta = manifest
+        .metadata()
+        .no_deps()
+        .exec()
+        .map_err(crate::error::FatalError::from)?;
+    let ws_config = crate::config::load_workspace_config(args, &ws_meta)?;
+
+    let mut cmd = manifest.metadata();
+    // When evaluating dependency ordering, we need to consider optional dependencies
+    if let Some(features) = ws_config.dependency_features().cargo_opt() {
+        cmd.features(features);
+    }
+    cmd.exec().map_err(crate::error::FatalError::from)
+}
+
+/// Fail when `ids` depend on each other, as there is then no order to publish them in
+///
+/// Cycles through dev-dependencies don't affect the publish order and are only noted.
+pub fn verify_no_dependency_cycles(
+    ws_meta: &cargo_metadata::Metadata,
+    ids: &[&cargo_metadata::PackageId],
+) -> Result<(), crate::error::FatalError> {
+    let names = |cycle: Vec<&cargo_metadata::PackageId>| {
+        cycle
+            .into_iter()
+            .map(|id| ws_meta[id].name.as_str())
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    };
+    if let Some(cycle) = crate::ops::cargo::dependency_cycle(ws_meta, ids, false) {
+        return Err(crate::error::FatalError::DependencyCycle(names(cycle)));
+    }
+    if let Some(cycle) = crate::ops::cargo::dependency_cycle(ws_meta, ids, true) {
+        log::info!(
+            "Ignoring dev-dependency cycle {}, it doesn't affect the publish order",
+            names(cycle)
+        );
+    }
+    Ok(())
+}
 
 pub fn verify_git_is_clean(
     path: &std::path::Path,
+    ws_config: &crate::config::Config,
     dry_run: bool,
     level: log::Level,
 ) -> Result<bool, crate::error::ProcessError> {
     let mut success = true;
-    if crate::ops::git::is_dirty(path)? {
+    let mut dirty = false;
+    let mut dirty_files = crate::ops::git::dirty_files(path)?;
+    if ws_config.allow_dirty_lockfile() {
+        dirty_files.retain(|file| {
+            let lockfile = file.file_name() == Some(std::ffi::OsStr::new("Cargo.lock"));
+            if lockfile {
+                log::debug!("Ignoring uncommitted changes to {}", file.display());
+            }
+            !lockfile
+        });
+    }
+    if !dirty_files.is_empty() {
         log::log!(
             level,
             "Uncommitted changes detected, please commit before release."
         );
-        if level == log::Level::Error {
-            success = false;
-            if !dry_run {
-                return Err(101.into());
-            }
+        dirty = true;
+    }
+    if ws_config.check_submodules() {
+        for (submodule, reason) in crate::ops::git::dirty_submodules(path)? {
+            log::log!(level, "Submodule {} {}", submodule.display(), reason);
+            dirty = true;
+        }
+    }
+    if dirty && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+    Ok(success)
+}
+
+/// Catch releasing from a commit other than the reviewed `rev`
+pub fn verify_rev(
+    path: &std::path::Path,
+    rev: &str,
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let head = crate::ops::git::head_id(path)?;
+    let mismatch = match crate::ops::git::rev_id(path, rev)? {
+        Some(id) if id == head => false,
+        Some(id) => {
+            log::log!(
+                level,
+                "HEAD ({}) is not `{}` ({}), check it out with `git checkout {}`",
+                head,
+                rev,
+                id,
+                rev
+            );
+            true
+        }
+        None => {
+            log::log!(level, "`{}` is not a commit", rev);
+            true
+        }
+    };
+    if mismatch && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
         }
     }
+
     Ok(success)
 }
 
+/// Resolve a `--set-version` requirement for `pkg` against the versions it already published
+pub fn resolve_target_version(
+    level_or_version: &crate::ops::version::TargetVersion,
+    pkg: &plan::PackageRelease,
+    index: Option<&crates_index::Index>,
+) -> Result<crate::ops::version::TargetVersion, crate::error::FatalError> {
+    let crate_name = pkg.meta.name.as_str();
+    let published = match index {
+        Some(index) if pkg.config.registry().is_none() => {
+            crate::ops::cargo::published_versions(index, crate_name)
+        }
+        Some(_) => {
+            log::debug!(
+                "Not checking {}'s published versions in its registry",
+                crate_name
+            );
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+    level_or_version.resolve(crate_name, &published)
+}
+
+/// Catch planned tags that already exist
+///
+/// With `allow-existing-tag`, a tag at `HEAD` is reused, unless `commit_pending` says a release
+/// commit is still to be made: the tag could then never point at the commit being released.
 pub fn verify_tags_missing(
     pkgs: &[plan::PackageRelease],
+    commit_pending: bool,
     dry_run: bool,
     level: log::Level,
 ) -> Result<bool, crate::error::ProcessError> {
@@ -43,6 +175,37 @@ pub fn verify_tags_missing(
                 let cwd = &pkg.package_root;
                 if crate::ops::git::tag_exists(cwd, tag_name)? {
                     let crate_name = pkg.meta.name.as_str();
+                    if pkg.config.allow_existing_tag() && commit_pending {
+                        log::log!(
+                            level,
+                            "Tag `{}` already exists (for `{}`) but can't point at the release commit still to be made",
+                            tag_name,
+                            crate_name
+                        );
+                        tag_exists = true;
+                        continue;
+                    }
+                    if pkg.config.allow_existing_tag()
+                        && crate::ops::git::tag_points_at_head(cwd, tag_name)?
+                    {
+                        if pkg.config.sign_tag() && !crate::ops::git::is_tag_signed(cwd, tag_name)?
+                        {
+                            log::log!(
+                                level,
+                                "Tag `{}` already exists at HEAD (for `{}`) but its signature can't be verified",
+                                tag_name,
+                                crate_name
+                            );
+                            tag_exists = true;
+                            continue;
+                        }
+                        log::debug!(
+                            "Tag `{}` already exists at HEAD (for `{}`), reusing it",
+                            tag_name,
+                            crate_name
+                        );
+                        continue;
+                    }
                     log::log!(
                         level,
                         "Tag `{}` already exists (for `{}`)",
@@ -64,6 +227,286 @@ pub fn verify_tags_missing(
     Ok(success)
 }
 
+/// Catch tag names that collide across packages being released at different versions
+///
+/// Packages released together at the same version, like in a `shared-version` workspace, can
+/// share a tag.
+pub fn verify_tags_unique(
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut by_tag: std::collections::BTreeMap<&str, Vec<&plan::PackageRelease>> =
+        Default::default();
+    for pkg in pkgs {
+        if let Some(tag_name) = pkg.planned_tag.as_deref() {
+            by_tag.entry(tag_name).or_default().push(pkg);
+        }
+    }
+
+    let mut collision = false;
+    for (tag_name, tagged) in by_tag {
+        let mut versions = tagged.iter().map(|p| {
+            p.planned_version
+                .as_ref()
+                .unwrap_or(&p.initial_version)
+                .full_version_string
+                .as_str()
+        });
+        let first = versions.next();
+        if versions.any(|v| Some(v) != first) {
+            let crates = tagged
+                .iter()
+                .map(|p| {
+                    let version = p.planned_version.as_ref().unwrap_or(&p.initial_version);
+                    format!("{} {}", p.meta.name, version.full_version_string)
+                })
+                .collect::<Vec<_>>();
+            log::log!(
+                level,
+                "Tag `{}` would be created for each of {}; give them distinct `tag-prefix`es or a `tag-name` with `{{{{crate_name}}}}`",
+                tag_name,
+                crates.join(", ")
+            );
+            collision = true;
+        }
+    }
+    if collision && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+/// A literal `--tag-name` can only name the tag of a single package
+///
+/// `tag_name` comes from [`crate::config::TagArgs::literal_tag_name`] with the opening of the
+/// configured template `delimiters`.
+pub fn verify_literal_tag_name(
+    pkgs: &[plan::PackageRelease],
+    tag_name: Option<&str>,
+    delimiters: (&str, &str),
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let (open, close) = delimiters;
+    let tag_name = if let Some(tag_name) = tag_name {
+        tag_name
+    } else {
+        return Ok(success);
+    };
+    let tagged: Vec<_> = pkgs
+        .iter()
+        .filter(|p| p.planned_tag.is_some())
+        .map(|p| p.meta.name.as_str())
+        .collect();
+    if 1 < tagged.len() {
+        log::log!(
+            level,
+            "`--tag-name {}` would tag each of {}; use a placeholder like `{}crate_name{}` or set `tag-prefix` per package",
+            tag_name,
+            tagged.join(", "),
+            open,
+            close
+        );
+        if level == log::Level::Error {
+            success = false;
+            if !dry_run {
+                return Err(101.into());
+            }
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn verify_github_release(
+    path: &std::path::Path,
+    ws_config: &crate::config::Config,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    if github_release_pkgs(ws_config, pkgs).next().is_none() {
+        return Ok(success);
+    }
+    let mut misconfigured = false;
+    if let Err(err) = github_repo(path, ws_config) {
+        log::log!(level, "Cannot create GitHub releases: {}", err);
+        misconfigured = true;
+    }
+    if std::env::var_os(crate::ops::github::TOKEN_VAR).is_none() {
+        log::log!(
+            level,
+            "Cannot create GitHub releases: `{}` is not set",
+            crate::ops::github::TOKEN_VAR
+        );
+        misconfigured = true;
+    }
+    if !crate::ops::github::curl_available() {
+        log::log!(level, "Cannot create GitHub releases: `curl` can't be run");
+        misconfigured = true;
+    }
+    if misconfigured && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+/// Catch a `package.repository` that doesn't point at the repository being pushed to
+pub fn verify_repository(
+    path: &std::path::Path,
+    ws_config: &crate::config::Config,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let git_remote = ws_config.push_remote();
+    let remote_url = match crate::ops::git::remote_url(path, git_remote)? {
+        Some(remote_url) => remote_url,
+        None => {
+            log::debug!(
+                "Not checking `repository` fields, remote `{}` has no URL",
+                git_remote
+            );
+            return Ok(success);
+        }
+    };
+    let remote = crate::ops::git::normalize_url(&remote_url);
+
+    let mut mismatched = false;
+    for pkg in pkgs {
+        if let Some(repository) = pkg.meta.repository.as_deref() {
+            if crate::ops::git::normalize_url(repository) != remote {
+                let crate_name = pkg.meta.name.as_str();
+                log::log!(
+                    level,
+                    "{}'s repository {} doesn't match remote `{}` ({})",
+                    crate_name,
+                    repository,
+                    git_remote,
+                    remote_url
+                );
+                mismatched = true;
+            }
+        }
+    }
+    if mismatched && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn repository_level(ws_config: &crate::config::Config) -> log::Level {
+    if ws_config.repository_check() {
+        log::Level::Error
+    } else {
+        log::Level::Warn
+    }
+}
+
+/// Packages with `github-release` whose tags will be pushed, one per tag
+///
+/// Packages with `changelog = false` are left out, so a shared tag takes its notes from another.
+pub fn github_release_pkgs<'p>(
+    ws_config: &crate::config::Config,
+    pkgs: &'p [plan::PackageRelease],
+) -> impl Iterator<Item = &'p plan::PackageRelease> {
+    let push = ws_config.push();
+    let mut seen_tags = std::collections::HashSet::new();
+    pkgs.iter().filter(move |pkg| {
+        push && pkg.config.push()
+            && pkg.config.github_release()
+            && pkg.config.changelog()
+            && pkg
+                .planned_tag
+                .as_deref()
+                .map(|tag_name| seen_tags.insert(tag_name))
+                .unwrap_or(false)
+    })
+}
+
+/// Check each package's changelog has a section for its new version, or an `Unreleased` one for
+/// `pre-release-replacements` to fill in
+///
+/// Packages without a changelog, or with `changelog = false`, aren't checked.
+pub fn verify_changelog(
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+    let ws_root = ws_meta.workspace_root.as_std_path();
+    let mut missing = false;
+    for pkg in pkgs {
+        let version = match pkg.planned_version.as_ref() {
+            Some(version) if pkg.config.changelog() => version,
+            _ => continue,
+        };
+        let candidates = crate::ops::changelog::candidates(
+            &[pkg.package_root.as_path(), ws_root],
+            pkg.config.changelog_path(),
+        );
+        let path = match candidates.first() {
+            Some(path) => path,
+            None => {
+                log::debug!("No changelog found for {}", pkg.meta.name);
+                continue;
+            }
+        };
+        let changelog = std::fs::read_to_string(path)?;
+        let has_section = |version| crate::ops::changelog::section(&changelog, version).is_some();
+        if !has_section(&version.bare_version_string) && !has_section("Unreleased") {
+            log::log!(
+                level,
+                "{} has no section for {} {} or `Unreleased`",
+                path.display(),
+                pkg.meta.name,
+                version.bare_version_string
+            );
+            missing = true;
+        }
+    }
+    if missing && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+    Ok(success)
+}
+
+/// `owner/repo` of the GitHub repository behind the push remote
+pub fn github_repo(
+    path: &std::path::Path,
+    ws_config: &crate::config::Config,
+) -> Result<String, crate::error::FatalError> {
+    let git_remote = ws_config.push_remote();
+    crate::ops::git::remote_url(path, git_remote)?
+        .and_then(|url| crate::ops::github::repo_from_url(&url))
+        .ok_or_else(|| crate::error::FatalError::GitHubRemoteError(git_remote.to_owned()))
+}
+
 pub fn verify_tags_exist(
     pkgs: &[plan::PackageRelease],
     dry_run: bool,
@@ -100,6 +543,42 @@ pub fn verify_tags_exist(
     Ok(success)
 }
 
+/// Catch resuming after publishing when a crate never made it to crates.io
+pub fn verify_published(
+    pkgs: &[plan::PackageRelease],
+    index: &crates_index::Index,
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut unpublished = false;
+    for pkg in pkgs {
+        if !pkg.config.publish() || pkg.config.package_only() || pkg.config.registry().is_some() {
+            continue;
+        }
+        let crate_name = pkg.meta.name.as_str();
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        if !crate::ops::cargo::is_published(index, crate_name, &version.full_version_string) {
+            log::log!(
+                level,
+                "{} {} is not published",
+                crate_name,
+                version.full_version_string
+            );
+            unpublished = true;
+        }
+    }
+    if unpublished && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
 pub fn verify_git_branch(
     path: &std::path::Path,
     ws_config: &crate::config::Config,
@@ -136,54 +615,770 @@ pub fn verify_git_branch(
     Ok(success)
 }
 
-pub fn verify_if_behind(
-    path: &std::path::Path,
+pub fn verify_if_behind(
+    path: &std::path::Path,
+    ws_config: &crate::config::Config,
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let git_remote = ws_config.push_remote();
+    let branch = crate::ops::git::current_branch(path)?;
+    crate::ops::git::fetch(path, git_remote, &branch)?;
+    if crate::ops::git::is_behind_remote(path, git_remote, &branch)? {
+        log::log!(level, "{} is behind {}/{}", branch, git_remote, branch);
+        if level == log::Level::Error {
+            success = false;
+            if !dry_run {
+                return Err(101.into());
+            }
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn verify_push_branch(
+    path: &std::path::Path,
+    ws_config: &crate::config::Config,
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let push_branch = match ws_config.push_branch() {
+        Some(push_branch) if ws_config.push() && !ws_config.force_push() => push_branch,
+        _ => return Ok(success),
+    };
+    let git_remote = ws_config.push_remote();
+    if let Err(err) = crate::ops::git::fetch(path, git_remote, push_branch) {
+        log::debug!("Could not fetch {}/{}: {}", git_remote, push_branch, err);
+    }
+    if !crate::ops::git::is_fast_forward(path, git_remote, push_branch)? {
+        log::log!(
+            level,
+            "Pushing to {}/{} would not be a fast-forward; use `--force` to push anyway",
+            git_remote,
+            push_branch
+        );
+        if level == log::Level::Error {
+            success = false;
+            if !dry_run {
+                return Err(101.into());
+            }
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn verify_amend_unpushed(
+    path: &std::path::Path,
+    ws_config: &crate::config::Config,
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let git_remote = ws_config.push_remote();
+    let branch = crate::ops::git::current_branch(path)?;
+    if crate::ops::git::is_head_pushed(path, git_remote, &branch)? {
+        log::log!(
+            level,
+            "Cannot amend HEAD as it is already on {}/{}; use `--force-amend` to amend anyway",
+            git_remote,
+            branch
+        );
+        if level == log::Level::Error {
+            success = false;
+            if !dry_run {
+                return Err(101.into());
+            }
+        }
+    }
+
+    Ok(success)
+}
+
+/// With `lock-only`, the release commit must be able to include `Cargo.lock`
+pub fn verify_lock_tracked(
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    if !pkgs.iter().any(|p| p.config.lock_only()) {
+        return Ok(success);
+    }
+    let root = ws_meta.workspace_root.as_std_path();
+    let lock_path = root.join("Cargo.lock");
+    if !crate::ops::git::is_tracked(root, &lock_path)? {
+        log::log!(
+            level,
+            "`lock-only` requires {} to be committed",
+            lock_path.display()
+        );
+        if level == log::Level::Error {
+            success = false;
+            if !dry_run {
+                return Err(101.into());
+            }
+        }
+    }
+
+    Ok(success)
+}
+
+/// Catch a stale `Cargo.lock` that disagrees with the manifest about a package's version
+pub fn verify_locked_versions(
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let lock_path = ws_meta.workspace_root.as_std_path().join("Cargo.lock");
+    if !lock_path.exists() {
+        log::debug!("Not checking locked versions, no {}", lock_path.display());
+        return Ok(success);
+    }
+    let mut stale = false;
+    for pkg in pkgs {
+        let crate_name = pkg.meta.name.as_str();
+        let version = &pkg.initial_version.full_version_string;
+        let locked = crate::ops::cargo::locked_versions(&lock_path, crate_name)?;
+        if !locked.is_empty() && !locked.contains(version) {
+            log::log!(
+                level,
+                "{} is {} in Cargo.toml but {} in Cargo.lock",
+                crate_name,
+                version,
+                locked.join(", ")
+            );
+            stale = true;
+        }
+    }
+    if stale && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn if_behind_level(ws_config: &crate::config::Config) -> log::Level {
+    if ws_config.fail_if_behind() {
+        log::Level::Error
+    } else {
+        log::Level::Warn
+    }
+}
+
+pub fn verify_monotonically_increasing(
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut downgrades_present = false;
+    for pkg in pkgs {
+        if let Some(version) = pkg.planned_version.as_ref() {
+            if version.full_version < pkg.initial_version.full_version {
+                let crate_name = pkg.meta.name.as_str();
+                log::log!(
+                    level,
+                    "Cannot downgrade {} from {} to {}",
+                    crate_name,
+                    version.full_version,
+                    pkg.initial_version.full_version
+                );
+                downgrades_present = true;
+            }
+        }
+    }
+    if downgrades_present && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+/// Catch releasing a crate still at a placeholder version like `0.0.0`
+pub fn verify_placeholder_version(
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut placeholder_present = false;
+    for pkg in pkgs {
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        if is_placeholder_version(&version.full_version) {
+            let crate_name = pkg.meta.name.as_str();
+            log::log!(
+                level,
+                "{} is still at the placeholder version {}, set a version before releasing",
+                crate_name,
+                version.full_version_string
+            );
+            placeholder_present = true;
+        }
+    }
+    if placeholder_present && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+fn is_placeholder_version(version: &semver::Version) -> bool {
+    version.major == 0 && version.minor == 0 && version.patch == 0
+}
+
+pub fn placeholder_level(ws_config: &crate::config::Config) -> log::Level {
+    if ws_config.placeholder_check() {
+        log::Level::Error
+    } else {
+        log::Level::Warn
+    }
+}
+
+/// Catch publishing crates that depend on a workspace crate with `publish = false`
+///
+/// Dev-dependencies are fine since `cargo publish` strips them.
+pub fn verify_publishable_dependencies(
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let unpublishable: std::collections::HashSet<_> = ws_meta
+        .packages
+        .iter()
+        .filter(|p| ws_meta.workspace_members.contains(&p.id))
+        .filter(|p| p.publish.as_deref() == Some(&[]))
+        .map(|p| p.name.as_str())
+        .collect();
+
+    let mut unpublishable_present = false;
+    for pkg in pkgs {
+        if !pkg.config.publish() {
+            continue;
+        }
+        let crate_name = pkg.meta.name.as_str();
+        for dep in &pkg.meta.dependencies {
+            if dep.kind != cargo_metadata::DependencyKind::Development
+                && dep.path.is_some()
+                && unpublishable.contains(dep.name.as_str())
+            {
+                log::log!(
+                    level,
+                    "{} cannot be published because it depends on {} which has `publish = false`; make {} a dev-dependency or allow publishing it",
+                    crate_name,
+                    dep.name,
+                    dep.name
+                );
+                unpublishable_present = true;
+            }
+        }
+    }
+    if unpublishable_present && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+/// Catch a `rust-version` too old for the crate's `edition` or its workspace dependencies
+///
+/// This is a heuristic; it can't tell whether a newer dependency is actually exposed.
+pub fn verify_edition(
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut mismatch_present = false;
+    for pkg in pkgs {
+        let crate_name = pkg.meta.name.as_str();
+        let rust_version = match pkg.meta.rust_version.as_ref().and_then(rust_version_min) {
+            Some(rust_version) => rust_version,
+            None => continue,
+        };
+
+        if let Some((edition, edition_min)) = edition_rust_version(&pkg.meta.edition) {
+            if rust_version < edition_min {
+                log::log!(
+                    level,
+                    "{} declares rust-version {} but edition {} needs {}",
+                    crate_name,
+                    rust_version,
+                    edition,
+                    edition_min
+                );
+                mismatch_present = true;
+            }
+        }
+
+        for dep in &pkg.meta.dependencies {
+            if dep.kind == cargo_metadata::DependencyKind::Development || dep.path.is_none() {
+                continue;
+            }
+            let dep_rust_version = ws_meta
+                .packages
+                .iter()
+                .find(|p| p.name == dep.name && ws_meta.workspace_members.contains(&p.id))
+                .and_then(|p| p.rust_version.as_ref())
+                .and_then(rust_version_min);
+            if let Some(dep_rust_version) = dep_rust_version {
+                if rust_version < dep_rust_version {
+                    log::log!(
+                        level,
+                        "{} declares rust-version {} but depends on {} which needs {}",
+                        crate_name,
+                        rust_version,
+                        dep.name,
+                        dep_rust_version
+                    );
+                    mismatch_present = true;
+                }
+            }
+        }
+    }
+    if mismatch_present && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+/// The oldest Rust a `rust-version` requirement allows
+fn rust_version_min(req: &semver::VersionReq) -> Option<semver::Version> {
+    let comparator = req.comparators.first()?;
+    Some(semver::Version::new(
+        comparator.major,
+        comparator.minor.unwrap_or(0),
+        comparator.patch.unwrap_or(0),
+    ))
+}
+
+/// The name of `edition` and the first Rust release supporting it
+fn edition_rust_version(
+    edition: &cargo_metadata::Edition,
+) -> Option<(&'static str, semver::Version)> {
+    match edition {
+        cargo_metadata::Edition::E2015 => Some(("2015", semver::Version::new(1, 0, 0))),
+        cargo_metadata::Edition::E2018 => Some(("2018", semver::Version::new(1, 31, 0))),
+        cargo_metadata::Edition::E2021 => Some(("2021", semver::Version::new(1, 56, 0))),
+        _ => None,
+    }
+}
+
+pub fn edition_level(ws_config: &crate::config::Config) -> log::Level {
+    if ws_config.edition_check() {
+        log::Level::Error
+    } else {
+        log::Level::Warn
+    }
+}
+
+/// Report every dependency of a crate being published that only has a `path`
+///
+/// `cargo publish` requires a `version` on normal and build dependencies but would only report
+/// the first one it hits.  Dev-dependencies are stripped on publish, so they are skipped.
+pub fn verify_no_path_only_deps_in_publish(
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut path_only = Vec::new();
+    for pkg in pkgs {
+        if !pkg.config.publish() {
+            continue;
+        }
+        for dep in &pkg.meta.dependencies {
+            if dep.kind != cargo_metadata::DependencyKind::Development
+                && dep.path.is_some()
+                && dep.req == semver::VersionReq::STAR
+            {
+                path_only.push((pkg.meta.name.as_str(), dep.name.as_str()));
+            }
+        }
+    }
+    if !path_only.is_empty() {
+        log::log!(
+            level,
+            "Dependencies need a `version` to be published:\n{}",
+            path_only
+                .iter()
+                .map(|(crate_name, dep_name)| format!("  {} -> {}", crate_name, dep_name))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        if level == log::Level::Error {
+            success = false;
+            if !dry_run {
+                return Err(101.into());
+            }
+        }
+    }
+
+    Ok(success)
+}
+
+/// Catch crates being published with both `enable-all-features` and `enable-features`
+pub fn verify_features_exclusive(
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut conflicting = false;
+    for pkg in pkgs {
+        if pkg.config.publish()
+            && pkg.config.enable_all_features()
+            && !pkg.config.enable_features().is_empty()
+        {
+            log::log!(
+                level,
+                "{} enables all features while also selecting features, pick one",
+                pkg.meta.name
+            );
+            conflicting = true;
+        }
+    }
+    if conflicting && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+/// Catch crates being published to a registry that isn't in their `allowed-registries`
+pub fn verify_registries_allowed(
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut disallowed = false;
+    for pkg in pkgs {
+        if pkg.config.publish() && !pkg.config.package_only() && !pkg.config.is_registry_allowed() {
+            log::log!(
+                level,
+                "{} may not be published to `{}`, it isn't in `allowed-registries`",
+                pkg.meta.name,
+                pkg.config.registry().unwrap_or("crates-io")
+            );
+            disallowed = true;
+        }
+    }
+    if disallowed && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+/// How to report releasing a version below what the registry already has
+///
+/// This is legitimate for backports, which can turn off `registry-newer-check` to only warn.
+pub fn registry_newer_level(ws_config: &crate::config::Config) -> log::Level {
+    if ws_config.registry_newer_check() {
+        log::Level::Error
+    } else {
+        log::Level::Warn
+    }
+}
+
+/// Catch publishing a version below what the registry already has
+pub fn verify_registry_newer(
+    pkgs: &[plan::PackageRelease],
+    index: &crates_index::Index,
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut lower_present = false;
+    for pkg in pkgs {
+        if !pkg.config.publish() || pkg.config.package_only() || pkg.config.registry().is_some() {
+            continue;
+        }
+        let crate_name = pkg.meta.name.as_str();
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        let max_published = crate::ops::cargo::max_published_version(
+            index,
+            crate_name,
+            pkg.config.include_prereleases(),
+        );
+        if let Some(max_published) = max_published {
+            if version.full_version < max_published {
+                log::log!(
+                    level,
+                    "Registry already has a higher version of {} ({}) than {}",
+                    crate_name,
+                    max_published,
+                    version.full_version
+                );
+                lower_present = true;
+            }
+        }
+    }
+    if lower_present && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn verify_semver_compatibility(
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut incompatible = false;
+    for pkg in pkgs {
+        if !pkg.config.semver_check() {
+            continue;
+        }
+        let version = if let Some(version) = pkg.planned_version.as_ref() {
+            version
+        } else {
+            continue;
+        };
+
+        let crate_name = pkg.meta.name.as_str();
+        let release_type = crate::ops::version::release_type(
+            &pkg.initial_version.bare_version,
+            &version.bare_version,
+        );
+        let pkgid = if 1 < ws_meta.workspace_members.len() {
+            Some(crate_name)
+        } else {
+            None
+        };
+        log::debug!(
+            "Checking {} API against a {} release",
+            crate_name,
+            release_type
+        );
+        match crate::ops::cargo::semver_checks(
+            &pkg.manifest_path,
+            pkgid,
+            release_type,
+            pkg.config.cargo_bin(),
+        )? {
+            Some(true) => {}
+            Some(false) => {
+                log::log!(
+                    level,
+                    "{} has API changes incompatible with a {} release to {}",
+                    crate_name,
+                    release_type,
+                    version.full_version_string
+                );
+                incompatible = true;
+            }
+            None => {
+                log::warn!(
+                    "Skipping semver check for {}; `cargo-semver-checks` is not installed",
+                    crate_name
+                );
+            }
+        }
+    }
+    if incompatible && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn verify_features_buildable(
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+    level: log::Level,
+) -> Result<bool, crate::error::ProcessError> {
+    let mut success = true;
+
+    let mut broken = false;
+    for pkg in pkgs {
+        if pkg.planned_version.is_none() {
+            continue;
+        }
+
+        let crate_name = pkg.meta.name.as_str();
+        let pkgid = if 1 < ws_meta.workspace_members.len() {
+            Some(crate_name)
+        } else {
+            None
+        };
+        for features in pkg.config.verify_features().feature_sets() {
+            let flags = match &features {
+                crate::ops::cargo::Features::None => "--no-default-features".to_owned(),
+                crate::ops::cargo::Features::Selective(set) => {
+                    format!("--no-default-features --features {}", set.join(","))
+                }
+                crate::ops::cargo::Features::All => "--all-features".to_owned(),
+            };
+            log::debug!("Checking {} builds with {}", crate_name, flags);
+            if !crate::ops::cargo::check(
+                &pkg.manifest_path,
+                pkgid,
+                &features,
+                pkg.config.target.as_deref(),
+                pkg.config.cargo_bin(),
+            )? {
+                log::log!(level, "{} fails to build with {}", crate_name, flags);
+                broken = true;
+            }
+        }
+    }
+    if broken && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
+        }
+    }
+
+    Ok(success)
+}
+
+pub fn verify_commands(
+    ws_meta: &cargo_metadata::Metadata,
     ws_config: &crate::config::Config,
     dry_run: bool,
     level: log::Level,
 ) -> Result<bool, crate::error::ProcessError> {
     let mut success = true;
 
-    let git_remote = ws_config.push_remote();
-    let branch = crate::ops::git::current_branch(path)?;
-    crate::ops::git::fetch(path, git_remote, &branch)?;
-    if crate::ops::git::is_behind_remote(path, git_remote, &branch)? {
-        log::log!(level, "{} is behind {}/{}", branch, git_remote, branch);
-        if level == log::Level::Error {
-            success = false;
-            if !dry_run {
-                return Err(101.into());
-            }
+    let mut broken = false;
+    for command in ws_config.verify_commands().commands() {
+        let args = command.args();
+        let command_line = args.join(" ");
+        log::debug!("Running `{}`", command_line);
+        if crate::ops::cmd::call_on_path(args, ws_meta.workspace_root.as_std_path(), false)? {
+            log::debug!("`{}` passed", command_line);
+        } else {
+            log::log!(level, "Verify command `{}` failed", command_line);
+            broken = true;
+        }
+    }
+    if broken && level == log::Level::Error {
+        success = false;
+        if !dry_run {
+            return Err(101.into());
         }
     }
 
     Ok(success)
 }
 
-pub fn verify_monotonically_increasing(
+pub fn verify_version_edits(
+    ws_meta: &cargo_metadata::Metadata,
     pkgs: &[plan::PackageRelease],
     dry_run: bool,
     level: log::Level,
 ) -> Result<bool, crate::error::ProcessError> {
     let mut success = true;
 
-    let mut downgrades_present = false;
+    let root = ws_meta.workspace_root.as_std_path();
+    let mut disallowed = false;
     for pkg in pkgs {
-        if let Some(version) = pkg.planned_version.as_ref() {
-            if version.full_version < pkg.initial_version.full_version {
-                let crate_name = pkg.meta.name.as_str();
-                log::log!(
-                    level,
-                    "Cannot downgrade {} from {} to {}",
-                    crate_name,
-                    version.full_version,
-                    pkg.initial_version.full_version
-                );
-                downgrades_present = true;
+        let allowlist = match pkg.config.version_edit_allowlist() {
+            Some(allowlist) => allowlist,
+            None => continue,
+        };
+        let mut allowed = globset::GlobSetBuilder::new();
+        for pattern in allowlist {
+            match globset::GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+            {
+                Ok(glob) => {
+                    allowed.add(glob);
+                }
+                Err(err) => {
+                    log::log!(
+                        level,
+                        "Invalid `version-edit-allowlist` entry {:?}: {}",
+                        pattern,
+                        err
+                    );
+                    disallowed = true;
+                }
+            }
+        }
+        let allowed = allowed
+            .build()
+            .unwrap_or_else(|_| globset::GlobSet::empty());
+
+        let crate_name = pkg.meta.name.as_str();
+        for version in pkg.planned_version.iter().chain(pkg.post_version.iter()) {
+            for manifest in crate::steps::version::edited_manifests(pkg, version)? {
+                let is_allowed = manifest
+                    .strip_prefix(root)
+                    .map(|relative| allowed.is_match(relative))
+                    .unwrap_or(false);
+                if !is_allowed {
+                    log::log!(
+                        level,
+                        "Bumping {} to {} would edit {}, which `version-edit-allowlist` doesn't allow",
+                        crate_name,
+                        version.full_version_string,
+                        manifest.display()
+                    );
+                    disallowed = true;
+                }
             }
         }
     }
-    if downgrades_present && level == log::Level::Error {
+    if disallowed && level == log::Level::Error {
         success = false;
         if !dry_run {
             return Err(101.into());
@@ -204,10 +1399,13 @@ pub fn verify_rate_limit(
     // "It's not particularly secret, we just don't publish it other than in the code because
     // it's subject to change. The responses from the rate limited requests on when to try
     // again contain the most accurate information."
+    const NEW_BURST: usize = 5;
+    const EXISTING_BURST: usize = 30;
+
     let mut new = 0;
     let mut existing = 0;
     for pkg in pkgs {
-        if pkg.config.registry().is_none() {
+        if pkg.config.publish() && !pkg.config.package_only() && pkg.config.registry().is_none() {
             let crate_name = pkg.meta.name.as_str();
             if index.crate_(crate_name).is_some() {
                 existing += 1;
@@ -217,7 +1415,19 @@ pub fn verify_rate_limit(
         }
     }
 
-    if 5 < new {
+    if 0 < new + existing {
+        // The index doesn't record when versions were published, so what's left of the bursts
+        // after recent publishes is unknown
+        log::info!(
+            "Publishing {} new crates and {} existing crates; crates.io's bursts are at most {} and {}, less after recent publishes",
+            new,
+            existing,
+            NEW_BURST,
+            EXISTING_BURST
+        );
+    }
+
+    if NEW_BURST < new {
         // "The rate limit for creating new crates is 1 crate every 10 minutes, with a burst of 5 crates."
         success = false;
         log::log!(
@@ -227,7 +1437,7 @@ pub fn verify_rate_limit(
         );
     }
 
-    if 30 < existing {
+    if EXISTING_BURST < existing {
         // "The rate limit for new versions of existing crates is 1 per minute, with a burst of 30 crates, so when releasing new versions of these crates, you shouldn't hit the limit."
         success = false;
         log::log!(
@@ -253,15 +1463,15 @@ pub fn warn_changed(
         let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
         let crate_name = pkg.meta.name.as_str();
         if let Some(prior_tag_name) = &pkg.prior_tag {
-            if let Some((changed, lock_changed)) =
+            if let Some(changes) =
                 crate::steps::version::changed_since(ws_meta, pkg, prior_tag_name)
             {
-                if !changed.is_empty() {
+                if !changes.files.is_empty() {
                     log::debug!(
                         "Files changed in {} since {}: {:#?}",
                         crate_name,
                         prior_tag_name,
-                        changed
+                        changes.files
                     );
                     changed_pkgs.insert(&pkg.meta.id);
                     changed_pkgs.extend(pkg.dependents.iter().map(|d| &d.pkg.id));
@@ -273,7 +1483,7 @@ pub fn warn_changed(
                     );
                     changed_pkgs.insert(&pkg.meta.id);
                     changed_pkgs.extend(pkg.dependents.iter().map(|d| &d.pkg.id));
-                } else if lock_changed {
+                } else if changes.lock_changed {
                     log::debug!(
                         "Lock file changed for {} since {}, assuming its relevant",
                         crate_name,
@@ -340,38 +1550,56 @@ pub fn find_shared_versions(
     Ok(shared_version)
 }
 
+/// Set to `true` or `1` to skip confirmation, like `--no-confirm`
+pub const NO_CONFIRM_VAR: &str = "CARGO_RELEASE_NO_CONFIRM";
+
+/// Whether to ask before making changes
+///
+/// Dry-run never prompts since it changes nothing; otherwise we prompt unless `--no-confirm` or
+/// [`NO_CONFIRM_VAR`] say not to.
+fn should_prompt(no_confirm: bool, dry_run: bool, no_confirm_var: Option<&str>) -> bool {
+    let no_confirm_var = matches!(no_confirm_var.map(str::trim), Some("true") | Some("1"));
+    !dry_run && !no_confirm && !no_confirm_var
+}
+
 pub fn confirm(
     step: &str,
     pkgs: &[plan::PackageRelease],
     no_confirm: bool,
     dry_run: bool,
 ) -> Result<(), crate::error::ProcessError> {
-    if !dry_run && !no_confirm {
-        let prompt = if pkgs.len() == 1 {
-            let pkg = &pkgs[0];
-            let crate_name = pkg.meta.name.as_str();
-            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-            format!("{} {} {}?", step, crate_name, version.full_version_string)
-        } else {
-            use std::io::Write;
+    if should_prompt(
+        no_confirm,
+        dry_run,
+        std::env::var(NO_CONFIRM_VAR).ok().as_deref(),
+    ) {
+        let prompt = format!("{}?", preview(step, pkgs, false));
+        let confirmed = crate::ops::shell::confirm(&prompt);
+        if !confirmed {
+            return Err(0.into());
+        }
+    }
 
-            let mut buffer: Vec<u8> = vec![];
-            writeln!(&mut buffer, "{}", step).unwrap();
-            for pkg in pkgs {
-                let crate_name = pkg.meta.name.as_str();
-                let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-                writeln!(
-                    &mut buffer,
-                    "  {} {}",
-                    crate_name, version.full_version_string
-                )
-                .unwrap();
-            }
-            write!(&mut buffer, "?").unwrap();
-            String::from_utf8(buffer).expect("Only valid UTF-8 has been written")
-        };
+    Ok(())
+}
 
-        let confirmed = crate::ops::shell::confirm(&prompt);
+/// Like [`confirm`], also showing each package's development version
+///
+/// The preview is logged in dry-run so the dev-bump scheme can be checked.
+pub fn confirm_release(
+    pkgs: &[plan::PackageRelease],
+    no_confirm: bool,
+    dry_run: bool,
+) -> Result<(), crate::error::ProcessError> {
+    let preview = preview("Release", pkgs, true);
+    if dry_run {
+        log::info!("{}", preview);
+    } else if should_prompt(
+        no_confirm,
+        dry_run,
+        std::env::var(NO_CONFIRM_VAR).ok().as_deref(),
+    ) {
+        let confirmed = crate::ops::shell::confirm(&format!("{}?", preview));
         if !confirmed {
             return Err(0.into());
         }
@@ -380,6 +1608,33 @@ pub fn confirm(
     Ok(())
 }
 
+fn preview(step: &str, pkgs: &[plan::PackageRelease], post_version: bool) -> String {
+    let describe = |pkg: &plan::PackageRelease| {
+        let crate_name = pkg.meta.name.as_str();
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        if post_version {
+            let next = pkg
+                .post_version
+                .as_ref()
+                .map(|v| v.full_version_string.as_str())
+                .unwrap_or("none");
+            format!(
+                "{} {} (dev version: {})",
+                crate_name, version.full_version_string, next
+            )
+        } else {
+            format!("{} {}", crate_name, version.full_version_string)
+        }
+    };
+    if pkgs.len() == 1 {
+        format!("{} {}", step, describe(&pkgs[0]))
+    } else {
+        let mut lines = vec![step.to_owned()];
+        lines.extend(pkgs.iter().map(|pkg| format!("  {}", describe(pkg))));
+        lines.join("\n")
+    }
+}
+
 pub fn finish(failed: bool, dry_run: bool) -> Result<(), crate::error::ProcessError> {
     if dry_run {
         if failed {
@@ -393,3 +1648,588 @@ pub fn finish(failed: bool, dry_run: bool) -> Result<(), crate::error::ProcessEr
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod rust_version_min {
+        use super::*;
+
+        #[test]
+        fn partial() {
+            let req = semver::VersionReq::parse("1.60").unwrap();
+            assert_eq!(rust_version_min(&req), Some(semver::Version::new(1, 60, 0)));
+        }
+
+        #[test]
+        fn full() {
+            let req = semver::VersionReq::parse("1.56.1").unwrap();
+            assert_eq!(rust_version_min(&req), Some(semver::Version::new(1, 56, 1)));
+        }
+    }
+
+    mod verify_tags_unique {
+        use super::*;
+
+        fn pkgs(tag_name: &str) -> Vec<plan::PackageRelease> {
+            crate::test_support::pkgs("pure_ws")
+                .into_iter()
+                .map(|mut pkg| {
+                    pkg.planned_tag = Some(tag_name.to_owned());
+                    pkg
+                })
+                .collect()
+        }
+
+        #[test]
+        fn distinct() {
+            let mut pkgs = pkgs("v0.1.0");
+            for pkg in &mut pkgs {
+                pkg.planned_tag = Some(format!("{}-v0.1.0", pkg.meta.name));
+            }
+            assert!(verify_tags_unique(&pkgs, true, log::Level::Error).unwrap());
+        }
+
+        #[test]
+        fn shared_version() {
+            let pkgs = pkgs("v0.1.0");
+            assert!(verify_tags_unique(&pkgs, true, log::Level::Error).unwrap());
+        }
+
+        #[test]
+        fn collision() {
+            let mut pkgs = pkgs("v0.1.0");
+            pkgs[0].planned_version = Some(semver::Version::new(0, 2, 0).into());
+            assert!(!verify_tags_unique(&pkgs, true, log::Level::Error).unwrap());
+            assert!(verify_tags_unique(&pkgs, false, log::Level::Error).is_err());
+        }
+    }
+
+    mod verify_literal_tag_name {
+        use super::*;
+
+        fn pkgs() -> Vec<plan::PackageRelease> {
+            crate::test_support::pkgs("pure_ws")
+                .into_iter()
+                .map(|mut pkg| {
+                    pkg.planned_tag = Some(format!("{}-v0.1.0", pkg.meta.name));
+                    pkg
+                })
+                .collect()
+        }
+
+        #[test]
+        fn several_packages() {
+            let pkgs = pkgs();
+            let delimiters = ("<<", ">>");
+            let level = log::Level::Error;
+            assert!(verify_literal_tag_name(&pkgs, None, delimiters, false, level).unwrap());
+            let tag_name = Some("v1.0.0");
+            assert!(!verify_literal_tag_name(&pkgs, tag_name, delimiters, true, level).unwrap());
+            assert!(verify_literal_tag_name(&pkgs, tag_name, delimiters, false, level).is_err());
+        }
+
+        #[test]
+        fn single_package() {
+            let pkgs = &pkgs()[..1];
+            let tag_name = Some("v1.0.0");
+            let level = log::Level::Error;
+            assert!(verify_literal_tag_name(pkgs, tag_name, ("{{", "}}"), false, level).unwrap());
+        }
+    }
+
+    mod verify_tags_missing {
+        use super::*;
+
+        /// `simple` planned as `v0.1.0`, committed in a repo where that tag is at `HEAD`
+        fn pkgs(temp: &assert_fs::TempDir) -> Vec<plan::PackageRelease> {
+            use assert_fs::prelude::*;
+
+            temp.copy_from("tests/fixtures/simple", &["**"]).unwrap();
+            crate::test_support::init_repo(temp.path());
+            crate::test_support::git(temp.path(), &["add", "."]);
+            crate::test_support::git(temp.path(), &["commit", "-m", "Release"]);
+            crate::test_support::git(temp.path(), &["tag", "v0.1.0"]);
+
+            let meta = cargo_metadata::MetadataCommand::new()
+                .manifest_path(temp.path().join("Cargo.toml"))
+                .exec()
+                .unwrap();
+            plan::load(&Default::default(), &meta)
+                .unwrap()
+                .into_iter()
+                .map(|(_, mut pkg)| {
+                    pkg.config.allow_existing_tag = Some(true);
+                    pkg.planned_tag = Some("v0.1.0".to_owned());
+                    pkg
+                })
+                .collect()
+        }
+
+        #[test]
+        fn reused_at_head() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let pkgs = pkgs(&temp);
+            assert!(verify_tags_missing(&pkgs, false, true, log::Level::Error).unwrap());
+        }
+
+        #[test]
+        fn commit_pending() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let pkgs = pkgs(&temp);
+            assert!(!verify_tags_missing(&pkgs, true, true, log::Level::Error).unwrap());
+            assert!(verify_tags_missing(&pkgs, true, false, log::Level::Error).is_err());
+        }
+
+        #[test]
+        fn not_allowed() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut pkgs = pkgs(&temp);
+            pkgs[0].config.allow_existing_tag = Some(false);
+            assert!(!verify_tags_missing(&pkgs, false, true, log::Level::Error).unwrap());
+        }
+    }
+
+    mod verify_amend_unpushed {
+        use super::*;
+
+        use assert_fs::prelude::*;
+
+        fn init(temp: &assert_fs::TempDir) -> std::path::PathBuf {
+            let local = temp.child("local");
+            let remote = temp.child("remote.git");
+            local.create_dir_all().unwrap();
+            remote.create_dir_all().unwrap();
+            crate::test_support::init_pushed_repo(local.path(), remote.path());
+            local.path().to_owned()
+        }
+
+        fn verify(
+            local: &std::path::Path,
+            dry_run: bool,
+        ) -> Result<bool, crate::error::ProcessError> {
+            let ws_config = crate::config::Config::default();
+            verify_amend_unpushed(local, &ws_config, dry_run, log::Level::Error)
+        }
+
+        #[test]
+        fn pushed() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            assert!(!verify(&local, true).unwrap());
+            assert!(verify(&local, false).is_err());
+        }
+
+        #[test]
+        fn unpushed() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            std::fs::write(local.join("file.txt"), "two\n").unwrap();
+            assert!(crate::ops::git::commit_all(&local, "Work", false, false).unwrap());
+            assert!(verify(&local, false).unwrap());
+        }
+
+        #[test]
+        fn missing_upstream() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            crate::test_support::git(&local, &["checkout", "-b", "feature"]);
+            assert!(verify(&local, false).unwrap());
+        }
+
+        /// With `consolidate-commits = false`, each package's release amends `HEAD` in turn
+        #[test]
+        fn amended_per_package() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let local = init(&temp);
+            let pushed = crate::ops::git::head_id(&local).unwrap();
+            std::fs::write(local.join("file.txt"), "two\n").unwrap();
+            assert!(crate::ops::git::commit_all(&local, "Work", false, false).unwrap());
+            assert!(verify(&local, false).unwrap());
+
+            for version in ["a 0.2.0\n", "b 0.2.0\n"] {
+                std::fs::write(local.join("file.txt"), version).unwrap();
+                assert!(crate::ops::git::amend_all(&local, false, false).unwrap());
+                // Each amend leaves the pushed commit alone
+                assert!(verify(&local, false).unwrap());
+                assert_eq!(
+                    crate::ops::git::rev_id(&local, "HEAD~").unwrap(),
+                    Some(pushed.clone())
+                );
+            }
+        }
+    }
+
+    mod verify_publishable_dependencies {
+        use super::*;
+
+        /// `mixed_ws`, where `b` depends on `a` by path, with `edit` applied to `a` and `b`
+        fn ws(
+            edit: impl Fn(&mut cargo_metadata::Package),
+        ) -> (cargo_metadata::Metadata, Vec<plan::PackageRelease>) {
+            let mut ws_meta = crate::test_support::metadata("mixed_ws");
+            for pkg in &mut ws_meta.packages {
+                edit(pkg);
+            }
+            let pkgs = plan::load(&Default::default(), &ws_meta)
+                .unwrap()
+                .into_iter()
+                .map(|(_, pkg)| pkg)
+                .collect();
+            (ws_meta, pkgs)
+        }
+
+        #[test]
+        fn path_only() {
+            let (ws_meta, pkgs) = ws(|_| {});
+            let level = log::Level::Error;
+            assert!(verify_publishable_dependencies(&ws_meta, &pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn unpublishable() {
+            let (ws_meta, pkgs) = ws(|pkg| {
+                if pkg.name == "a" {
+                    pkg.publish = Some(vec![]);
+                }
+            });
+            let level = log::Level::Error;
+            assert!(!verify_publishable_dependencies(&ws_meta, &pkgs, true, level).unwrap());
+            assert!(verify_publishable_dependencies(&ws_meta, &pkgs, false, level).is_err());
+            let level = log::Level::Warn;
+            assert!(verify_publishable_dependencies(&ws_meta, &pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn unpublishable_dev_dependency() {
+            let (ws_meta, pkgs) = ws(|pkg| {
+                if pkg.name == "a" {
+                    pkg.publish = Some(vec![]);
+                }
+                if pkg.name == "b" {
+                    for dep in &mut pkg.dependencies {
+                        dep.kind = cargo_metadata::DependencyKind::Development;
+                    }
+                }
+            });
+            let level = log::Level::Error;
+            assert!(verify_publishable_dependencies(&ws_meta, &pkgs, false, level).unwrap());
+        }
+    }
+
+    mod verify_edition {
+        use super::*;
+
+        /// `mixed_ws`, where `b` depends on `a` by path, with the `rust-version`s of `a` and `b`
+        fn ws(
+            edition: cargo_metadata::Edition,
+            a: &str,
+            b: &str,
+        ) -> (cargo_metadata::Metadata, Vec<plan::PackageRelease>) {
+            let mut ws_meta = crate::test_support::metadata("mixed_ws");
+            for pkg in &mut ws_meta.packages {
+                let rust_version = match pkg.name.as_str() {
+                    "a" => a,
+                    "b" => b,
+                    _ => continue,
+                };
+                pkg.edition = edition.clone();
+                pkg.rust_version = Some(semver::VersionReq::parse(rust_version).unwrap());
+            }
+            let pkgs = plan::load(&Default::default(), &ws_meta)
+                .unwrap()
+                .into_iter()
+                .map(|(_, pkg)| pkg)
+                .collect();
+            (ws_meta, pkgs)
+        }
+
+        #[test]
+        fn compatible() {
+            let (ws_meta, pkgs) = ws(cargo_metadata::Edition::E2021, "1.60", "1.60");
+            let level = log::Level::Error;
+            assert!(verify_edition(&ws_meta, &pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn edition_too_new() {
+            let (ws_meta, pkgs) = ws(cargo_metadata::Edition::E2021, "1.50", "1.60");
+            let level = log::Level::Error;
+            assert!(!verify_edition(&ws_meta, &pkgs, true, level).unwrap());
+            assert!(verify_edition(&ws_meta, &pkgs, false, level).is_err());
+            let level = log::Level::Warn;
+            assert!(verify_edition(&ws_meta, &pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn dependency_too_new() {
+            let (ws_meta, pkgs) = ws(cargo_metadata::Edition::E2018, "1.65", "1.60");
+            let level = log::Level::Error;
+            assert!(!verify_edition(&ws_meta, &pkgs, true, level).unwrap());
+            assert!(verify_edition(&ws_meta, &pkgs, false, level).is_err());
+            let level = log::Level::Warn;
+            assert!(verify_edition(&ws_meta, &pkgs, false, level).unwrap());
+        }
+    }
+
+    mod verify_no_path_only_deps_in_publish {
+        use super::*;
+
+        /// `mixed_ws`'s `b`, with its dependency on `a` by path edited by `edit`
+        fn pkgs(edit: impl Fn(&mut cargo_metadata::Dependency)) -> Vec<plan::PackageRelease> {
+            let mut pkg = crate::test_support::pkg("mixed_ws", "b");
+            for dep in &mut pkg.meta.dependencies {
+                edit(dep);
+            }
+            vec![pkg]
+        }
+
+        #[test]
+        fn path_only() {
+            let pkgs = pkgs(|_| {});
+            let level = log::Level::Error;
+            assert!(!verify_no_path_only_deps_in_publish(&pkgs, true, level).unwrap());
+            assert!(verify_no_path_only_deps_in_publish(&pkgs, false, level).is_err());
+            let level = log::Level::Warn;
+            assert!(verify_no_path_only_deps_in_publish(&pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn versioned() {
+            let pkgs = pkgs(|dep| dep.req = semver::VersionReq::parse("0.1.0").unwrap());
+            let level = log::Level::Error;
+            assert!(verify_no_path_only_deps_in_publish(&pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn dev_dependency() {
+            let pkgs = pkgs(|dep| dep.kind = cargo_metadata::DependencyKind::Development);
+            let level = log::Level::Error;
+            assert!(verify_no_path_only_deps_in_publish(&pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn not_published() {
+            let mut pkgs = pkgs(|_| {});
+            pkgs[0].config.publish = Some(false);
+            let level = log::Level::Error;
+            assert!(verify_no_path_only_deps_in_publish(&pkgs, false, level).unwrap());
+        }
+    }
+
+    mod verify_features_exclusive {
+        use super::*;
+
+        fn pkgs(all: bool, features: &[&str]) -> Vec<plan::PackageRelease> {
+            let mut pkg = crate::test_support::pkg("pure_ws", "a");
+            pkg.config.enable_all_features = Some(all);
+            pkg.config.enable_features = Some(features.iter().map(|f| (*f).to_owned()).collect());
+            vec![pkg]
+        }
+
+        #[test]
+        fn exclusive() {
+            let level = log::Level::Error;
+            assert!(verify_features_exclusive(&pkgs(true, &[]), false, level).unwrap());
+            assert!(verify_features_exclusive(&pkgs(false, &["serde"]), false, level).unwrap());
+        }
+
+        #[test]
+        fn conflicting() {
+            let pkgs = pkgs(true, &["serde"]);
+            let level = log::Level::Error;
+            assert!(!verify_features_exclusive(&pkgs, true, level).unwrap());
+            assert!(verify_features_exclusive(&pkgs, false, level).is_err());
+        }
+
+        #[test]
+        fn not_published() {
+            let mut pkgs = pkgs(true, &["serde"]);
+            pkgs[0].config.publish = Some(false);
+            assert!(verify_features_exclusive(&pkgs, false, log::Level::Error).unwrap());
+        }
+    }
+
+    mod verify_registries_allowed {
+        use super::*;
+
+        fn pkgs(registry: Option<&str>, allowed: &[&str]) -> Vec<plan::PackageRelease> {
+            let mut pkg = crate::test_support::pkg("pure_ws", "a");
+            pkg.config.registry = registry.map(ToOwned::to_owned);
+            pkg.config.allowed_registries = Some(allowed.iter().map(|r| (*r).to_owned()).collect());
+            vec![pkg]
+        }
+
+        #[test]
+        fn allowed() {
+            let level = log::Level::Error;
+            assert!(verify_registries_allowed(&pkgs(None, &["crates-io"]), false, level).unwrap());
+            let pkgs = pkgs(Some("internal"), &["internal"]);
+            assert!(verify_registries_allowed(&pkgs, false, level).unwrap());
+        }
+
+        #[test]
+        fn disallowed() {
+            let pkgs = pkgs(None, &["internal"]);
+            let level = log::Level::Error;
+            assert!(!verify_registries_allowed(&pkgs, true, level).unwrap());
+            assert!(verify_registries_allowed(&pkgs, false, level).is_err());
+        }
+
+        #[test]
+        fn package_only() {
+            let mut pkgs = pkgs(None, &["internal"]);
+            pkgs[0].config.package_only = Some(true);
+            assert!(verify_registries_allowed(&pkgs, false, log::Level::Error).unwrap());
+        }
+    }
+
+    mod verify_registry_newer {
+        use super::*;
+
+        /// `pure_ws`'s `a` at `0.1.0`, with `0.2.0` already in the index
+        fn check(
+            config: impl FnOnce(&mut crate::config::Config),
+            dry_run: bool,
+        ) -> Result<bool, crate::error::ProcessError> {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let index = crate::test_support::index(temp.path(), &[("a", "0.2.0")]);
+            let mut pkg = crate::test_support::pkg("pure_ws", "a");
+            config(&mut pkg.config);
+            let ws_config = crate::config::Config::default();
+            verify_registry_newer(&[pkg], &index, dry_run, registry_newer_level(&ws_config))
+        }
+
+        #[test]
+        fn lower() {
+            assert!(!check(|_| {}, true).unwrap());
+            assert!(check(|_| {}, false).is_err());
+        }
+
+        #[test]
+        fn backport() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let index = crate::test_support::index(temp.path(), &[("a", "0.2.0")]);
+            let pkgs = [crate::test_support::pkg("pure_ws", "a")];
+            let ws_config = crate::config::Config {
+                registry_newer_check: Some(false),
+                ..Default::default()
+            };
+            let level = registry_newer_level(&ws_config);
+            assert_eq!(level, log::Level::Warn);
+            assert!(verify_registry_newer(&pkgs, &index, false, level).unwrap());
+        }
+
+        #[test]
+        fn not_published() {
+            assert!(check(|c| c.publish = Some(false), false).unwrap());
+            assert!(check(|c| c.package_only = Some(true), false).unwrap());
+            assert!(check(|c| c.registry = Some("other".to_owned()), false).unwrap());
+        }
+    }
+
+    mod github_release_pkgs {
+        use super::*;
+
+        #[test]
+        fn changelog_excluded() {
+            let mut pkgs: Vec<_> = crate::test_support::pkgs("pure_ws")
+                .into_iter()
+                .map(|mut pkg| {
+                    pkg.planned_tag = Some("v0.1.0".to_owned());
+                    pkg.config.github_release = Some(true);
+                    pkg
+                })
+                .collect();
+            pkgs[0].config.changelog = Some(false);
+
+            let ws_config = crate::config::Config::default();
+            let released: Vec<_> = github_release_pkgs(&ws_config, &pkgs)
+                .map(|p| p.meta.name.as_str())
+                .collect();
+            assert_eq!(released, [pkgs[1].meta.name.as_str()]);
+        }
+    }
+
+    mod should_prompt {
+        use super::*;
+
+        #[test]
+        fn dry_run() {
+            assert!(!should_prompt(false, true, None));
+            assert!(!should_prompt(true, true, None));
+        }
+
+        #[test]
+        fn execute() {
+            assert!(should_prompt(false, false, None));
+            assert!(!should_prompt(true, false, None));
+        }
+
+        #[test]
+        fn env_override() {
+            assert!(!should_prompt(false, false, Some("true")));
+            assert!(!should_prompt(false, false, Some("1")));
+            assert!(should_prompt(false, false, Some("false")));
+            assert!(should_prompt(false, false, Some("")));
+        }
+    }
+
+    mod is_placeholder_version {
+        use super::*;
+
+        #[test]
+        fn placeholder() {
+            assert!(is_placeholder_version(&semver::Version::new(0, 0, 0)));
+            assert!(is_placeholder_version(
+                &semver::Version::parse("0.0.0-dev").unwrap()
+            ));
+        }
+
+        #[test]
+        fn real() {
+            assert!(!is_placeholder_version(&semver::Version::new(0, 0, 1)));
+            assert!(!is_placeholder_version(&semver::Version::new(0, 1, 0)));
+            assert!(!is_placeholder_version(&semver::Version::new(1, 0, 0)));
+        }
+    }
+    mod preview {
+        use super::*;
+
+        fn pkgs() -> Vec<plan::PackageRelease> {
+            crate::test_support::pkgs("pure_ws")
+        }
+
+        #[test]
+        fn single() {
+            let mut pkgs = pkgs();
+            pkgs.truncate(1);
+            pkgs[0].post_version = Some(semver::Version::parse("0.1.1-alpha.0").unwrap().into());
+            let name = pkgs[0].meta.name.clone();
+            assert_eq!(
+                preview("Release", &pkgs, true),
+                format!("Release {} 0.1.0 (dev version: 0.1.1-alpha.0)", name)
+            );
+            assert_eq!(
+                preview("Release", &pkgs, false),
+                format!("Release {} 0.1.0", name)
+            );
+        }
+
+        #[test]
+        fn no_dev_version() {
+            let pkgs = pkgs();
+            let preview = preview("Release", &pkgs, true);
+            let lines: Vec<_> = preview.lines().collect();
+            assert_eq!(lines.len(), pkgs.len() + 1);
+            assert_eq!(lines[0], "Release");
+            for (line, pkg) in lines[1..].iter().zip(&pkgs) {
+                assert_eq!(
+                    *line,
+                    format!("  {} 0.1.0 (dev version: none)", pkg.meta.name)
+                );
+            }
+        }
+    }
+}
@@ -14,12 +14,39 @@ pub fn load(
     args: &config::ConfigArgs,
     ws_meta: &cargo_metadata::Metadata,
 ) -> Result<indexmap::IndexMap<cargo_metadata::PackageId, PackageRelease>, error::FatalError> {
-    let root = git::top_level(ws_meta.workspace_root.as_std_path())?;
+    load_allowing_disabled(args, ws_meta, &[], true)
+}
+
+/// Like [`load`] but `allow_disabled` packages are kept even with `release = false`
+///
+/// Without `vcs`, git isn't consulted: the workspace root stands in for the repository root and
+/// no prior tags are looked up.
+pub fn load_allowing_disabled(
+    args: &config::ConfigArgs,
+    ws_meta: &cargo_metadata::Metadata,
+    allow_disabled: &[cargo_metadata::PackageId],
+    vcs: bool,
+) -> Result<indexmap::IndexMap<cargo_metadata::PackageId, PackageRelease>, error::FatalError> {
+    let root = if vcs {
+        git::top_level(ws_meta.workspace_root.as_std_path())?
+    } else {
+        ws_meta.workspace_root.as_std_path().to_owned()
+    };
 
     let member_ids = cargo::sort_workspace(ws_meta);
     member_ids
         .iter()
-        .filter_map(|p| PackageRelease::load(args, &root, ws_meta, &ws_meta[p]).transpose())
+        .filter_map(|p| {
+            let allow_disabled = allow_disabled.contains(p);
+            PackageRelease::load(args, &root, ws_meta, &ws_meta[p], allow_disabled)
+                .map(|p| {
+                    p.map(|mut p| {
+                        p.vcs = vcs;
+                        p
+                    })
+                })
+                .transpose()
+        })
         .map(|p| p.map(|p| (p.meta.id.clone(), p)))
         .collect()
 }
@@ -63,6 +90,8 @@ pub struct PackageRelease {
     pub manifest_path: PathBuf,
     pub package_root: PathBuf,
     pub is_root: bool,
+    /// Whether to look up tags in the package's git repository
+    pub vcs: bool,
     pub config: config::Config,
 
     pub package_content: Vec<PathBuf>,
@@ -85,16 +114,25 @@ impl PackageRelease {
         git_root: &Path,
         ws_meta: &cargo_metadata::Metadata,
         pkg_meta: &cargo_metadata::Package,
+        allow_disabled: bool,
     ) -> Result<Option<Self>, error::FatalError> {
         let manifest_path = pkg_meta.manifest_path.as_std_path();
         let package_root = manifest_path.parent().unwrap_or_else(|| Path::new("."));
-        let config = config::load_package_config(args, ws_meta, pkg_meta)?;
+        let mut config = config::load_package_config(args, ws_meta, pkg_meta)?;
         if !config.release() {
-            log::trace!("Disabled in config, skipping {}", manifest_path.display());
-            return Ok(None);
+            if allow_disabled {
+                log::warn!(
+                    "Releasing {} despite `release = false` in config, due to `--allow-disabled`",
+                    pkg_meta.name
+                );
+                config.release = Some(true);
+            } else {
+                log::trace!("Disabled in config, skipping {}", manifest_path.display());
+                return Ok(None);
+            }
         }
 
-        let package_content = cargo::package_content(manifest_path)?;
+        let package_content = cargo::package_content(manifest_path, config.cargo_bin())?;
         let bin = pkg_meta
             .targets
             .iter()
@@ -114,11 +152,12 @@ impl PackageRelease {
         let tag_prefix = config.tag_prefix(is_root);
         let name = pkg_meta.name.as_str();
         let initial_tag = render_tag(
-            tag_name,
-            tag_prefix,
+            &tag_name,
+            &tag_prefix,
             name,
             &initial_version,
             &initial_version,
+            config.template_delimiters(),
         );
 
         let prior_tag = None;
@@ -132,6 +171,7 @@ impl PackageRelease {
             manifest_path: manifest_path.to_owned(),
             package_root: package_root.to_owned(),
             is_root,
+            vcs: true,
             config,
 
             package_content,
@@ -154,13 +194,62 @@ impl PackageRelease {
         self.prior_tag = Some(prior_tag);
     }
 
+    /// `pre-release-replacements` along with those `auto-update` generates for existing files
+    pub fn pre_release_replacements(&self) -> std::borrow::Cow<'_, [config::Replace]> {
+        let configured = self.config.pre_release_replacements();
+        if self.config.auto_update().is_empty() {
+            return std::borrow::Cow::Borrowed(configured);
+        }
+
+        let mut replacements = configured.to_vec();
+        for auto_update in self.config.auto_update() {
+            let file = match auto_update {
+                config::AutoUpdate::Readme => PathBuf::from("README.md"),
+                config::AutoUpdate::HtmlRootUrl => self
+                    .meta
+                    .targets
+                    .iter()
+                    .find(|t| t.kind.iter().any(|k| k == "lib"))
+                    .and_then(|t| {
+                        t.src_path
+                            .as_std_path()
+                            .strip_prefix(&self.package_root)
+                            .ok()
+                    })
+                    .map(|p| p.to_owned())
+                    .unwrap_or_else(|| PathBuf::from("src/lib.rs")),
+            };
+            if !self.package_root.join(&file).exists() {
+                log::debug!(
+                    "Not auto-updating {} for {}, it doesn't exist",
+                    file.display(),
+                    self.meta.name
+                );
+                continue;
+            }
+            replacements.push(self.config.auto_update_replacement(
+                *auto_update,
+                &self.meta.name,
+                file,
+            ));
+        }
+        std::borrow::Cow::Owned(replacements)
+    }
+
     pub fn bump(
         &mut self,
         level_or_version: &version::TargetVersion,
         metadata: Option<&str>,
     ) -> Result<(), FatalError> {
+        let current = &self.initial_version.full_version;
+        let level_or_version = match level_or_version {
+            version::TargetVersion::Relative(level) => version::TargetVersion::Relative(
+                level.pre_1_0(current, self.config.pre_1_0_breaking()),
+            ),
+            _ => level_or_version.clone(),
+        };
         self.planned_version =
-            level_or_version.bump(&self.initial_version.full_version, metadata)?;
+            level_or_version.bump(current, metadata, self.config.prerelease_identifier())?;
         Ok(())
     }
 
@@ -169,17 +258,23 @@ impl PackageRelease {
             return Ok(());
         }
 
-        if self.planned_version.is_some()
+        if self.vcs
+            && self.planned_version.is_some()
             && crate::ops::git::tag_exists(&self.package_root, &self.initial_tag)?
         {
             self.prior_tag
                 .get_or_insert_with(|| self.initial_tag.clone());
         }
-        if self.prior_tag.is_none() {
+        if self.vcs && self.prior_tag.is_none() {
             let tag_name = self.config.tag_name();
             let tag_prefix = self.config.tag_prefix(self.is_root);
             let name = self.meta.name.as_str();
-            let tag_glob = render_tag_glob(tag_name, tag_prefix, name);
+            let tag_glob = render_tag_glob(
+                &tag_name,
+                &tag_prefix,
+                name,
+                self.config.template_delimiters(),
+            );
             match globset::Glob::new(&tag_glob) {
                 Ok(tag_glob) => {
                     let tag_glob = tag_glob.compile_matcher();
@@ -200,11 +295,12 @@ impl PackageRelease {
             let tag_prefix = self.config.tag_prefix(self.is_root);
             let name = self.meta.name.as_str();
             Some(render_tag(
-                tag_name,
-                tag_prefix,
+                &tag_name,
+                &tag_prefix,
                 name,
                 &self.initial_version,
                 base,
+                self.config.template_delimiters(),
             ))
         } else {
             None
@@ -213,7 +309,11 @@ impl PackageRelease {
         let is_pre_release = base.is_prerelease();
         let post_version = if !is_pre_release && self.config.dev_version() {
             let mut post = base.full_version.clone();
-            post.increment_patch();
+            match self.config.dev_version_level() {
+                config::DevVersionLevel::Major => post.increment_major(),
+                config::DevVersionLevel::Minor => post.increment_minor(),
+                config::DevVersionLevel::Patch => post.increment_patch(),
+            }
             post.pre = semver::Prerelease::new(self.config.dev_version_ext())?;
 
             Some(version::Version::from(post))
@@ -234,6 +334,7 @@ fn render_tag(
     name: &str,
     prev: &version::Version,
     base: &version::Version,
+    delimiters: (&str, &str),
 ) -> String {
     let initial_version_var = prev.bare_version_string.as_str();
     let existing_metadata_var = prev.full_version.build.as_str();
@@ -245,6 +346,7 @@ fn render_tag(
         version: Some(version_var),
         metadata: Some(metadata_var),
         crate_name: Some(name),
+        delimiters: Some(delimiters),
         ..Default::default()
     };
 
@@ -253,7 +355,12 @@ fn render_tag(
     template.render(tag_name)
 }
 
-fn render_tag_glob(tag_name: &str, tag_prefix: &str, name: &str) -> String {
+fn render_tag_glob(
+    tag_name: &str,
+    tag_prefix: &str,
+    name: &str,
+    delimiters: (&str, &str),
+) -> String {
     let initial_version_var = "*";
     let existing_metadata_var = "*";
     let version_var = "*";
@@ -264,6 +371,7 @@ fn render_tag_glob(tag_name: &str, tag_prefix: &str, name: &str) -> String {
         version: Some(version_var),
         metadata: Some(metadata_var),
         crate_name: Some(name),
+        delimiters: Some(delimiters),
         ..Default::default()
     };
 
@@ -292,3 +400,163 @@ pub struct Dependency {
     pub pkg: cargo_metadata::Package,
     pub req: semver::VersionReq,
 }
+
+/// The release plan for `pkgs`, for tools like hooks
+pub fn to_json(pkgs: &[PackageRelease]) -> serde_json::Value {
+    let crates: Vec<_> = pkgs
+        .iter()
+        .map(|pkg| {
+            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+            serde_json::json!({
+                "name": pkg.meta.name,
+                "manifest_path": pkg.manifest_path,
+                "prev_version": pkg.initial_version.full_version_string,
+                "version": version.full_version_string,
+                "next_version": pkg.post_version.as_ref().map(|v| &v.full_version_string),
+                "tag": pkg.planned_tag,
+                "publish": pkg.config.publish(),
+            })
+        })
+        .collect();
+    serde_json::json!({ "crates": crates })
+}
+
+/// What was released, for `release-manifest`
+///
+/// `commit` is the release commit, unknown in dry-run.
+pub fn to_manifest(
+    pkgs: &[PackageRelease],
+    commit: Option<&str>,
+    timestamp: &str,
+) -> serde_json::Value {
+    let crates: Vec<_> = pkgs
+        .iter()
+        .map(|pkg| {
+            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+            let published = pkg.config.publish() && !pkg.config.package_only();
+            let registry = published.then(|| pkg.config.registry().unwrap_or("crates-io"));
+            serde_json::json!({
+                "name": pkg.meta.name,
+                "version": version.full_version_string,
+                "tag": pkg.planned_tag,
+                "commit": commit,
+                "published": published,
+                "registry": registry,
+            })
+        })
+        .collect();
+    serde_json::json!({ "timestamp": timestamp, "crates": crates })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod plan {
+        use super::*;
+
+        fn pkg(tag: Option<bool>) -> PackageRelease {
+            let mut pkg = crate::test_support::pkg("simple", "simple");
+            pkg.config.tag = tag;
+            pkg.plan().unwrap();
+            pkg
+        }
+
+        #[test]
+        fn tagged() {
+            assert_eq!(pkg(None).planned_tag.as_deref(), Some("simple-v0.1.0"));
+        }
+
+        #[test]
+        fn untagged() {
+            assert_eq!(pkg(Some(false)).planned_tag, None);
+        }
+
+        #[test]
+        fn dev_version_level() {
+            let mut pkg = pkg(None);
+            pkg.config.dev_version = Some(true);
+            pkg.config.dev_version_level = Some(config::DevVersionLevel::Minor);
+            pkg.config.dev_version_ext = Some("beta.0".to_owned());
+            pkg.planned_version = Some(semver::Version::new(1, 2, 0).into());
+            pkg.plan().unwrap();
+
+            let post = pkg.post_version.unwrap();
+            assert_eq!(post.full_version_string, "1.3.0-beta.0");
+
+            let temp = assert_fs::TempDir::new().unwrap();
+            let manifest_path = temp.path().join("Cargo.toml");
+            std::fs::copy(&pkg.manifest_path, &manifest_path).unwrap();
+            cargo::set_package_version(&manifest_path, &post.full_version_string, false, false)
+                .unwrap();
+            let manifest: toml_edit::Document = std::fs::read_to_string(&manifest_path)
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(
+                manifest["package"]["version"].as_str(),
+                Some("1.3.0-beta.0")
+            );
+        }
+    }
+
+    mod load_allowing_disabled {
+        use super::*;
+
+        #[test]
+        fn without_vcs() {
+            let meta = crate::test_support::metadata("simple");
+            let (_, mut pkg) = load_allowing_disabled(&Default::default(), &meta, &[], false)
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap();
+            assert!(!pkg.vcs);
+            assert!(pkg.is_root);
+            pkg.planned_version = Some(semver::Version::new(0, 2, 0).into());
+            pkg.plan().unwrap();
+            assert_eq!(pkg.prior_tag, None);
+        }
+    }
+
+    mod to_manifest {
+        use super::*;
+
+        #[test]
+        fn released() {
+            let mut pkg = crate::test_support::pkg("simple", "simple");
+            pkg.planned_version = Some(semver::Version::new(0, 2, 0).into());
+            pkg.plan().unwrap();
+
+            let manifest = to_manifest(&[pkg], Some("abc123"), "2022-10-01T00:00:00Z");
+            assert_eq!(manifest["timestamp"], "2022-10-01T00:00:00Z");
+            let krate = &manifest["crates"][0];
+            assert_eq!(krate["name"], "simple");
+            assert_eq!(krate["version"], "0.2.0");
+            assert_eq!(krate["tag"], "simple-v0.2.0");
+            assert_eq!(krate["commit"], "abc123");
+            assert_eq!(krate["published"], true);
+            assert_eq!(krate["registry"], "crates-io");
+        }
+    }
+
+    mod to_json {
+        use super::*;
+
+        #[test]
+        fn planned() {
+            let mut pkg = crate::test_support::pkg("simple", "simple");
+            pkg.planned_version = Some(semver::Version::new(0, 2, 0).into());
+            pkg.plan().unwrap();
+
+            let json = to_json(&[pkg]);
+            let krate = &json["crates"][0];
+            assert_eq!(krate["name"], "simple");
+            assert_eq!(krate["prev_version"], "0.1.0");
+            assert_eq!(krate["version"], "0.2.0");
+            assert_eq!(krate["next_version"], serde_json::Value::Null);
+            assert_eq!(krate["tag"], "simple-v0.2.0");
+            assert_eq!(krate["publish"], true);
+        }
+    }
+}
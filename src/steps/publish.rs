@@ -26,10 +26,18 @@ pub struct PublishStep {
     #[arg(long, value_delimiter = ',')]
     allow_branch: Option<Vec<String>>,
 
+    /// Don't check whether the branch is behind its remote
+    #[arg(long)]
+    allow_behind: bool,
+
     /// Actually perform a release. Dry-run mode is the default
     #[arg(short = 'x', long)]
     execute: bool,
 
+    /// Only show what would be done, the default.  Conflicts with `--execute`
+    #[arg(long, conflicts_with = "execute")]
+    dry_run: bool,
+
     /// Skip release confirmation and version preview
     #[arg(long)]
     no_confirm: bool,
@@ -42,14 +50,8 @@ impl PublishStep {
     pub fn run(&self) -> Result<(), ProcessError> {
         git::git_version()?;
 
-        let ws_meta = self
-            .manifest
-            .metadata()
-            // When evaluating dependency ordering, we need to consider optional dependencies
-            .features(cargo_metadata::CargoOpt::AllFeatures)
-            .exec()
-            .map_err(FatalError::from)?;
         let config = self.to_config();
+        let ws_meta = super::load_metadata(&self.manifest, &config)?;
         let ws_config = crate::config::load_workspace_config(&config, &ws_meta)?;
         let mut pkgs = plan::load(&config, &ws_meta)?;
 
@@ -68,9 +70,15 @@ impl PublishStep {
             log::debug!("Disabled by user, skipping {}", crate_name,);
         }
 
+        let releasing: Vec<_> = pkgs
+            .values()
+            .filter(|p| p.config.release())
+            .map(|p| &p.meta.id)
+            .collect();
+        super::verify_no_dependency_cycles(&ws_meta, &releasing)?;
         let mut pkgs = plan::plan(pkgs)?;
 
-        let mut index = crates_index::Index::new_cargo_default()?;
+        let mut index = crate::ops::cargo::open_index(ws_config.index_freshness())?;
         for pkg in pkgs.values_mut() {
             if pkg.config.registry().is_none() && pkg.config.release() {
                 let crate_name = pkg.meta.name.as_str();
@@ -98,12 +106,13 @@ impl PublishStep {
             return Err(2.into());
         }
 
-        let dry_run = !self.execute;
+        let dry_run = self.dry_run || !self.execute;
         let mut failed = false;
 
         // STEP 0: Help the user make the right decisions.
         failed |= !super::verify_git_is_clean(
             ws_meta.workspace_root.as_std_path(),
+            &ws_config,
             dry_run,
             log::Level::Error,
         )?;
@@ -115,20 +124,49 @@ impl PublishStep {
             log::Level::Error,
         )?;
 
-        failed |= !super::verify_if_behind(
+        if !self.allow_behind {
+            failed |= !super::verify_if_behind(
+                ws_meta.workspace_root.as_std_path(),
+                &ws_config,
+                dry_run,
+                super::if_behind_level(&ws_config),
+            )?;
+        }
+
+        failed |= !super::verify_repository(
             ws_meta.workspace_root.as_std_path(),
             &ws_config,
+            &pkgs,
+            dry_run,
+            super::repository_level(&ws_config),
+        )?;
+
+        failed |= !super::verify_registry_newer(
+            &pkgs,
+            &index,
             dry_run,
-            log::Level::Warn,
+            super::registry_newer_level(&ws_config),
         )?;
 
+        failed |=
+            !super::verify_publishable_dependencies(&ws_meta, &pkgs, dry_run, log::Level::Error)?;
+
+        failed |= !super::verify_no_path_only_deps_in_publish(&pkgs, dry_run, log::Level::Error)?;
+
+        failed |= !super::verify_features_exclusive(&pkgs, dry_run, log::Level::Error)?;
+
+        failed |= !super::verify_registries_allowed(&pkgs, dry_run, log::Level::Error)?;
+
+        failed |=
+            !super::verify_edition(&ws_meta, &pkgs, dry_run, super::edition_level(&ws_config))?;
+
         failed |= !super::verify_rate_limit(&pkgs, &index, dry_run, log::Level::Error)?;
 
         // STEP 1: Release Confirmation
         super::confirm("Publish", &pkgs, self.no_confirm, dry_run)?;
 
         // STEP 3: cargo publish
-        publish(&ws_meta, &pkgs, &mut index, dry_run)?;
+        publish(&ws_meta, &ws_config, &pkgs, &mut index, dry_run)?;
 
         super::finish(failed, dry_run)
     }
@@ -146,76 +184,489 @@ impl PublishStep {
 
 pub fn publish(
     ws_meta: &cargo_metadata::Metadata,
+    ws_config: &crate::config::Config,
     pkgs: &[plan::PackageRelease],
     index: &mut crates_index::Index,
     dry_run: bool,
 ) -> Result<(), ProcessError> {
-    for pkg in pkgs {
-        if !pkg.config.publish() {
-            continue;
+    let deps = publish_deps(pkgs);
+    let has_dependents: std::collections::HashSet<usize> =
+        deps.iter().flatten().flatten().copied().collect();
+    let concurrency = ws_config.publish_concurrency().min(ws_config.jobs());
+    let lookups = ws_config.jobs();
+    let wait_for_deps = ws_config.after_publish_wait_for_deps();
+    let mut available = std::collections::HashSet::new();
+    for layer in layers(&deps) {
+        for chunk in layer.chunks(concurrency) {
+            if wait_for_deps {
+                let mut waits = Vec::new();
+                for dep in pending_deps(&deps, chunk, &available) {
+                    let dep_pkg = &pkgs[dep];
+                    let crate_name = dep_pkg.meta.name.as_str();
+                    let version = dep_pkg
+                        .planned_version
+                        .as_ref()
+                        .unwrap_or(&dep_pkg.initial_version);
+                    if dep_pkg.config.package_only() || dep_pkg.config.registry().is_some() {
+                        log::debug!(
+                            "Not waiting for {} because it wasn't published to crates.io",
+                            crate_name
+                        );
+                    } else if dep_pkg.config.publish_wait() == crate::config::PublishWait::Never {
+                        log::debug!("Not waiting for {} to show up in the index", crate_name);
+                    } else {
+                        waits.push((crate_name, version.full_version_string.as_str()));
+                    }
+                    available.insert(dep);
+                }
+                for waits in waits.chunks(lookups) {
+                    let timeout = std::time::Duration::from_secs(300);
+                    crate::ops::cargo::wait_for_publish(
+                        index,
+                        waits,
+                        timeout,
+                        ws_config.index_freshness(),
+                        dry_run,
+                    )?;
+                }
+                if !waits.is_empty() && !dry_run {
+                    grace_sleep();
+                }
+            }
+
+            let jobs: Vec<_> = chunk
+                .iter()
+                .map(|&i| {
+                    let job = PublishJob::new(ws_meta, &pkgs[i], pkgs.len(), dry_run);
+                    std::thread::spawn(move || job.run(dry_run))
+                })
+                .collect();
+            // Let every job in the chunk finish before reporting failures
+            let results: Vec<_> = jobs
+                .into_iter()
+                .map(|job| job.join().expect("publish thread panicked"))
+                .collect();
+            let mut success = true;
+            for result in results {
+                success &= result?;
+            }
+            if !success {
+                return Err(101.into());
+            }
+        }
+
+        let mut waits = Vec::new();
+        for &i in &layer {
+            let pkg = &pkgs[i];
+            let crate_name = pkg.meta.name.as_str();
+            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+            if pkg.config.package_only() {
+                if let Some(package_dir) = pkg.config.package_dir() {
+                    let crate_file =
+                        format!("{}-{}.crate", crate_name, version.full_version_string);
+                    let src = ws_meta
+                        .target_directory
+                        .as_std_path()
+                        .join("package")
+                        .join(&crate_file);
+                    let dest = ws_meta.workspace_root.as_std_path().join(package_dir);
+                    log::debug!("Copying {} to {}", src.display(), dest.display());
+                    if !dry_run {
+                        std::fs::create_dir_all(&dest)?;
+                        std::fs::copy(&src, dest.join(&crate_file))?;
+                    }
+                }
+            } else if pkg.config.registry().is_some() {
+                log::debug!("Not waiting for publish because the registry is not crates.io and doesn't get updated automatically");
+            } else if wait_for_deps && !pkg.config.verify_published_checksum() {
+                log::debug!(
+                    "Deferring the wait for {} until a dependent is published",
+                    crate_name
+                );
+            } else if !pkg.config.verify_published_checksum()
+                && !should_wait(pkg.config.publish_wait(), has_dependents.contains(&i))
+            {
+                log::debug!("Not waiting for {} to show up in the index", crate_name);
+            } else {
+                waits.push(i);
+            }
+        }
+
+        for waits in waits.chunks(lookups) {
+            let crates: Vec<_> = waits
+                .iter()
+                .map(|&i| {
+                    let pkg = &pkgs[i];
+                    let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+                    (pkg.meta.name.as_str(), version.full_version_string.as_str())
+                })
+                .collect();
+            let timeout = std::time::Duration::from_secs(300);
+            crate::ops::cargo::wait_for_publish(
+                index,
+                &crates,
+                timeout,
+                ws_config.index_freshness(),
+                dry_run,
+            )?;
+            for (&i, (crate_name, version)) in waits.iter().zip(crates) {
+                if pkgs[i].config.verify_published_checksum() {
+                    verify_checksum(ws_meta, index, crate_name, version, dry_run)?;
+                }
+            }
         }
 
+        if !waits.is_empty() && !dry_run {
+            grace_sleep();
+        }
+    }
+
+    Ok(())
+}
+
+/// Everything needed to run `cargo publish` / `cargo package` off the main thread
+struct PublishJob {
+    manifest_path: std::path::PathBuf,
+    pkgid: Option<String>,
+    features: crate::ops::cargo::Features,
+    no_default_features: bool,
+    registry: Option<String>,
+    target: Option<String>,
+    verify: bool,
+    jobs: Option<usize>,
+    package_only: bool,
+    cargo_bin: Option<String>,
+}
+
+impl PublishJob {
+    fn new(
+        ws_meta: &cargo_metadata::Metadata,
+        pkg: &plan::PackageRelease,
+        pkg_count: usize,
+        dry_run: bool,
+    ) -> Self {
         let crate_name = pkg.meta.name.as_str();
-        log::info!("Publishing {}", crate_name);
+        if pkg.config.package_only() {
+            log::info!("Packaging {}", crate_name);
+        } else {
+            log::info!("Publishing {}", crate_name);
+        }
 
         let verify = if !pkg.config.verify() {
             false
-        } else if dry_run && pkgs.len() != 1 {
+        } else if dry_run && pkg_count != 1 {
             log::debug!("Skipping verification to avoid unpublished dependencies from dry-run");
             false
         } else {
             true
         };
-        // feature list to release
-        let features = &pkg.features;
         let pkgid = if 1 < ws_meta.workspace_members.len() {
             // Override `workspace.default-members`
-            Some(crate_name)
+            Some(crate_name.to_owned())
         } else {
             // `-p` is not recommended outside of a workspace
             None
         };
-        if !crate::ops::cargo::publish(
-            dry_run,
-            verify,
-            &pkg.manifest_path,
+        Self {
+            manifest_path: pkg.manifest_path.clone(),
             pkgid,
-            features,
-            pkg.config.registry(),
-            pkg.config.target.as_ref().map(AsRef::as_ref),
-        )? {
-            return Err(101.into());
+            // feature list to release
+            features: pkg.features.clone(),
+            no_default_features: pkg.config.no_default_features(),
+            registry: pkg.config.registry().map(ToOwned::to_owned),
+            target: pkg.config.target.clone(),
+            verify,
+            jobs: pkg.config.jobs,
+            package_only: pkg.config.package_only(),
+            cargo_bin: pkg.config.cargo_bin().map(ToOwned::to_owned),
         }
+    }
 
-        if pkg.config.registry().is_none() {
-            let timeout = std::time::Duration::from_secs(300);
-            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-            crate::ops::cargo::wait_for_publish(
-                index,
-                crate_name,
-                &version.full_version_string,
-                timeout,
+    fn run(&self, dry_run: bool) -> Result<bool, FatalError> {
+        if self.package_only {
+            crate::ops::cargo::package(
                 dry_run,
-            )?;
-            // HACK: Even once the index is updated, there seems to be another step before the publish is fully ready.
-            // We don't have a way yet to check for that, so waiting for now in hopes everything is ready
-            if !dry_run {
-                let publish_grace_sleep = std::env::var("PUBLISH_GRACE_SLEEP")
-                    .unwrap_or_else(|_| Default::default())
-                    .parse()
-                    .unwrap_or(0);
-                if 0 < publish_grace_sleep {
-                    log::info!(
-                        "Waiting an additional {} seconds for crates.io to update its indices...",
-                        publish_grace_sleep
-                    );
-                    std::thread::sleep(std::time::Duration::from_secs(publish_grace_sleep));
-                }
-            }
+                self.verify,
+                self.jobs,
+                &self.manifest_path,
+                self.pkgid.as_deref(),
+                &self.features,
+                self.no_default_features,
+                self.target.as_deref(),
+                self.cargo_bin.as_deref(),
+            )
         } else {
-            log::debug!("Not waiting for publish because the registry is not crates.io and doesn't get updated automatically");
+            crate::ops::cargo::publish(
+                dry_run,
+                self.verify,
+                self.jobs,
+                &self.manifest_path,
+                self.pkgid.as_deref(),
+                &self.features,
+                self.no_default_features,
+                self.registry.as_deref(),
+                self.target.as_deref(),
+                self.cargo_bin.as_deref(),
+            )
         }
     }
+}
 
-    Ok(())
+// HACK: Even once the index is updated, there seems to be another step before the publish is fully ready.
+// We don't have a way yet to check for that, so waiting for now in hopes everything is ready
+fn grace_sleep() {
+    let publish_grace_sleep = std::env::var("PUBLISH_GRACE_SLEEP")
+        .unwrap_or_else(|_| Default::default())
+        .parse()
+        .unwrap_or(0);
+    if 0 < publish_grace_sleep {
+        log::info!(
+            "Waiting an additional {} seconds for crates.io to update its indices...",
+            publish_grace_sleep
+        );
+        std::thread::sleep(std::time::Duration::from_secs(publish_grace_sleep));
+    }
+}
+
+/// Check the checksum the index recorded for the upload against the locally packaged `.crate`
+fn verify_checksum(
+    ws_meta: &cargo_metadata::Metadata,
+    index: &crates_index::Index,
+    crate_name: &str,
+    version: &str,
+    dry_run: bool,
+) -> Result<(), ProcessError> {
+    let crate_file = ws_meta
+        .target_directory
+        .as_std_path()
+        .join("package")
+        .join(format!("{}-{}.crate", crate_name, version));
+    if dry_run {
+        log::debug!(
+            "Would compare the published checksum of {} {} with {}",
+            crate_name,
+            version,
+            crate_file.display()
+        );
+        return Ok(());
+    }
+
+    let local = crate::ops::cargo::file_checksum(&crate_file)?;
+    match crate::ops::cargo::published_checksum(index, crate_name, version) {
+        Some(published) if published == local => {
+            log::debug!("Published checksum of {} {} matches", crate_name, version);
+            Ok(())
+        }
+        Some(published) => {
+            log::error!(
+                "{} {} was published with checksum {} but {} has {}",
+                crate_name,
+                version,
+                published,
+                crate_file.display(),
+                local
+            );
+            Err(101.into())
+        }
+        None => {
+            log::error!("{} {} is missing from the index", crate_name, version);
+            Err(101.into())
+        }
+    }
+}
+
+fn should_wait(policy: crate::config::PublishWait, has_dependents: bool) -> bool {
+    match policy {
+        crate::config::PublishWait::Always => true,
+        crate::config::PublishWait::Dependencies => has_dependents,
+        crate::config::PublishWait::Never => false,
+    }
+}
+
+/// For each publishable crate in `pkgs`, the indices of the earlier publishable crates it depends
+/// on
+///
+/// `pkgs` must already be in dependency order.
+fn publish_deps(pkgs: &[plan::PackageRelease]) -> Vec<Option<Vec<usize>>> {
+    pkgs.iter()
+        .enumerate()
+        .map(|(i, pkg)| {
+            pkg.config.publish().then(|| {
+                pkg.meta
+                    .dependencies
+                    .iter()
+                    .filter(|d| d.kind != cargo_metadata::DependencyKind::Development)
+                    .filter_map(|d| pkgs[..i].iter().position(|p| p.meta.name == d.name))
+                    .filter(|&dep| pkgs[dep].config.publish())
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+/// Published dependencies of the `chunk` nodes not yet known to be `available`, each listed once
+fn pending_deps(
+    deps: &[Option<Vec<usize>>],
+    chunk: &[usize],
+    available: &std::collections::HashSet<usize>,
+) -> Vec<usize> {
+    let mut pending: Vec<usize> = chunk
+        .iter()
+        .filter_map(|&i| deps[i].as_ref())
+        .flatten()
+        .copied()
+        .filter(|&dep| deps[dep].is_some() && !available.contains(&dep))
+        .collect();
+    pending.sort_unstable();
+    pending.dedup();
+    pending
+}
+
+/// Group nodes so each one is in the layer after its deepest dependency, skipping `None` nodes
+///
+/// Dependencies must point to earlier nodes.
+fn layers(deps: &[Option<Vec<usize>>]) -> Vec<Vec<usize>> {
+    let mut depths: Vec<Option<usize>> = vec![None; deps.len()];
+    let mut layers: Vec<Vec<usize>> = Vec::new();
+    for (i, node_deps) in deps.iter().enumerate() {
+        let node_deps = match node_deps {
+            Some(node_deps) => node_deps,
+            None => continue,
+        };
+        let depth = node_deps
+            .iter()
+            .filter_map(|&dep| depths[dep])
+            .map(|depth| depth + 1)
+            .max()
+            .unwrap_or(0);
+        depths[i] = Some(depth);
+        if layers.len() <= depth {
+            layers.resize(depth + 1, Vec::new());
+        }
+        layers[depth].push(i);
+    }
+    layers
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod should_wait {
+        use super::*;
+
+        use crate::config::PublishWait;
+
+        #[test]
+        fn leaf() {
+            assert!(should_wait(PublishWait::Always, false));
+            assert!(!should_wait(PublishWait::Dependencies, false));
+            assert!(!should_wait(PublishWait::Never, false));
+        }
+
+        #[test]
+        fn dependency() {
+            assert!(should_wait(PublishWait::Always, true));
+            assert!(should_wait(PublishWait::Dependencies, true));
+            assert!(!should_wait(PublishWait::Never, true));
+        }
+    }
+
+    mod pending_deps {
+        use super::*;
+
+        #[test]
+        fn shared() {
+            let deps = [Some(vec![]), Some(vec![]), Some(vec![1, 0]), Some(vec![0])];
+            let available = std::collections::HashSet::new();
+            assert_eq!(pending_deps(&deps, &[2, 3], &available), vec![0, 1]);
+        }
+
+        #[test]
+        fn available() {
+            let deps = [Some(vec![]), Some(vec![]), Some(vec![0, 1])];
+            let available = [0].into_iter().collect();
+            assert_eq!(pending_deps(&deps, &[2], &available), vec![1]);
+        }
+
+        #[test]
+        fn unpublished() {
+            let deps = [Some(vec![]), None];
+            let available = std::collections::HashSet::new();
+            assert!(pending_deps(&deps, &[1], &available).is_empty());
+        }
+
+        #[test]
+        fn unpublished_dependency() {
+            let deps = [None, Some(vec![0])];
+            let available = std::collections::HashSet::new();
+            assert!(pending_deps(&deps, &[1], &available).is_empty());
+        }
+    }
+
+    mod publish_deps {
+        use super::*;
+
+        /// `mixed_ws` in dependency order: `c` depends on `b`, which depends on `a`
+        fn pkgs() -> Vec<plan::PackageRelease> {
+            ["a", "b", "c"]
+                .iter()
+                .map(|name| crate::test_support::pkg("mixed_ws", name))
+                .collect()
+        }
+
+        #[test]
+        fn chain() {
+            assert_eq!(
+                publish_deps(&pkgs()),
+                vec![Some(vec![]), Some(vec![0]), Some(vec![1])]
+            );
+        }
+
+        #[test]
+        fn lock_only_dependency() {
+            let mut pkgs = pkgs();
+            pkgs[0].config.lock_only = Some(true);
+            let deps = publish_deps(&pkgs);
+            assert_eq!(deps, vec![None, Some(vec![]), Some(vec![1])]);
+            let available = std::collections::HashSet::new();
+            assert!(pending_deps(&deps, &[1], &available).is_empty());
+            assert_eq!(layers(&deps), vec![vec![1], vec![2]]);
+        }
+
+        #[test]
+        fn unpublished_dependency() {
+            let mut pkgs = pkgs();
+            pkgs[1].config.publish = Some(false);
+            assert_eq!(publish_deps(&pkgs), vec![Some(vec![]), None, Some(vec![])]);
+        }
+    }
+
+    mod layers {
+        use super::*;
+
+        #[test]
+        fn independent() {
+            let deps = [Some(vec![]), Some(vec![]), Some(vec![])];
+            assert_eq!(layers(&deps), vec![vec![0, 1, 2]]);
+        }
+
+        #[test]
+        fn chain() {
+            let deps = [Some(vec![]), Some(vec![0]), Some(vec![1])];
+            assert_eq!(layers(&deps), vec![vec![0], vec![1], vec![2]]);
+        }
+
+        #[test]
+        fn diamond() {
+            let deps = [Some(vec![]), Some(vec![0]), Some(vec![0]), Some(vec![1, 2])];
+            assert_eq!(layers(&deps), vec![vec![0], vec![1, 2], vec![3]]);
+        }
+
+        #[test]
+        fn unpublished_dependency() {
+            let deps = [None, Some(vec![0]), Some(vec![1])];
+            assert_eq!(layers(&deps), vec![vec![1], vec![2]]);
+        }
+    }
 }
@@ -1,6 +1,5 @@
 use std::collections::HashSet;
 
-use crate::error::FatalError;
 use crate::error::ProcessError;
 use crate::ops::git;
 use crate::steps::plan;
@@ -26,10 +25,18 @@ pub struct PushStep {
     #[arg(long, value_delimiter = ',')]
     allow_branch: Option<Vec<String>>,
 
+    /// Don't check whether the branch is behind its remote
+    #[arg(long)]
+    allow_behind: bool,
+
     /// Actually perform a release. Dry-run mode is the default
     #[arg(short = 'x', long)]
     execute: bool,
 
+    /// Only show what would be done, the default.  Conflicts with `--execute`
+    #[arg(long, conflicts_with = "execute")]
+    dry_run: bool,
+
     /// Skip release confirmation and version preview
     #[arg(long)]
     no_confirm: bool,
@@ -45,14 +52,8 @@ impl PushStep {
     pub fn run(&self) -> Result<(), ProcessError> {
         git::git_version()?;
 
-        let ws_meta = self
-            .manifest
-            .metadata()
-            // When evaluating dependency ordering, we need to consider optional dependencies
-            .features(cargo_metadata::CargoOpt::AllFeatures)
-            .exec()
-            .map_err(FatalError::from)?;
         let config = self.to_config();
+        let ws_meta = super::load_metadata(&self.manifest, &config)?;
         let ws_config = crate::config::load_workspace_config(&config, &ws_meta)?;
         let mut pkgs = plan::load(&config, &ws_meta)?;
 
@@ -82,12 +83,13 @@ impl PushStep {
             return Err(2.into());
         }
 
-        let dry_run = !self.execute;
+        let dry_run = self.dry_run || !self.execute;
         let mut failed = false;
 
         // STEP 0: Help the user make the right decisions.
         failed |= !super::verify_git_is_clean(
             ws_meta.workspace_root.as_std_path(),
+            &ws_config,
             dry_run,
             log::Level::Error,
         )?;
@@ -101,13 +103,22 @@ impl PushStep {
             log::Level::Error,
         )?;
 
-        failed |= !super::verify_if_behind(
+        failed |= !super::verify_push_branch(
             ws_meta.workspace_root.as_std_path(),
             &ws_config,
             dry_run,
-            log::Level::Warn,
+            log::Level::Error,
         )?;
 
+        if !self.allow_behind {
+            failed |= !super::verify_if_behind(
+                ws_meta.workspace_root.as_std_path(),
+                &ws_config,
+                dry_run,
+                super::if_behind_level(&ws_config),
+            )?;
+        }
+
         // STEP 1: Release Confirmation
         super::confirm("Push", &pkgs, self.no_confirm, dry_run)?;
 
@@ -138,6 +149,13 @@ pub fn push(
     if ws_config.push() {
         let git_remote = ws_config.push_remote();
         let branch = crate::ops::git::current_branch(ws_meta.workspace_root.as_std_path())?;
+        let branch = match ws_config.push_branch() {
+            Some(push_branch) => {
+                let force = if ws_config.force_push() { "+" } else { "" };
+                format!("{}{}:{}", force, branch, push_branch)
+            }
+            None => branch,
+        };
 
         let mut shared_refs = HashSet::new();
         for pkg in pkgs {
@@ -180,3 +198,83 @@ pub fn push(
 
     Ok(())
 }
+
+/// Push only the tags of `pkgs`, leaving the branch for [`push`]
+///
+/// Returns whether any tags were pushed.
+pub fn push_tags(
+    ws_config: &crate::config::Config,
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+) -> Result<bool, ProcessError> {
+    if !ws_config.push() {
+        return Ok(false);
+    }
+    let mut tags: Vec<_> = pkgs
+        .iter()
+        .filter(|p| p.config.push())
+        .filter_map(|p| p.planned_tag.as_deref())
+        .collect();
+    tags.sort_unstable();
+    tags.dedup();
+    if tags.is_empty() {
+        return Ok(false);
+    }
+
+    let git_remote = ws_config.push_remote();
+    log::info!("Pushing {} to {}", tags.join(", "), git_remote);
+    if !git::push(
+        ws_meta.workspace_root.as_std_path(),
+        git_remote,
+        tags,
+        ws_config.push_options(),
+        dry_run,
+    )? {
+        return Err(101.into());
+    }
+    Ok(true)
+}
+
+/// Create a GitHub release for each pushed tag of a package with `github-release`
+pub fn github_releases(
+    ws_config: &crate::config::Config,
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+) -> Result<(), ProcessError> {
+    let mut pkgs = super::github_release_pkgs(ws_config, pkgs).peekable();
+    if pkgs.peek().is_none() {
+        return Ok(());
+    }
+    let ws_root = ws_meta.workspace_root.as_std_path();
+    let repo = super::github_repo(ws_root, ws_config)?;
+    for pkg in pkgs {
+        let tag_name = pkg
+            .planned_tag
+            .as_deref()
+            .expect("filtered for tagged packages");
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        let body = crate::ops::changelog::release_notes(
+            &[pkg.package_root.as_path(), ws_root],
+            pkg.config.changelog_path(),
+            &version.bare_version_string,
+        );
+        log::info!("Creating GitHub release {}", tag_name);
+        if !crate::ops::github::create_release(
+            &repo,
+            tag_name,
+            &body,
+            version.is_prerelease(),
+            dry_run,
+        )? {
+            log::error!(
+                "Failed to create the GitHub release for {}; the tag is already pushed",
+                tag_name
+            );
+            return Err(101.into());
+        }
+    }
+
+    Ok(())
+}
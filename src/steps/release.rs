@@ -12,6 +12,7 @@ use crate::ops::version;
 use crate::steps::plan;
 
 #[derive(Debug, Clone, clap::Args)]
+#[command(group = clap::ArgGroup::new("target_version").args(["level_or_version", "bump", "set_version"]))]
 pub struct ReleaseStep {
     #[command(flatten)]
     manifest: clap_cargo::Manifest,
@@ -19,12 +20,24 @@ pub struct ReleaseStep {
     #[command(flatten)]
     workspace: clap_cargo::Workspace,
 
+    /// Release the workspace members whose name matches GLOB, narrowed by `--exclude`
+    #[arg(long, value_name = "GLOB", value_parser = globset::Glob::new, conflicts_with = "package")]
+    include: Vec<globset::Glob>,
+
     /// Either bump by LEVEL or set the VERSION for all selected packages
     #[arg(value_name = "LEVEL|VERSION")]
     level_or_version: Option<version::TargetVersion>,
 
+    /// Bump all selected packages by LEVEL
+    #[arg(long, value_name = "LEVEL")]
+    bump: Option<version::BumpLevel>,
+
+    /// Set all selected packages to VERSION, or the lowest unpublished version matching a REQ
+    #[arg(long, value_name = "VERSION|REQ", value_parser = version::TargetVersion::parse_set_version)]
+    set_version: Option<version::TargetVersion>,
+
     /// Semver metadata
-    #[arg(short, long, requires = "level_or_version")]
+    #[arg(short, long, requires = "target_version")]
     metadata: Option<String>,
 
     #[command(flatten)]
@@ -34,6 +47,10 @@ pub struct ReleaseStep {
     #[arg(short = 'x', long)]
     execute: bool,
 
+    /// Only show what would be done, the default.  Conflicts with `--execute`
+    #[arg(long, conflicts_with = "execute")]
+    dry_run: bool,
+
     /// Skip release confirmation and version preview
     #[arg(long)]
     no_confirm: bool,
@@ -41,155 +58,719 @@ pub struct ReleaseStep {
     /// The name of tag for the previous release.
     #[arg(long)]
     prev_tag_name: Option<String>,
+
+    /// Show the full diff of manifest edits in dry-run
+    #[arg(long)]
+    verbose_diff: bool,
+
+    /// Don't check whether the branch is behind its remote
+    #[arg(long)]
+    allow_behind: bool,
+
+    /// Allow `--amend` even when the last commit was already pushed
+    #[arg(long)]
+    force_amend: bool,
+
+    /// Release each `--package` even if disabled with `release = false`
+    #[arg(long)]
+    allow_disabled: bool,
+
+    /// List the steps the release would take, without checking or changing anything
+    #[arg(long)]
+    list_steps: bool,
+
+    /// Stop as soon as a version is found to already be published, even in dry-run
+    #[arg(long)]
+    strict: bool,
+
+    /// Only release if HEAD is REV, e.g. the reviewed commit
+    #[arg(long, value_name = "REV")]
+    rev: Option<String>,
+
+    /// Check that `Cargo.lock` has the version of each package in its `Cargo.toml`
+    #[arg(long)]
+    force_version_check: bool,
+
+    /// Print the resolved config of the workspace and each package, then exit
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "toml"
+    )]
+    dump_config: Option<super::config::DumpFormat>,
+
+    /// Continue a partially-failed release from STEP, using the versions already in `Cargo.toml`
+    #[arg(long, value_name = "STEP", conflicts_with = "target_version")]
+    resume_from: Option<ResumeFrom>,
+
+    /// Skip all git operations, only updating versions and publishing
+    #[arg(long, conflicts_with_all = ["rev", "resume_from"])]
+    no_vcs: bool,
+
+    /// Succeed when no packages are selected, e.g. nothing changed since the last release
+    #[arg(long)]
+    allow_empty: bool,
+
+    /// Print the version the selected package would be released as, then exit
+    #[arg(long, conflicts_with_all = ["list_steps", "dump_config", "resume_from"])]
+    print_next: bool,
+
+    /// Apply the release's file edits to a copy of the workspace in DIR, to diff the result
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["execute", "resume_from"])]
+    dry_run_to: Option<std::path::PathBuf>,
+
+    /// Fail the dry-run if a pre-release hook changes tracked files
+    #[arg(long, conflicts_with = "execute")]
+    strict_dry_run: bool,
 }
 
 impl ReleaseStep {
     pub fn run(&self) -> Result<(), ProcessError> {
-        git::git_version()?;
-        let mut index = crates_index::Index::new_cargo_default()?;
-
-        let ws_meta = self
-            .manifest
-            .metadata()
-            // When evaluating dependency ordering, we need to consider optional dependencies
-            .features(cargo_metadata::CargoOpt::AllFeatures)
-            .exec()
-            .map_err(FatalError::from)?;
-        let ws_config = config::load_workspace_config(&self.config, &ws_meta)?;
-        let mut pkgs = plan::load(&self.config, &ws_meta)?;
+        if !self.no_vcs {
+            git::git_version()?;
+        }
 
-        for pkg in pkgs.values_mut() {
-            if let Some(prev_tag) = self.prev_tag_name.as_ref() {
-                // Trust the user that the tag passed in is the latest tag for the workspace and that
-                // they don't care about any changes from before this tag.
-                pkg.set_prior_tag(prev_tag.to_owned());
-            }
-            if let Some(level_or_version) = &self.level_or_version {
-                pkg.bump(level_or_version, self.metadata.as_deref())?;
-            }
+        if let Some(manifest_path) = self.manifest.manifest_path.as_deref() {
+            cargo::verify_manifest_path(manifest_path)?;
+        }
+        let ws_meta = super::load_metadata(&self.manifest, &self.config)?;
+
+        let mut opts = self.to_options(&ws_meta)?;
+        if !self.no_vcs && self.resume_from.is_none() {
+            opts.level_or_version =
+                release_as(ws_meta.workspace_root.as_std_path(), opts.level_or_version)?;
         }
+        run_release(&ws_meta, &opts)
+    }
+
+    fn target_version(&self) -> Option<version::TargetVersion> {
+        self.level_or_version
+            .clone()
+            .or_else(|| self.bump.map(version::TargetVersion::Relative))
+            .or_else(|| self.set_version.clone())
+    }
 
-        let (_selected_pkgs, excluded_pkgs) = self.workspace.partition_packages(&ws_meta);
-        for excluded_pkg in &excluded_pkgs {
-            let pkg = if let Some(pkg) = pkgs.get_mut(&excluded_pkg.id) {
-                pkg
+    fn to_options(&self, ws_meta: &cargo_metadata::Metadata) -> Result<ReleaseOptions, FatalError> {
+        let (selected_pkgs, excluded_pkgs) = if self.include.is_empty() {
+            self.workspace.partition_packages(ws_meta)
+        } else {
+            include_packages(&self.workspace, &self.include, ws_meta)?
+        };
+        // Without `--package`, `--manifest-path` to a member (or running from within a member's
+        // directory) scopes the release to that member while still loading the whole workspace
+        // for dependents
+        let root = ws_meta.resolve.as_ref().and_then(|r| r.root.as_ref());
+        let explicit = !self.workspace.package.is_empty()
+            || self.workspace.workspace
+            || self.workspace.all
+            || !self.include.is_empty();
+        if let Some(root) = root.filter(|_| !explicit && 1 < ws_meta.workspace_members.len()) {
+            let root_dir = ws_meta[root].manifest_path.parent();
+            let source = if self.manifest.manifest_path.is_some() {
+                "--manifest-path"
             } else {
-                // Either not in workspace or marked as `release = false`.
-                continue;
+                "the current directory"
             };
-            pkg.config.release = Some(false);
-            pkg.planned_version = None;
+            if root_dir != Some(ws_meta.workspace_root.as_path()) {
+                log::info!(
+                    "Releasing {} from {}, pass `--workspace` to release all members",
+                    ws_meta[root].name,
+                    source
+                );
+            } else {
+                log::debug!("Releasing {} from {}", ws_meta[root].name, source);
+            }
+        }
+        Ok(ReleaseOptions {
+            excluded_packages: excluded_pkgs.into_iter().map(|p| p.id.clone()).collect(),
+            level_or_version: self.target_version(),
+            metadata: self.metadata.clone(),
+            config: self.config.clone(),
+            dry_run: self.dry_run || !self.execute,
+            explicit_dry_run: self.dry_run,
+            no_confirm: self.no_confirm,
+            prev_tag_name: self.prev_tag_name.clone(),
+            verbose_diff: self.verbose_diff,
+            allow_behind: self.allow_behind,
+            force_amend: self.force_amend,
+            allow_disabled: if self.allow_disabled {
+                selected_pkgs
+                    .iter()
+                    .filter(|p| self.workspace.package.contains(&p.name))
+                    .map(|p| p.id.clone())
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            list_steps: self.list_steps,
+            strict: self.strict,
+            resume_from: self.resume_from,
+            dump_config: self.dump_config,
+            force_version_check: self.force_version_check,
+            rev: self.rev.clone(),
+            no_vcs: self.no_vcs,
+            allow_empty: self.allow_empty,
+            print_next: self.print_next,
+            dry_run_to: self.dry_run_to.clone(),
+            strict_dry_run: self.strict_dry_run,
+            select_interactively: !explicit
+                && self.resume_from.is_none()
+                && self.dump_config.is_none()
+                && !self.print_next
+                && !self.list_steps
+                && root.is_none()
+                && 1 < ws_meta.workspace_members.len()
+                && !self.no_confirm
+                && crate::ops::shell::is_interactive(),
+        })
+    }
+}
 
-            let crate_name = pkg.meta.name.as_str();
-            if let Some(prior_tag_name) = &pkg.prior_tag {
-                if let Some((changed, lock_changed)) =
-                    crate::steps::version::changed_since(&ws_meta, pkg, prior_tag_name)
-                {
-                    if !changed.is_empty() {
-                        log::warn!(
-                            "Disabled by user, skipping {} which has files changed since {}: {:#?}",
-                            crate_name,
-                            prior_tag_name,
-                            changed
-                        );
-                    } else if lock_changed {
-                        log::warn!(
+/// Select the workspace members matching any of `include`, then drop those `--exclude`d
+///
+/// Each pattern has to match at least one member, to catch typos.
+fn include_packages<'m>(
+    workspace: &clap_cargo::Workspace,
+    include: &[globset::Glob],
+    ws_meta: &'m cargo_metadata::Metadata,
+) -> Result<
+    (
+        Vec<&'m cargo_metadata::Package>,
+        Vec<&'m cargo_metadata::Package>,
+    ),
+    FatalError,
+> {
+    let mut workspace = workspace.clone();
+    workspace.workspace = true;
+    let (selected, mut excluded) = workspace.partition_packages(ws_meta);
+
+    let members: Vec<_> = ws_meta
+        .packages
+        .iter()
+        .filter(|p| ws_meta.workspace_members.contains(&p.id))
+        .collect();
+    let mut matchers = globset::GlobSetBuilder::new();
+    for glob in include {
+        let matcher = glob.compile_matcher();
+        if !members.iter().any(|p| matcher.is_match(p.name.as_str())) {
+            return Err(FatalError::NoIncludeMatch(glob.glob().to_owned()));
+        }
+        matchers.add(glob.clone());
+    }
+    let matchers = matchers
+        .build()
+        .expect("globs were already compiled individually");
+
+    let (selected, unmatched): (Vec<_>, Vec<_>) = selected
+        .into_iter()
+        .partition(|p| matchers.is_match(p.name.as_str()));
+    excluded.extend(unmatched);
+    Ok((selected, excluded))
+}
+
+/// Apply a `Release-As: <LEVEL|VERSION>` trailer from `HEAD`'s commit message
+///
+/// A level or version passed on the command-line wins over the trailer.
+fn release_as(
+    root: &Path,
+    level_or_version: Option<version::TargetVersion>,
+) -> Result<Option<version::TargetVersion>, FatalError> {
+    let message = git::head_message(root)?;
+    let trailer = match git::trailer(&message, "Release-As") {
+        Some(trailer) => trailer,
+        None => return Ok(level_or_version),
+    };
+    if let Some(level_or_version) = level_or_version {
+        log::info!(
+            "Ignoring `Release-As: {}` in the last commit, releasing {} as passed",
+            trailer,
+            level_or_version
+        );
+        return Ok(Some(level_or_version));
+    }
+    let level_or_version = trailer.parse::<version::TargetVersion>()?;
+    log::info!(
+        "Releasing {} from `Release-As` in the last commit",
+        level_or_version
+    );
+    Ok(Some(level_or_version))
+}
+
+/// Release settings, independent of how they were gathered
+///
+/// See [`ReleaseStep`] for what each field does on the command-line.
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseOptions {
+    /// Workspace members to leave out of this release
+    pub excluded_packages: Vec<cargo_metadata::PackageId>,
+    pub level_or_version: Option<version::TargetVersion>,
+    pub metadata: Option<String>,
+    pub config: config::ConfigArgs,
+    pub dry_run: bool,
+    /// `--dry-run` was passed, rather than dry-run being the default
+    pub explicit_dry_run: bool,
+    pub no_confirm: bool,
+    pub prev_tag_name: Option<String>,
+    pub verbose_diff: bool,
+    pub allow_behind: bool,
+    pub force_amend: bool,
+    /// Packages to release even when disabled in config
+    pub allow_disabled: Vec<cargo_metadata::PackageId>,
+    /// Print an overview of the release steps instead of running them
+    pub list_steps: bool,
+    /// Abort on already-published versions, even in dry-run
+    pub strict: bool,
+    /// Skip the steps a failed release already completed
+    pub resume_from: Option<ResumeFrom>,
+    /// Print the resolved config instead of releasing
+    pub dump_config: Option<super::config::DumpFormat>,
+    /// Check the locked versions of packages against their manifests
+    pub force_version_check: bool,
+    /// The commit that must be checked out
+    pub rev: Option<String>,
+    /// Release without git: no checks of the repository, commits, tags, or pushes
+    pub no_vcs: bool,
+    /// Treat an empty selection as nothing to do, rather than an error
+    pub allow_empty: bool,
+    /// Print the planned version of the one selected package instead of releasing
+    pub print_next: bool,
+    /// Copy the workspace here and write the release's file edits to the copy
+    pub dry_run_to: Option<std::path::PathBuf>,
+    /// Check that hooks leave tracked files alone in dry-run
+    pub strict_dry_run: bool,
+    /// Prompt for which packages to release and how to bump them
+    pub select_interactively: bool,
+}
+
+/// Where to pick up a release that failed part way through
+///
+/// Each step assumes the ones before it completed:
+/// - `publish`: the versions in `Cargo.toml` are committed; already published crates are skipped
+/// - `tag`: the crates are published
+/// - `push`: the crates are published and tagged; the development versions, if any, may already
+///   be committed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ResumeFrom {
+    Publish,
+    Tag,
+    Push,
+}
+
+impl ResumeFrom {
+    /// Whether `step` still has to run when resuming from `self`
+    fn runs(self, step: ResumeFrom, tag_before_publish: bool) -> bool {
+        let order = publish_tag_order(tag_before_publish);
+        let position = |step| match step {
+            ResumeFrom::Publish | ResumeFrom::Tag => order
+                .iter()
+                .position(|s| ResumeFrom::from(*s) == step)
+                .expect("all of publish and tag are ordered"),
+            ResumeFrom::Push => order.len(),
+        };
+        position(self) <= position(step)
+    }
+}
+
+impl From<PublishTag> for ResumeFrom {
+    fn from(step: PublishTag) -> Self {
+        match step {
+            PublishTag::Publish => ResumeFrom::Publish,
+            PublishTag::Tag | PublishTag::PushTags => ResumeFrom::Tag,
+        }
+    }
+}
+
+/// Release the workspace described by `ws_meta`
+///
+/// `ws_meta` should be loaded with all features enabled so dependency ordering accounts for
+/// optional dependencies.
+pub fn run_release(
+    ws_meta: &cargo_metadata::Metadata,
+    opts: &ReleaseOptions,
+) -> Result<(), ProcessError> {
+    let mut ws_config = config::load_workspace_config(&opts.config, ws_meta)?;
+    let mut pkgs =
+        plan::load_allowing_disabled(&opts.config, ws_meta, &opts.allow_disabled, !opts.no_vcs)?;
+    if opts.no_vcs {
+        without_vcs(&mut ws_config);
+        for pkg in pkgs.values_mut() {
+            without_vcs(&mut pkg.config);
+        }
+    }
+
+    if let Some(format) = opts.dump_config {
+        use std::io::Write;
+
+        let output = super::config::dump(&ws_config, pkgs.values(), format)?;
+        std::io::stdout().write_all(output.as_bytes())?;
+        return Ok(());
+    }
+
+    // Opened here only when resolving a version needs it, otherwise before the checks
+    let published_index = match &opts.level_or_version {
+        Some(level_or_version) if level_or_version.is_matching() => {
+            Some(cargo::open_index(ws_config.index_freshness())?)
+        }
+        _ => None,
+    };
+    for pkg in pkgs.values_mut() {
+        if let Some(prev_tag) = opts.prev_tag_name.as_ref() {
+            // Trust the user that the tag passed in is the latest tag for the workspace and that
+            // they don't care about any changes from before this tag.
+            pkg.set_prior_tag(prev_tag.to_owned());
+        }
+        if let Some(level_or_version) = &opts.level_or_version {
+            let level_or_version =
+                super::resolve_target_version(level_or_version, pkg, published_index.as_ref())?;
+            pkg.bump(&level_or_version, opts.metadata.as_deref())?;
+        }
+    }
+
+    for excluded_id in &opts.excluded_packages {
+        let pkg = if let Some(pkg) = pkgs.get_mut(excluded_id) {
+            pkg
+        } else {
+            // Either not in workspace or marked as `release = false`.
+            continue;
+        };
+        pkg.config.release = Some(false);
+        pkg.planned_version = None;
+
+        let crate_name = pkg.meta.name.as_str();
+        if let Some(prior_tag_name) = &pkg.prior_tag {
+            if let Some(changes) =
+                crate::steps::version::changed_since(ws_meta, pkg, prior_tag_name)
+            {
+                if !changes.files.is_empty() {
+                    log::warn!(
+                        "Disabled by user, skipping {} which has files changed since {}: {:#?}",
+                        crate_name,
+                        prior_tag_name,
+                        changes.files
+                    );
+                } else if changes.lock_changed {
+                    log::warn!(
                         "Disabled by user, skipping {} despite lock file being changed since {}",
                         crate_name,
                         prior_tag_name
                     );
-                    } else {
-                        log::trace!(
-                            "Disabled by user, skipping {} (no changes since {})",
-                            crate_name,
-                            prior_tag_name
-                        );
-                    }
                 } else {
-                    log::debug!(
-                        "Disabled by user, skipping {} (no {} tag)",
+                    log::trace!(
+                        "Disabled by user, skipping {} (no changes since {})",
                         crate_name,
                         prior_tag_name
                     );
                 }
             } else {
-                log::debug!("Disabled by user, skipping {} (no tag found)", crate_name,);
+                log::debug!(
+                    "Disabled by user, skipping {} (no {} tag)",
+                    crate_name,
+                    prior_tag_name
+                );
+            }
+        } else {
+            log::debug!("Disabled by user, skipping {} (no tag found)", crate_name,);
+        }
+    }
+
+    if opts.select_interactively {
+        let ask_level = opts.level_or_version.is_none();
+        select_packages(&mut pkgs, ask_level, opts.metadata.as_deref())?;
+    }
+
+    let releasing: Vec<_> = pkgs
+        .values()
+        .filter(|p| p.config.release())
+        .map(|p| &p.meta.id)
+        .collect();
+    super::verify_no_dependency_cycles(ws_meta, &releasing)?;
+    let mut pkgs = plan::plan(pkgs)?;
+
+    for pkg in pkgs.values_mut() {
+        if !pkg.config.release() || !pkg.config.skip_dependency_bumps() {
+            continue;
+        }
+        let prior_tag_name = match pkg.prior_tag.clone() {
+            Some(prior_tag_name) => prior_tag_name,
+            None => continue,
+        };
+        let changes = crate::steps::version::changed_since(ws_meta, pkg, &prior_tag_name);
+        if let Some(changes) = changes {
+            if changes.dependency_bump && changes.files.is_empty() && !changes.lock_changed {
+                log::info!(
+                    "Skipping {}, its only change since {} is a dependency version bump",
+                    pkg.meta.name,
+                    prior_tag_name
+                );
+                pkg.config.release = Some(false);
+                pkg.planned_version = None;
             }
         }
+    }
 
-        let pkgs = plan::plan(pkgs)?;
+    let (mut pkgs, skipped): (Vec<_>, Vec<_>) = pkgs
+        .into_iter()
+        .map(|(_, pkg)| pkg)
+        .partition(|p| p.config.release());
+    if pkgs.is_empty() {
+        if opts.allow_empty {
+            log::info!("No packages selected, nothing to release.");
+            return Ok(());
+        }
+        log::info!("No packages selected.");
+        return Err(2.into());
+    }
 
-        for excluded_pkg in &excluded_pkgs {
-            let pkg = if let Some(pkg) = pkgs.get(&excluded_pkg.id) {
-                pkg
-            } else {
-                // Either not in workspace or marked as `release = false`.
-                continue;
-            };
+    if opts.print_next {
+        use std::io::Write;
 
-            if pkg.config.publish() && pkg.config.registry().is_none() {
-                let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-                let crate_name = pkg.meta.name.as_str();
-                if !cargo::is_published(&index, crate_name, &version.full_version_string) {
-                    log::warn!(
-                        "Disabled by user, skipping {} v{} despite being unpublished",
-                        crate_name,
-                        version.full_version_string,
-                    );
-                }
+        let pkg = match pkgs.as_slice() {
+            [pkg] => pkg,
+            _ => {
+                let names: Vec<_> = pkgs.iter().map(|p| p.meta.name.as_str()).collect();
+                log::error!(
+                    "`--print-next` needs exactly one package, selected {}",
+                    names.join(", ")
+                );
+                return Err(101.into());
             }
+        };
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        writeln!(std::io::stdout(), "{}", version.full_version_string)?;
+        return Ok(());
+    }
+
+    let tag_before_publish = ws_config.tag_before_publish();
+    let runs = |step| {
+        opts.resume_from
+            .map_or(true, |r| r.runs(step, tag_before_publish))
+    };
+    if let Some(resume_from) = opts.resume_from {
+        resume(&mut pkgs, resume_from)?;
+    }
+
+    if opts.list_steps {
+        use std::io::Write;
+
+        let mut stdout = std::io::stdout();
+        for (i, step) in list_steps(&ws_config, &pkgs, opts.resume_from, !opts.no_vcs)
+            .iter()
+            .enumerate()
+        {
+            writeln!(stdout, "{}. {}", i + 1, step)?;
         }
+        return Ok(());
+    }
 
-        let pkgs: Vec<_> = pkgs
-            .into_iter()
-            .map(|(_, pkg)| pkg)
-            .filter(|p| p.config.release())
-            .collect();
-        if pkgs.is_empty() {
-            log::info!("No packages selected.");
-            return Err(2.into());
+    let mut index = match published_index {
+        Some(index) => index,
+        None => cargo::open_index(ws_config.index_freshness())?,
+    };
+
+    let excluded = skipped
+        .iter()
+        .filter(|p| opts.excluded_packages.contains(&p.meta.id));
+    for pkg in excluded {
+        if pkg.config.publish() && pkg.config.registry().is_none() {
+            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+            let crate_name = pkg.meta.name.as_str();
+            if !cargo::is_published(&index, crate_name, &version.full_version_string) {
+                log::warn!(
+                    "Disabled by user, skipping {} v{} despite being unpublished",
+                    crate_name,
+                    version.full_version_string,
+                );
+            }
         }
+    }
+
+    let dry_run = opts.dry_run;
+    let strict_dry_run = dry_run && opts.strict_dry_run && !opts.no_vcs;
+    let mut failed = false;
 
-        let dry_run = !self.execute;
-        let mut failed = false;
+    // STEP 0: Help the user make the right decisions.
+    if let Some(rev) = opts.rev.as_deref() {
+        failed |= !super::verify_rev(
+            ws_meta.workspace_root.as_std_path(),
+            rev,
+            dry_run,
+            log::Level::Error,
+        )?;
+    }
 
-        // STEP 0: Help the user make the right decisions.
+    if !opts.no_vcs {
         failed |= !super::verify_git_is_clean(
             ws_meta.workspace_root.as_std_path(),
+            &ws_config,
             dry_run,
             log::Level::Error,
         )?;
+    }
 
-        failed |= !super::verify_tags_missing(&pkgs, dry_run, log::Level::Error)?;
+    failed |= !super::verify_tags_unique(&pkgs, dry_run, log::Level::Error)?;
 
-        failed |= !super::verify_monotonically_increasing(&pkgs, dry_run, log::Level::Error)?;
+    failed |= !super::verify_literal_tag_name(
+        &pkgs,
+        opts.config
+            .tag
+            .literal_tag_name(ws_config.template_delimiters().0),
+        ws_config.template_delimiters(),
+        dry_run,
+        log::Level::Error,
+    )?;
 
-        let mut double_publish = false;
-        for pkg in &pkgs {
-            if !pkg.config.publish() {
-                continue;
-            }
-            if pkg.config.registry().is_none() {
-                let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-                let crate_name = pkg.meta.name.as_str();
-                if cargo::is_published(&index, crate_name, &version.full_version_string) {
-                    log::error!(
-                        "{} {} is already published",
-                        crate_name,
-                        version.full_version_string
-                    );
-                    double_publish = true;
+    if opts.no_vcs {
+        log::debug!("Not checking tags, `--no-vcs`");
+    } else if runs(ResumeFrom::Tag) {
+        // Version bumps and replacements always make a release commit, moving `HEAD` past any
+        // existing tag
+        let commit_pending = opts.resume_from.is_none()
+            && (ws_config.amend()
+                || pkgs.iter().any(|p| {
+                    p.planned_version.is_some()
+                        || p.config.amend()
+                        || !p.pre_release_replacements().is_empty()
+                }));
+        failed |= !super::verify_tags_missing(&pkgs, commit_pending, dry_run, log::Level::Error)?;
+    } else {
+        failed |= !super::verify_tags_exist(&pkgs, dry_run, log::Level::Error)?;
+    }
+    if !runs(ResumeFrom::Publish) {
+        failed |= !super::verify_published(&pkgs, &index, dry_run, log::Level::Error)?;
+    }
+
+    let amend = ws_config.amend() || pkgs.iter().any(|p| p.config.amend());
+    if amend && !opts.force_amend && opts.resume_from.is_none() && !opts.no_vcs {
+        failed |= !super::verify_amend_unpushed(
+            ws_meta.workspace_root.as_std_path(),
+            &ws_config,
+            dry_run,
+            log::Level::Error,
+        )?;
+    }
+
+    if ws_config.squash_dev_version()
+        && !opts.no_vcs
+        && pkgs
+            .iter()
+            .any(|p| p.post_version.is_some() && !p.config.tag())
+    {
+        log::warn!(
+            "`squash-dev-version` without `tag` leaves nothing pointing at the released version"
+        );
+    }
+
+    if !opts.no_vcs {
+        failed |= !super::verify_lock_tracked(ws_meta, &pkgs, dry_run, log::Level::Error)?;
+    }
+
+    if opts.force_version_check {
+        failed |= !super::verify_locked_versions(ws_meta, &pkgs, dry_run, log::Level::Error)?;
+    }
+
+    failed |= !super::verify_monotonically_increasing(&pkgs, dry_run, log::Level::Error)?;
+
+    failed |= !super::verify_version_edits(ws_meta, &pkgs, dry_run, log::Level::Error)?;
+
+    failed |=
+        !super::verify_placeholder_version(&pkgs, dry_run, super::placeholder_level(&ws_config))?;
+
+    failed |= !super::verify_registry_newer(
+        &pkgs,
+        &index,
+        dry_run,
+        super::registry_newer_level(&ws_config),
+    )?;
+
+    failed |= !super::verify_changelog(ws_meta, &pkgs, dry_run, log::Level::Warn)?;
+
+    failed |= !super::verify_publishable_dependencies(ws_meta, &pkgs, dry_run, log::Level::Error)?;
+
+    failed |= !super::verify_no_path_only_deps_in_publish(&pkgs, dry_run, log::Level::Error)?;
+
+    failed |= !super::verify_features_exclusive(&pkgs, dry_run, log::Level::Error)?;
+
+    failed |= !super::verify_registries_allowed(&pkgs, dry_run, log::Level::Error)?;
+
+    failed |= !super::verify_edition(ws_meta, &pkgs, dry_run, super::edition_level(&ws_config))?;
+
+    let mut double_publish = false;
+    let mut registry_indexes = std::collections::HashMap::new();
+    for pkg in &mut pkgs {
+        if !runs(ResumeFrom::Publish) || !pkg.config.publish() || pkg.config.package_only() {
+            continue;
+        }
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        let crate_name = pkg.meta.name.as_str();
+        let mut published = false;
+        if let Some(registry) = pkg.config.registry() {
+            let registry_index = registry_indexes
+                .entry(registry.to_owned())
+                .or_insert_with(|| {
+                    cargo::open_registry_index(ws_meta.workspace_root.as_std_path(), registry)
+                        .unwrap_or_else(|err| {
+                            log::warn!(
+                                "Unable to check whether {} {} is already published to {}: {}",
+                                crate_name,
+                                version.full_version_string,
+                                registry,
+                                err
+                            );
+                            None
+                        })
+                });
+            if let Some(registry_index) = registry_index {
+                if cargo::is_published(registry_index, crate_name, &version.full_version_string) {
+                    if opts.resume_from.is_some() {
+                        log::info!(
+                            "{} {} is already published to {}, skipping",
+                            crate_name,
+                            version.full_version_string,
+                            registry
+                        );
+                        published = true;
+                    } else {
+                        log::error!(
+                            "{} {} is already published to {}",
+                            crate_name,
+                            version.full_version_string,
+                            registry
+                        );
+                        double_publish = true;
+                    }
                 }
             }
-        }
-        if double_publish {
-            failed = true;
-            if !dry_run {
-                return Err(101.into());
+        } else if cargo::is_published(&index, crate_name, &version.full_version_string) {
+            if opts.resume_from.is_some() {
+                log::info!(
+                    "{} {} is already published, skipping",
+                    crate_name,
+                    version.full_version_string
+                );
+                published = true;
+            } else {
+                log::error!(
+                    "{} {} is already published",
+                    crate_name,
+                    version.full_version_string
+                );
+                double_publish = true;
             }
         }
+        if published {
+            pkg.config.publish = Some(false);
+        }
+    }
+    if double_publish {
+        failed = true;
+        if !dry_run || opts.strict {
+            return Err(101.into());
+        }
+    }
 
-        super::warn_changed(&ws_meta, &pkgs)?;
+    if !opts.no_vcs {
+        super::warn_changed(ws_meta, &pkgs)?;
 
         failed |= !super::verify_git_branch(
             ws_meta.workspace_root.as_std_path(),
@@ -198,272 +779,1856 @@ impl ReleaseStep {
             log::Level::Error,
         )?;
 
-        failed |= !super::verify_if_behind(
+        failed |= !super::verify_github_release(
+            ws_meta.workspace_root.as_std_path(),
+            &ws_config,
+            &pkgs,
+            dry_run,
+            log::Level::Error,
+        )?;
+
+        failed |= !super::verify_repository(
+            ws_meta.workspace_root.as_std_path(),
+            &ws_config,
+            &pkgs,
+            dry_run,
+            super::repository_level(&ws_config),
+        )?;
+
+        failed |= !super::verify_push_branch(
             ws_meta.workspace_root.as_std_path(),
             &ws_config,
             dry_run,
-            log::Level::Warn,
+            log::Level::Error,
         )?;
 
+        if !opts.allow_behind {
+            failed |= !super::verify_if_behind(
+                ws_meta.workspace_root.as_std_path(),
+                &ws_config,
+                dry_run,
+                super::if_behind_level(&ws_config),
+            )?;
+        }
+    }
+
+    failed |= !super::verify_semver_compatibility(ws_meta, &pkgs, dry_run, log::Level::Error)?;
+
+    failed |= !super::verify_features_buildable(ws_meta, &pkgs, dry_run, log::Level::Error)?;
+
+    failed |= !super::verify_commands(ws_meta, &ws_config, dry_run, log::Level::Error)?;
+
+    if runs(ResumeFrom::Publish) {
         failed |= !super::verify_rate_limit(&pkgs, &index, dry_run, log::Level::Error)?;
+    }
 
-        let shared_version = super::find_shared_versions(&pkgs)?;
+    let shared_version = super::find_shared_versions(&pkgs)?;
 
-        // STEP 1: Release Confirmation
-        super::confirm("Release", &pkgs, self.no_confirm, dry_run)?;
+    // STEP 1: Release Confirmation
+    super::confirm_release(&pkgs, opts.no_confirm, dry_run)?;
 
-        // STEP 2: update current version, save and commit
-        let mut shared_commit = false;
-        for pkg in &pkgs {
-            let cwd = &pkg.package_root;
-            let crate_name = pkg.meta.name.as_str();
+    if let Some(dir) = opts.dry_run_to.as_deref() {
+        write_preview(dir, ws_meta, &ws_config, &pkgs, opts)?;
+    }
 
-            if let Some(version) = pkg.planned_version.as_ref() {
-                log::info!(
-                    "Update {} to version {}",
-                    crate_name,
-                    version.full_version_string
-                );
-                cargo::set_package_version(
-                    &pkg.manifest_path,
-                    version.full_version_string.as_str(),
+    if let Some(hook) = ws_config
+        .workspace_pre_release_hook()
+        .filter(|_| opts.resume_from.is_none())
+    {
+        if !unchanged_by_hook(
+            strict_dry_run,
+            ws_meta.workspace_root.as_std_path(),
+            "workspace-pre-release",
+            || {
+                run_workspace_hook(
+                    "workspace-pre-release",
+                    hook,
+                    &ws_config,
+                    ws_meta,
+                    &pkgs,
                     dry_run,
-                )?;
-                crate::steps::version::update_dependent_versions(pkg, version, dry_run)?;
-                if dry_run {
-                    log::debug!("Updating lock file");
-                } else {
-                    cargo::update_lock(&pkg.manifest_path)?;
-                }
-            }
+                )
+            },
+        )? {
+            log::error!("Release aborted by non-zero return of workspace pre-release hook.");
+            return Err(101.into());
+        }
+    }
 
-            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-            let prev_version_var = pkg.initial_version.bare_version_string.as_str();
-            let prev_metadata_var = pkg.initial_version.full_version.build.as_str();
-            let version_var = version.bare_version_string.as_str();
-            let metadata_var = version.full_version.build.as_str();
-            if !pkg.config.pre_release_replacements().is_empty() {
-                // try replacing text in configured files
-                let template = Template {
-                    prev_version: Some(prev_version_var),
-                    prev_metadata: Some(prev_metadata_var),
-                    version: Some(version_var),
-                    metadata: Some(metadata_var),
-                    crate_name: Some(crate_name),
-                    date: Some(NOW.as_str()),
-                    tag_name: pkg.planned_tag.as_deref(),
-                    ..Default::default()
-                };
-                let prerelease = version.is_prerelease();
-                let noisy = false;
-                do_file_replacements(
-                    pkg.config.pre_release_replacements(),
-                    &template,
-                    cwd,
-                    prerelease,
-                    noisy,
-                    dry_run,
-                )?;
-            }
-
-            // pre-release hook
-            if let Some(pre_rel_hook) = pkg.config.pre_release_hook() {
-                let template = Template {
-                    prev_version: Some(prev_version_var),
-                    prev_metadata: Some(prev_metadata_var),
-                    version: Some(version_var),
-                    metadata: Some(metadata_var),
-                    crate_name: Some(crate_name),
-                    date: Some(NOW.as_str()),
-                    tag_name: pkg.planned_tag.as_deref(),
-                    ..Default::default()
-                };
-                let pre_rel_hook = pre_rel_hook
-                    .args()
-                    .into_iter()
-                    .map(|arg| template.render(arg));
-                log::debug!("Calling pre-release hook: {:?}", pre_rel_hook);
-                let envs = maplit::btreemap! {
-                    OsStr::new("PREV_VERSION") => prev_version_var.as_ref(),
-                    OsStr::new("PREV_METADATA") => prev_metadata_var.as_ref(),
-                    OsStr::new("NEW_VERSION") => version_var.as_ref(),
-                    OsStr::new("NEW_METADATA") => metadata_var.as_ref(),
-                    OsStr::new("DRY_RUN") => OsStr::new(if dry_run { "true" } else { "false" }),
-                    OsStr::new("CRATE_NAME") => OsStr::new(crate_name),
-                    OsStr::new("WORKSPACE_ROOT") => ws_meta.workspace_root.as_os_str(),
-                    OsStr::new("CRATE_ROOT") => pkg.manifest_path.parent().unwrap_or_else(|| Path::new(".")).as_os_str(),
-                };
-                // we use dry_run environmental variable to run the script
-                // so here we set dry_run=false and always execute the command.
-                if !cmd::call_with_env(pre_rel_hook, envs, cwd, false)? {
+    // STEP 2: update current version, save and commit
+    let release_committed =
+        commit_release(ws_meta, &ws_config, &pkgs, opts, shared_version.as_ref())?;
+
+    let release_commit = if dry_run || opts.no_vcs {
+        None
+    } else {
+        Some(git::head_id(ws_meta.workspace_root.as_std_path())?)
+    };
+
+    // STEP 3 & 5: cargo publish and tag
+    let mut tagged = false;
+    let mut tags_pushed = false;
+    for &step in publish_tag_order(tag_before_publish) {
+        if !runs(step.into()) {
+            log::debug!("Resuming, skipping {:?}", step);
+            continue;
+        }
+        match step {
+            PublishTag::Publish => {
+                let published =
+                    super::publish::publish(ws_meta, &ws_config, &pkgs, &mut index, dry_run);
+                if published.is_err() && tagged {
+                    let tags = pkgs
+                        .iter()
+                        .filter_map(|p| p.planned_tag.as_deref())
+                        .collect::<Vec<_>>();
                     log::error!(
-                        "Release of {} aborted by non-zero return of prerelease hook.",
-                        crate_name
+                        "Publish failed after tagging; remove the tags with `git tag -d {}`",
+                        tags.join(" ")
                     );
-                    return Err(101.into());
+                    if tags_pushed {
+                        log::error!(
+                            "The tags were already pushed; remove them with `git push --delete {} {}`",
+                            ws_config.push_remote(),
+                            tags.join(" ")
+                        );
+                    }
                 }
+                published?;
             }
-
-            if pkg.config.consolidate_commits() {
-                shared_commit = true;
-            } else {
-                let template = Template {
-                    prev_version: Some(prev_version_var),
-                    prev_metadata: Some(prev_metadata_var),
-                    version: Some(version_var),
-                    metadata: Some(metadata_var),
-                    crate_name: Some(crate_name),
-                    date: Some(NOW.as_str()),
-                    ..Default::default()
-                };
-                let commit_msg = template.render(pkg.config.pre_release_commit_message());
-                let sign = pkg.config.sign_commit();
-                if !git::commit_all(cwd, &commit_msg, sign, dry_run)? {
-                    // commit failed, abort release
-                    return Err(101.into());
-                }
+            PublishTag::Tag => {
+                super::tag::tag(&pkgs, dry_run)?;
+                tagged = true;
             }
-        }
-        if shared_commit {
-            let shared_commit_msg = {
-                let version_var = shared_version
-                    .as_ref()
-                    .map(|v| v.bare_version_string.as_str());
-                let metadata_var = shared_version
-                    .as_ref()
-                    .map(|v| v.full_version.build.as_str());
-                let template = Template {
-                    version: version_var,
-                    metadata: metadata_var,
-                    date: Some(NOW.as_str()),
-                    ..Default::default()
-                };
-                template.render(ws_config.pre_release_commit_message())
-            };
-            if !git::commit_all(
-                ws_meta.workspace_root.as_std_path(),
-                &shared_commit_msg,
-                ws_config.sign_commit(),
-                dry_run,
-            )? {
-                // commit failed, abort release
-                return Err(101.into());
+            PublishTag::PushTags => {
+                tags_pushed = super::push::push_tags(&ws_config, ws_meta, &pkgs, dry_run)?;
             }
         }
+    }
 
-        // STEP 3: cargo publish
-        super::publish::publish(&ws_meta, &pkgs, &mut index, dry_run)?;
+    // STEP 6: bump version
+    commit_dev_versions(
+        ws_meta,
+        &ws_config,
+        &pkgs,
+        opts,
+        shared_version.as_ref(),
+        release_committed,
+    )?;
 
-        // STEP 5: Tag
-        super::tag::tag(&pkgs, dry_run)?;
+    // STEP 7: git push
+    super::push::push(&ws_config, ws_meta, &pkgs, dry_run)?;
 
-        // STEP 6: bump version
-        let mut shared_commit = false;
-        let mut shared_post_version: Option<version::Version> = None;
-        for pkg in &pkgs {
-            if let Some(next_version) = pkg.post_version.as_ref() {
-                let cwd = &pkg.package_root;
-                let crate_name = pkg.meta.name.as_str();
+    // STEP 8: GitHub releases
+    super::push::github_releases(&ws_config, ws_meta, &pkgs, dry_run)?;
 
-                log::info!(
-                    "Starting {}'s next development iteration {}",
+    if let Some(hook) = ws_config.workspace_post_release_hook() {
+        if !run_workspace_hook(
+            "workspace-post-release",
+            hook,
+            &ws_config,
+            ws_meta,
+            &pkgs,
+            dry_run,
+        )? {
+            log::error!("Workspace post-release hook returned non-zero.");
+            return Err(101.into());
+        }
+    }
+
+    if let Some(path) = ws_config.release_manifest() {
+        write_release_manifest(
+            path,
+            ws_meta,
+            &pkgs,
+            release_commit.as_deref(),
+            dry_run,
+            opts.explicit_dry_run,
+        )?;
+    }
+
+    super::finish(failed, dry_run)
+}
+
+/// Update the versions of `pkgs`, apply their pre-release replacements and hooks, and commit,
+/// returning whether a release commit was made
+///
+/// Packages committing on their own do so in [`commit_order`], before the consolidated commit
+/// of the rest.
+fn commit_release(
+    ws_meta: &cargo_metadata::Metadata,
+    ws_config: &config::Config,
+    pkgs: &[plan::PackageRelease],
+    opts: &ReleaseOptions,
+    shared_version: Option<&version::Version>,
+) -> Result<bool, ProcessError> {
+    let dry_run = opts.dry_run;
+    let strict_dry_run = dry_run && opts.strict_dry_run && !opts.no_vcs;
+    let mut shared_commit = false;
+    let mut release_committed = false;
+    let plan_json = plan::to_json(pkgs).to_string();
+    let bumped: &[_] = if opts.resume_from.is_none() {
+        pkgs
+    } else {
+        log::debug!("Resuming, versions were already updated and committed");
+        &[]
+    };
+    for pkg in commit_order(bumped) {
+        let cwd = &pkg.package_root;
+        let crate_name = pkg.meta.name.as_str();
+
+        if let Some(version) = pkg.planned_version.as_ref() {
+            log::info!(
+                "Update {} to version {}",
+                crate_name,
+                version.full_version_string
+            );
+            update_manifests(pkg, version, opts.verbose_diff, dry_run)?;
+            if dry_run {
+                log::debug!("Updating lock file");
+            } else {
+                cargo::update_lock(&pkg.manifest_path, pkg.config.cargo_bin())?;
+                let lock_path = ws_meta.workspace_root.as_std_path().join("Cargo.lock");
+                if pkg.config.lock_only()
+                    && !cargo::is_locked(&lock_path, crate_name, &version.full_version_string)?
+                {
+                    log::error!(
+                        "Cargo.lock was not updated to {} {}",
+                        crate_name,
+                        version.full_version_string
+                    );
+                    return Err(101.into());
+                }
+            }
+        }
+
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        let prev_version_var = pkg.initial_version.bare_version_string.as_str();
+        let prev_metadata_var = pkg.initial_version.full_version.build.as_str();
+        let version_var = version.bare_version_string.as_str();
+        let metadata_var = version.full_version.build.as_str();
+        if !pre_release_replacements(pkg, dry_run)? {
+            return Err(101.into());
+        }
+
+        // pre-release hook
+        if let Some(pre_rel_hook) = pkg.config.pre_release_hook() {
+            let template = pre_release_template(pkg);
+            let pre_rel_hook = pre_rel_hook
+                .args()
+                .into_iter()
+                .map(|arg| template.render(arg));
+            log::debug!("Calling pre-release hook: {:?}", pre_rel_hook);
+            let file_envs = hook_env_file(pkg.config.hook_env_file(), ws_meta, &template)?;
+            let mut envs = maplit::btreemap! {
+                OsStr::new("PREV_VERSION") => prev_version_var.as_ref(),
+                OsStr::new("PREV_METADATA") => prev_metadata_var.as_ref(),
+                OsStr::new("NEW_VERSION") => version_var.as_ref(),
+                OsStr::new("NEW_METADATA") => metadata_var.as_ref(),
+                OsStr::new("DRY_RUN") => OsStr::new(if dry_run { "true" } else { "false" }),
+                OsStr::new("CRATE_NAME") => OsStr::new(crate_name),
+                OsStr::new("WORKSPACE_ROOT") => ws_meta.workspace_root.as_os_str(),
+                OsStr::new("CRATE_ROOT") => pkg.manifest_path.parent().unwrap_or_else(|| Path::new(".")).as_os_str(),
+            };
+            if pkg.config.hook_plan_env() {
+                envs.insert(OsStr::new("RELEASE_PLAN_JSON"), OsStr::new(&plan_json));
+            }
+            for (key, value) in &file_envs {
+                envs.entry(OsStr::new(key)).or_insert(OsStr::new(value));
+            }
+            let hook_dir = match pkg.config.pre_release_hook_dir() {
+                Some(dir) => ws_meta
+                    .workspace_root
+                    .as_std_path()
+                    .join(template.render(dir)),
+                None => cwd.to_owned(),
+            };
+            if !hook_dir.is_dir() {
+                log::error!(
+                    "Release of {} aborted, pre-release hook directory {} does not exist",
                     crate_name,
-                    next_version.full_version_string
+                    hook_dir.display()
                 );
-                crate::steps::version::update_dependent_versions(pkg, next_version, dry_run)?;
-                cargo::set_package_version(
-                    &pkg.manifest_path,
-                    next_version.full_version_string.as_str(),
+                return Err(101.into());
+            }
+            // we use dry_run environmental variable to run the script
+            // so here we set dry_run=false and always execute the command.
+            if !unchanged_by_hook(strict_dry_run, cwd, "pre-release", || {
+                cmd::call_with_env(
+                    pre_rel_hook,
+                    envs,
+                    &hook_dir,
+                    pkg.config.hook_timeout(),
+                    false,
+                )
+                .map_err(ProcessError::from)
+            })? {
+                log::error!(
+                    "Release of {} aborted by non-zero return of prerelease hook.",
+                    crate_name
+                );
+                return Err(101.into());
+            }
+        }
+
+        if opts.no_vcs {
+            log::debug!("Leaving {} uncommitted, `--no-vcs`", crate_name);
+        } else if pkg.config.consolidate_commits() {
+            shared_commit = true;
+        } else if !dry_run && !pkg.config.amend() && !git::has_changes_to_commit(cwd)? {
+            log::warn!(
+                "Skipping release commit for {}, nothing changed",
+                crate_name
+            );
+        } else {
+            let template = Template {
+                prev_version: Some(prev_version_var),
+                prev_metadata: Some(prev_metadata_var),
+                version: Some(version_var),
+                metadata: Some(metadata_var),
+                crate_name: Some(crate_name),
+                date: Some(NOW.as_str()),
+                delimiters: Some(pkg.config.template_delimiters()),
+                ..Default::default()
+            };
+            let commit_msg = template.render(&pkg.config.pre_release_commit_message());
+            let sign = pkg.config.sign_commit();
+            let committed = if pkg.config.amend() {
+                git::amend_all(cwd, sign, dry_run)?
+            } else {
+                git::commit_all(cwd, &commit_msg, sign, dry_run)?
+            };
+            if !committed {
+                // commit failed, abort release
+                return Err(101.into());
+            }
+            release_committed = true;
+        }
+    }
+    if shared_commit
+        && !dry_run
+        && !ws_config.amend()
+        && !git::has_changes_to_commit(ws_meta.workspace_root.as_std_path())?
+    {
+        log::warn!("Skipping the release commit, nothing changed");
+    } else if shared_commit {
+        let shared_commit_msg = {
+            let version_var = shared_version
+                .as_ref()
+                .map(|v| v.bare_version_string.as_str());
+            let metadata_var = shared_version
+                .as_ref()
+                .map(|v| v.full_version.build.as_str());
+            let template = Template {
+                version: version_var,
+                metadata: metadata_var,
+                date: Some(NOW.as_str()),
+                delimiters: Some(ws_config.template_delimiters()),
+                ..Default::default()
+            };
+            template.render(&ws_config.pre_release_commit_message())
+        };
+        let committed = if ws_config.amend() {
+            git::amend_all(
+                ws_meta.workspace_root.as_std_path(),
+                ws_config.sign_commit(),
+                dry_run,
+            )?
+        } else {
+            git::commit_all(
+                ws_meta.workspace_root.as_std_path(),
+                &shared_commit_msg,
+                ws_config.sign_commit(),
+                dry_run,
+            )?
+        };
+        if !committed {
+            // commit failed, abort release
+            return Err(101.into());
+        }
+        release_committed = true;
+    }
+    Ok(release_committed)
+}
+
+/// Set the development versions of `pkgs` and commit them, or amend them into the release commit
+/// when [`squash_dev_versions`]
+fn commit_dev_versions(
+    ws_meta: &cargo_metadata::Metadata,
+    ws_config: &config::Config,
+    pkgs: &[plan::PackageRelease],
+    opts: &ReleaseOptions,
+    shared_version: Option<&version::Version>,
+    release_committed: bool,
+) -> Result<(), ProcessError> {
+    let dry_run = opts.dry_run;
+    let mut shared_commit = false;
+    let squash = squash_dev_versions(ws_config, pkgs, release_committed, opts.no_vcs);
+    let mut squashed = false;
+    let mut shared_post_version: Option<version::Version> = None;
+    let mut dev_uncommitted = false;
+    if pkgs.iter().all(|p| p.post_version.is_none()) {
+        log::debug!("No development versions planned, skipping post-release commit");
+    }
+    for pkg in commit_order(pkgs) {
+        if let Some(next_version) = pkg.post_version.as_ref() {
+            let cwd = &pkg.package_root;
+            let crate_name = pkg.meta.name.as_str();
+
+            log::info!(
+                "Starting {}'s next development iteration {}",
+                crate_name,
+                next_version.full_version_string
+            );
+            crate::steps::version::update_dependent_versions(
+                pkg,
+                next_version,
+                opts.verbose_diff,
+                dry_run,
+            )?;
+            cargo::set_package_version(
+                &pkg.manifest_path,
+                next_version.full_version_string.as_str(),
+                opts.verbose_diff,
+                dry_run,
+            )?;
+            if !dry_run {
+                cargo::update_lock(&pkg.manifest_path, pkg.config.cargo_bin())?;
+            }
+            let template = post_release_template(pkg, next_version);
+            if !pkg.config.post_release_replacements().is_empty() {
+                // try replacing text in configured files
+                let noisy = false;
+                if !do_file_replacements(
+                    pkg.config.post_release_replacements(),
+                    &template,
+                    cwd,
+                    false, // post-release replacements should always be applied
+                    noisy,
                     dry_run,
-                )?;
-                if !dry_run {
-                    cargo::update_lock(&pkg.manifest_path)?;
+                )? {
+                    return Err(101.into());
                 }
-                let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-                let prev_version_var = pkg.initial_version.bare_version_string.as_str();
-                let prev_metadata_var = pkg.initial_version.full_version.build.as_str();
-                let version_var = version.bare_version_string.as_str();
-                let metadata_var = version.full_version.build.as_str();
-                let next_version_var = next_version.bare_version_string.as_ref();
-                let next_metadata_var = next_version.full_version.build.as_ref();
-                let template = Template {
-                    prev_version: Some(prev_version_var),
-                    prev_metadata: Some(prev_metadata_var),
-                    version: Some(version_var),
-                    metadata: Some(metadata_var),
-                    crate_name: Some(crate_name),
-                    date: Some(NOW.as_str()),
-                    tag_name: pkg.planned_tag.as_deref(),
-                    next_version: Some(next_version_var),
-                    next_metadata: Some(next_metadata_var),
-                    ..Default::default()
-                };
-                if !pkg.config.post_release_replacements().is_empty() {
-                    // try replacing text in configured files
-                    let noisy = false;
-                    do_file_replacements(
-                        pkg.config.post_release_replacements(),
-                        &template,
-                        cwd,
-                        false, // post-release replacements should always be applied
-                        noisy,
-                        dry_run,
-                    )?;
+            }
+
+            if pkg.config.shared_version() && shared_post_version.is_none() {
+                shared_post_version = Some(next_version.clone());
+            }
+            if !ws_config.dev_version_commit() || opts.no_vcs {
+                dev_uncommitted = true;
+            } else if squash {
+                squashed = true;
+            } else if pkg.config.consolidate_commits() {
+                shared_commit = true;
+            } else {
+                let sign = pkg.config.sign_commit();
+
+                let commit_msg = template.render(&pkg.config.post_release_commit_message());
+                if !git::commit_all(cwd, &commit_msg, sign, dry_run)? {
+                    return Err(101.into());
                 }
+            }
+        }
+    }
+    if shared_commit {
+        let shared_commit_msg = {
+            let version_var = shared_version
+                .as_ref()
+                .map(|v| v.bare_version_string.as_str());
+            let metadata_var = shared_version
+                .as_ref()
+                .map(|v| v.full_version.build.as_str());
+            let next_version_var = shared_post_version
+                .as_ref()
+                .map(|v| v.bare_version_string.as_str());
+            let next_metadata_var = shared_post_version
+                .as_ref()
+                .map(|v| v.full_version.build.as_str());
+            let template = Template {
+                version: version_var,
+                metadata: metadata_var,
+                date: Some(NOW.as_str()),
+                next_version: next_version_var,
+                next_metadata: next_metadata_var,
+                delimiters: Some(ws_config.template_delimiters()),
+                ..Default::default()
+            };
+            template.render(&ws_config.post_release_commit_message())
+        };
+        if !git::commit_all(
+            ws_meta.workspace_root.as_std_path(),
+            &shared_commit_msg,
+            ws_config.sign_commit(),
+            dry_run,
+        )? {
+            // commit failed, abort release
+            return Err(101.into());
+        }
+    }
+    if squashed {
+        // The tags already point at the release commit, so amending leaves them on its sibling
+        log::info!("Squashing the development versions into the release commit");
+        if !git::amend_all(
+            ws_meta.workspace_root.as_std_path(),
+            ws_config.sign_commit(),
+            dry_run,
+        )? {
+            return Err(101.into());
+        }
+    }
+    if dev_uncommitted {
+        log::warn!(
+            "Development versions were left uncommitted, the working tree has uncommitted changes"
+        );
+    }
 
-                if pkg.config.shared_version() && shared_post_version.is_none() {
-                    shared_post_version = Some(next_version.clone());
+    Ok(())
+}
+
+/// Whether STEP 6 amends the development versions into the release commit
+///
+/// That only leaves a single commit when there was a single release commit, so when several
+/// packages commit on their own, the development versions are committed separately.
+fn squash_dev_versions(
+    ws_config: &config::Config,
+    pkgs: &[plan::PackageRelease],
+    release_committed: bool,
+    no_vcs: bool,
+) -> bool {
+    if !ws_config.squash_dev_version() || !ws_config.dev_version_commit() || no_vcs {
+        return false;
+    }
+    if pkgs.iter().all(|p| p.post_version.is_none()) {
+        return false;
+    }
+    let separate = pkgs
+        .iter()
+        .filter(|p| !p.config.consolidate_commits())
+        .count();
+    let shared = pkgs.iter().any(|p| p.config.consolidate_commits());
+    if separate + usize::from(shared) > 1 {
+        log::warn!(
+            "Not squashing the development versions into one of several release commits, committing them separately"
+        );
+        return false;
+    }
+    if !release_committed {
+        log::warn!(
+            "No release commit to squash the development versions into, committing them separately"
+        );
+        return false;
+    }
+    true
+}
+
+/// The variables for `pkg`'s post-release replacements and commit, starting `next_version`
+fn post_release_template<'a>(
+    pkg: &'a plan::PackageRelease,
+    next_version: &'a version::Version,
+) -> Template<'a> {
+    let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+    Template {
+        prev_version: Some(pkg.initial_version.bare_version_string.as_str()),
+        prev_metadata: Some(pkg.initial_version.full_version.build.as_str()),
+        version: Some(version.bare_version_string.as_str()),
+        metadata: Some(version.full_version.build.as_str()),
+        crate_name: Some(pkg.meta.name.as_str()),
+        date: Some(NOW.as_str()),
+        tag_name: Some(pkg.planned_tag.as_deref().unwrap_or_default()),
+        next_version: Some(next_version.bare_version_string.as_str()),
+        next_metadata: Some(next_version.full_version.build.as_str()),
+        delimiters: Some(pkg.config.template_delimiters()),
+        ..Default::default()
+    }
+}
+
+/// Turn off the parts of `config` that need git, for `--no-vcs`
+fn without_vcs(config: &mut config::Config) {
+    config.tag = Some(false);
+    config.push = Some(false);
+    config.amend = Some(false);
+}
+
+/// Write `release-manifest`, relative to the workspace root
+///
+/// In dry-run, this is only written for an explicit `--dry-run`, with a `.dry-run` suffix.
+fn write_release_manifest(
+    path: &Path,
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    release_commit: Option<&str>,
+    dry_run: bool,
+    explicit_dry_run: bool,
+) -> Result<(), FatalError> {
+    let mut path = ws_meta.workspace_root.as_std_path().join(path);
+    if dry_run {
+        if !explicit_dry_run {
+            log::debug!("Not writing {} in dry-run", path.display());
+            return Ok(());
+        }
+        let mut dry_run_path = path.into_os_string();
+        dry_run_path.push(".dry-run");
+        path = dry_run_path.into();
+    }
+
+    let timestamp = time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap();
+    let manifest = plan::to_manifest(pkgs, release_commit, &timestamp);
+    let mut manifest = serde_json::to_string_pretty(&manifest).map_err(std::io::Error::from)?;
+    manifest.push('\n');
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, manifest)?;
+    log::info!("Wrote release manifest to {}", path.display());
+    Ok(())
+}
+
+/// Describe, in order, what [`run_release`] will do for `pkgs`
+fn list_steps(
+    ws_config: &config::Config,
+    pkgs: &[plan::PackageRelease],
+    resume_from: Option<ResumeFrom>,
+    vcs: bool,
+) -> Vec<String> {
+    fn names(
+        pkgs: &[plan::PackageRelease],
+        pred: impl Fn(&plan::PackageRelease) -> bool,
+    ) -> Vec<&str> {
+        pkgs.iter()
+            .filter(|p| pred(p))
+            .map(|p| p.meta.name.as_str())
+            .collect()
+    }
+
+    let mut steps = Vec::new();
+    let mut release_committed = false;
+
+    // Resuming picks up after the versions were committed
+    if resume_from.is_none() {
+        if ws_config.workspace_pre_release_hook().is_some() {
+            steps.push("run workspace pre-release hook".to_owned());
+        }
+        let bumped: Vec<_> = pkgs
+            .iter()
+            .filter_map(|p| {
+                p.planned_version
+                    .as_ref()
+                    .map(|v| format!("{} {}", p.meta.name, v.full_version_string))
+            })
+            .collect();
+        if !bumped.is_empty() {
+            steps.push(format!("bump versions ({})", bumped.join(", ")));
+        }
+        let replaced = names(pkgs, |p| !p.pre_release_replacements().is_empty());
+        if !replaced.is_empty() {
+            steps.push(format!(
+                "apply pre-release replacements ({})",
+                replaced.join(", ")
+            ));
+        }
+        let hooked = names(pkgs, |p| p.config.pre_release_hook().is_some());
+        if !hooked.is_empty() {
+            steps.push(format!("run pre-release hook ({})", hooked.join(", ")));
+        }
+        let separate = names(pkgs, |p| !p.config.consolidate_commits());
+        if vcs && !separate.is_empty() {
+            steps.push(format!("commit ({})", separate.join(", ")));
+            release_committed = true;
+        }
+        if vcs && pkgs.iter().any(|p| p.config.consolidate_commits()) {
+            steps.push(if ws_config.amend() {
+                "amend the last commit".to_owned()
+            } else {
+                "commit".to_owned()
+            });
+            release_committed = true;
+        }
+    }
+
+    let published = names(pkgs, |p| p.config.publish() && !p.config.package_only());
+    let packaged = names(pkgs, |p| p.config.publish() && p.config.package_only());
+    let mut tags: Vec<_> = pkgs
+        .iter()
+        .filter_map(|p| p.planned_tag.as_deref())
+        .collect();
+    tags.dedup();
+    let tag_before_publish = ws_config.tag_before_publish();
+    let pushed_tags: Vec<_> = pkgs
+        .iter()
+        .filter(|p| p.config.push())
+        .filter_map(|p| p.planned_tag.as_deref())
+        .collect();
+    for &step in publish_tag_order(tag_before_publish) {
+        if resume_from.is_some_and(|r| !r.runs(step.into(), tag_before_publish)) {
+            continue;
+        }
+        match step {
+            PublishTag::Publish => {
+                if !published.is_empty() {
+                    steps.push(format!("publish ({})", published.join(", ")));
                 }
-                if pkg.config.consolidate_commits() {
-                    shared_commit = true;
-                } else {
-                    let sign = pkg.config.sign_commit();
+                if !packaged.is_empty() {
+                    steps.push(format!("package ({})", packaged.join(", ")));
+                }
+            }
+            PublishTag::Tag => {
+                if !tags.is_empty() {
+                    steps.push(format!("tag ({})", tags.join(", ")));
+                }
+            }
+            PublishTag::PushTags => {
+                if ws_config.push() && !pushed_tags.is_empty() {
+                    steps.push(format!(
+                        "push tags to {} ({})",
+                        ws_config.push_remote(),
+                        pushed_tags.join(", ")
+                    ));
+                }
+            }
+        }
+    }
+
+    let dev_bumped: Vec<_> = pkgs
+        .iter()
+        .filter_map(|p| {
+            p.post_version
+                .as_ref()
+                .map(|v| format!("{} {}", p.meta.name, v.full_version_string))
+        })
+        .collect();
+    if !dev_bumped.is_empty() {
+        steps.push(format!(
+            "bump to development versions ({})",
+            dev_bumped.join(", ")
+        ));
+        let replaced = names(pkgs, |p| {
+            p.post_version.is_some() && !p.config.post_release_replacements().is_empty()
+        });
+        if !replaced.is_empty() {
+            steps.push(format!(
+                "apply post-release replacements ({})",
+                replaced.join(", ")
+            ));
+        }
+        if squash_dev_versions(ws_config, pkgs, release_committed, !vcs) {
+            steps.push("amend the release commit".to_owned());
+        } else if vcs && ws_config.dev_version_commit() {
+            let separate = names(pkgs, |p| {
+                p.post_version.is_some() && !p.config.consolidate_commits()
+            });
+            if !separate.is_empty() {
+                steps.push(format!("commit ({})", separate.join(", ")));
+            }
+            if pkgs
+                .iter()
+                .any(|p| p.post_version.is_some() && p.config.consolidate_commits())
+            {
+                steps.push("commit".to_owned());
+            }
+        }
+    }
+
+    if ws_config.push() && pkgs.iter().any(|p| p.config.push()) {
+        match ws_config.push_branch() {
+            Some(push_branch) => steps.push(format!(
+                "push to {}/{}",
+                ws_config.push_remote(),
+                push_branch
+            )),
+            None => steps.push(format!("push to {}", ws_config.push_remote())),
+        }
+    }
+    let github_releases: Vec<_> = super::github_release_pkgs(ws_config, pkgs)
+        .filter_map(|p| p.planned_tag.as_deref())
+        .collect();
+    if !github_releases.is_empty() {
+        steps.push(format!(
+            "create GitHub releases ({})",
+            github_releases.join(", ")
+        ));
+    }
+    if ws_config.workspace_post_release_hook().is_some() {
+        steps.push("run workspace post-release hook".to_owned());
+    }
+
+    steps
+}
 
-                    let commit_msg = template.render(pkg.config.post_release_commit_message());
-                    if !git::commit_all(cwd, &commit_msg, sign, dry_run)? {
+/// Adjust the plan to pick up a release at `resume_from`
+///
+/// The plan treats the versions in `Cargo.toml` as the ones being released, as they were bumped
+/// before the release failed, so a planned bump is dropped rather than applied a second time.
+/// Once the development versions are committed, that is no longer true, so those packages can only
+/// resume from `push`, re-using their last tag.
+fn resume(pkgs: &mut [plan::PackageRelease], resume_from: ResumeFrom) -> Result<(), ProcessError> {
+    for pkg in pkgs {
+        if let Some(planned_version) = pkg.planned_version.take() {
+            log::debug!(
+                "Resuming, releasing {} {} as already bumped rather than {}",
+                pkg.meta.name,
+                pkg.initial_version.full_version_string,
+                planned_version.full_version_string
+            );
+            pkg.plan()?;
+        }
+        let crate_name = pkg.meta.name.as_str();
+        let dev_version = is_dev_version(pkg);
+        if resume_from == ResumeFrom::Push {
+            if dev_version && pkg.planned_tag.is_some() {
+                match pkg.prior_tag.as_ref() {
+                    Some(prior_tag) => {
+                        log::debug!(
+                            "{} is at development version {}, pushing {}",
+                            crate_name,
+                            pkg.initial_version.full_version_string,
+                            prior_tag
+                        );
+                        pkg.planned_tag = Some(prior_tag.clone());
+                    }
+                    None => {
+                        log::error!(
+                            "{} is at development version {} but has no tag to push",
+                            crate_name,
+                            pkg.initial_version.full_version_string
+                        );
                         return Err(101.into());
                     }
                 }
             }
+            // Either done before the failed push or, without a development version, not needed
+            pkg.post_version = None;
+        } else if dev_version {
+            log::error!(
+                "{} is already at development version {}; resume from `push` or check out the release commit",
+                crate_name,
+                pkg.initial_version.full_version_string
+            );
+            return Err(101.into());
         }
-        if shared_commit {
-            let shared_commit_msg = {
-                let version_var = shared_version
-                    .as_ref()
-                    .map(|v| v.bare_version_string.as_str());
-                let metadata_var = shared_version
-                    .as_ref()
-                    .map(|v| v.full_version.build.as_str());
-                let next_version_var = shared_post_version
-                    .as_ref()
-                    .map(|v| v.bare_version_string.as_str());
-                let next_metadata_var = shared_post_version
-                    .as_ref()
-                    .map(|v| v.full_version.build.as_str());
-                let template = Template {
-                    version: version_var,
-                    metadata: metadata_var,
-                    date: Some(NOW.as_str()),
-                    next_version: next_version_var,
-                    next_metadata: next_metadata_var,
-                    ..Default::default()
-                };
-                template.render(ws_config.post_release_commit_message())
+    }
+    Ok(())
+}
+
+/// Whether `pkg` is at the development version a release would have bumped it to
+fn is_dev_version(pkg: &plan::PackageRelease) -> bool {
+    pkg.config.dev_version()
+        && pkg.initial_version.full_version.pre.as_str() == pkg.config.dev_version_ext()
+}
+
+/// Packages committing on their own, then those sharing a commit, each in release order
+///
+/// `git commit -a` picks up every change in the tree, so a package sharing the commit has to be
+/// updated after the last package committing on its own, or its changes would land in that
+/// package's commit.
+fn commit_order(pkgs: &[plan::PackageRelease]) -> impl Iterator<Item = &plan::PackageRelease> + '_ {
+    let (shared, separate): (Vec<_>, Vec<_>) =
+        pkgs.iter().partition(|p| p.config.consolidate_commits());
+    separate.into_iter().chain(shared)
+}
+
+/// Set `pkg`'s version to `version` and update the requirements of the packages depending on it
+fn update_manifests(
+    pkg: &plan::PackageRelease,
+    version: &version::Version,
+    verbose_diff: bool,
+    dry_run: bool,
+) -> Result<(), ProcessError> {
+    cargo::set_package_version(
+        &pkg.manifest_path,
+        version.full_version_string.as_str(),
+        verbose_diff,
+        dry_run,
+    )?;
+    crate::steps::version::update_dependent_versions(pkg, version, verbose_diff, dry_run)?;
+    Ok(())
+}
+
+/// The variables for `pkg`'s pre-release replacements and hook
+fn pre_release_template(pkg: &plan::PackageRelease) -> Template<'_> {
+    let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+    Template {
+        prev_version: Some(pkg.initial_version.bare_version_string.as_str()),
+        prev_metadata: Some(pkg.initial_version.full_version.build.as_str()),
+        version: Some(version.bare_version_string.as_str()),
+        metadata: Some(version.full_version.build.as_str()),
+        crate_name: Some(pkg.meta.name.as_str()),
+        date: Some(NOW.as_str()),
+        tag_name: Some(pkg.planned_tag.as_deref().unwrap_or_default()),
+        delimiters: Some(pkg.config.template_delimiters()),
+        ..Default::default()
+    }
+}
+
+/// Apply `pkg`'s pre-release replacements, returning whether they all matched as configured
+fn pre_release_replacements(
+    pkg: &plan::PackageRelease,
+    dry_run: bool,
+) -> Result<bool, ProcessError> {
+    let replacements = pkg.pre_release_replacements();
+    if replacements.is_empty() {
+        return Ok(true);
+    }
+    let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+    let noisy = false;
+    let ok = do_file_replacements(
+        &replacements,
+        &pre_release_template(pkg),
+        &pkg.package_root,
+        version.is_prerelease(),
+        noisy,
+        dry_run,
+    )?;
+    Ok(ok)
+}
+
+/// Copy the workspace to `dir` and apply the version bumps, replacements, and lock updates of
+/// STEP 2 to the copy, leaving the workspace itself untouched
+fn write_preview(
+    dir: &Path,
+    ws_meta: &cargo_metadata::Metadata,
+    ws_config: &config::Config,
+    pkgs: &[plan::PackageRelease],
+    opts: &ReleaseOptions,
+) -> Result<(), ProcessError> {
+    if dir.read_dir().is_ok_and(|mut d| d.next().is_some()) {
+        log::error!(
+            "`--dry-run-to` needs an empty directory, {} is not",
+            dir.display()
+        );
+        return Err(101.into());
+    }
+    std::fs::create_dir_all(dir)?;
+    let dir = dir.canonicalize()?;
+    let root = ws_meta.workspace_root.as_std_path();
+    log::info!("Copying {} to {}", root.display(), dir.display());
+    let skip = [
+        root.join(".git"),
+        ws_meta.target_directory.as_std_path().to_owned(),
+        dir.clone(),
+    ];
+    copy_dir(root, &dir, &skip)?;
+
+    // Plan from the copy so that every manifest path, including dependents', points into it
+    let mut cmd = cargo_metadata::MetadataCommand::new();
+    cmd.manifest_path(dir.join("Cargo.toml"));
+    if let Some(features) = ws_config.dependency_features().cargo_opt() {
+        cmd.features(features);
+    }
+    let copy_meta = cmd.exec().map_err(FatalError::from)?;
+    let copy_pkgs: Vec<_> = plan::load_allowing_disabled(&opts.config, &copy_meta, &[], false)?
+        .into_iter()
+        .filter_map(|(_, mut copy)| {
+            let pkg = pkgs.iter().find(|p| p.meta.name == copy.meta.name)?;
+            copy.config = pkg.config.clone();
+            copy.planned_version = pkg.planned_version.clone();
+            copy.planned_tag = pkg.planned_tag.clone();
+            Some(copy)
+        })
+        .collect();
+
+    let dry_run = false;
+    for pkg in commit_order(&copy_pkgs) {
+        if let Some(version) = pkg.planned_version.as_ref() {
+            update_manifests(pkg, version, opts.verbose_diff, dry_run)?;
+            cargo::update_lock(&pkg.manifest_path, pkg.config.cargo_bin())?;
+        }
+        if !pre_release_replacements(pkg, dry_run)? {
+            return Err(101.into());
+        }
+    }
+
+    log::info!(
+        "Wrote the release's file edits to {}, compare it with {}",
+        dir.display(),
+        root.display()
+    );
+    Ok(())
+}
+
+/// Recursively copy `src` to `dest`, leaving out the paths in `skip`
+///
+/// Symlinks are copied as links, not followed, so links to a parent can't recurse forever and
+/// dangling links don't fail the copy.
+fn copy_dir(src: &Path, dest: &Path, skip: &[std::path::PathBuf]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let path = entry.path();
+        if skip.contains(&path) {
+            continue;
+        }
+        let dest = dest.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            copy_symlink(&path, &dest)?;
+        } else if file_type.is_dir() {
+            copy_dir(&path, &dest, skip)?;
+        } else {
+            std::fs::copy(&path, &dest)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(src)?, dest)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, _dest: &Path) -> std::io::Result<()> {
+    log::debug!("Not copying symlink {}", src.display());
+    Ok(())
+}
+
+/// Run `hook`, and when `strict`, fail if it changed any tracked files under `root`
+///
+/// Hooks always run, even in dry-run, so `--strict-dry-run` checks they respect `DRY_RUN`.
+fn unchanged_by_hook(
+    strict: bool,
+    root: &Path,
+    name: &str,
+    hook: impl FnOnce() -> Result<bool, ProcessError>,
+) -> Result<bool, ProcessError> {
+    if !strict {
+        return hook();
+    }
+
+    let before = git::tracked_changes(root)?;
+    let success = hook()?;
+    let after = git::tracked_changes(root)?;
+    if before != after {
+        let mut changed: Vec<_> = after
+            .iter()
+            .filter(|(path, oid)| before.get(*path) != Some(*oid))
+            .map(|(path, _)| path)
+            .chain(before.keys().filter(|path| !after.contains_key(*path)))
+            .map(|path| path.display().to_string())
+            .collect();
+        changed.sort();
+        log::error!(
+            "The {} hook changed tracked files during a dry-run: {}",
+            name,
+            changed.join(", ")
+        );
+        return Err(101.into());
+    }
+    Ok(success)
+}
+
+fn run_workspace_hook(
+    name: &str,
+    hook: &config::Command,
+    ws_config: &config::Config,
+    ws_meta: &cargo_metadata::Metadata,
+    pkgs: &[plan::PackageRelease],
+    dry_run: bool,
+) -> Result<bool, ProcessError> {
+    let template = Template {
+        date: Some(NOW.as_str()),
+        delimiters: Some(ws_config.template_delimiters()),
+        ..Default::default()
+    };
+    let hook = hook.args().into_iter().map(|arg| template.render(arg));
+    log::debug!("Calling {} hook: {:?}", name, hook);
+    let crates = pkgs
+        .iter()
+        .map(|p| p.meta.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let file_envs = hook_env_file(ws_config.hook_env_file(), ws_meta, &template)?;
+    let mut envs = maplit::btreemap! {
+        OsStr::new("DRY_RUN") => OsStr::new(if dry_run { "true" } else { "false" }),
+        OsStr::new("RELEASE_CRATES") => OsStr::new(crates.as_str()),
+        OsStr::new("WORKSPACE_ROOT") => ws_meta.workspace_root.as_os_str(),
+    };
+    let plan_json;
+    if ws_config.hook_plan_env() {
+        plan_json = plan::to_json(pkgs).to_string();
+        envs.insert(OsStr::new("RELEASE_PLAN_JSON"), OsStr::new(&plan_json));
+    }
+    for (key, value) in &file_envs {
+        envs.entry(OsStr::new(key)).or_insert(OsStr::new(value));
+    }
+    // we use dry_run environmental variable to run the script
+    // so here we set dry_run=false and always execute the command.
+    let success = cmd::call_with_env(
+        hook,
+        envs,
+        ws_meta.workspace_root.as_std_path(),
+        ws_config.hook_timeout(),
+        false,
+    )?;
+    Ok(success)
+}
+
+/// Load the `hook-env-file`, relative to the workspace root, rendering each value
+fn hook_env_file(
+    path: Option<&Path>,
+    ws_meta: &cargo_metadata::Metadata,
+    template: &Template,
+) -> Result<Vec<(String, String)>, FatalError> {
+    let path = match path {
+        Some(path) => ws_meta.workspace_root.as_std_path().join(path),
+        None => return Ok(Vec::new()),
+    };
+    let envs = cmd::read_env_file(&path)?
+        .into_iter()
+        .map(|(key, value)| {
+            let value = template.render(&value);
+            (key, value)
+        })
+        .collect();
+    Ok(envs)
+}
+
+/// Prompt for which packages to release and, with `ask_level`, how to bump them
+///
+/// Without `ask_level`, the packages were already bumped by the LEVEL or VERSION passed in.
+fn select_packages(
+    pkgs: &mut indexmap::IndexMap<cargo_metadata::PackageId, plan::PackageRelease>,
+    ask_level: bool,
+    metadata: Option<&str>,
+) -> Result<(), ProcessError> {
+    let candidates: Vec<_> = pkgs
+        .values()
+        .filter(|p| p.config.release())
+        .map(|p| p.meta.id.clone())
+        .collect();
+    if candidates.is_empty() {
+        return Ok(());
+    }
+
+    let items: Vec<_> = candidates
+        .iter()
+        .map(|id| {
+            let pkg = &pkgs[id];
+            let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+            format!("{} {}", pkg.meta.name, version.full_version_string)
+        })
+        .collect();
+    let selected = crate::ops::shell::select("Select packages to release", &items);
+
+    let level_or_version = loop {
+        if !ask_level {
+            break None;
+        }
+        let input = crate::ops::shell::input("Bump by LEVEL or set the VERSION [keep] ");
+        if input.is_empty() {
+            break None;
+        }
+        match input.parse::<version::TargetVersion>() {
+            Ok(level_or_version) => break Some(level_or_version),
+            Err(err) => log::error!("Invalid LEVEL|VERSION `{}`: {}", input, err),
+        }
+    };
+
+    for (i, id) in candidates.iter().enumerate() {
+        let pkg = &mut pkgs[id];
+        if selected.contains(&i) {
+            if let Some(level_or_version) = level_or_version.as_ref() {
+                pkg.bump(level_or_version, metadata)?;
+            }
+        } else {
+            log::debug!("Disabled by user, skipping {}", pkg.meta.name);
+            pkg.config.release = Some(false);
+            pkg.planned_version = None;
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PublishTag {
+    Publish,
+    Tag,
+    /// Push only the tags, ahead of STEP 7, so CI watching for them can publish
+    PushTags,
+}
+
+fn publish_tag_order(tag_before_publish: bool) -> &'static [PublishTag] {
+    if tag_before_publish {
+        &[PublishTag::Tag, PublishTag::PushTags, PublishTag::Publish]
+    } else {
+        &[PublishTag::Publish, PublishTag::Tag]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod post_release_template {
+        use super::*;
+
+        fn pkg() -> plan::PackageRelease {
+            let mut pkg = crate::test_support::pkg("simple", "simple");
+            pkg.bump(
+                &version::TargetVersion::Relative(version::BumpLevel::Minor),
+                None,
+            )
+            .unwrap();
+            pkg.planned_tag = Some("v0.2.0".to_owned());
+            pkg
+        }
+
+        #[test]
+        fn per_package_default() {
+            let mut pkg = pkg();
+            pkg.config.consolidate_commits = Some(false);
+            let next_version =
+                version::Version::from(semver::Version::parse("0.2.1-alpha.0").unwrap());
+            let template = post_release_template(&pkg, &next_version);
+            assert_eq!(
+                template.render(&pkg.config.post_release_commit_message()),
+                "chore: Start development of 0.2.1-alpha.0"
+            );
+        }
+
+        #[test]
+        fn per_package_custom() {
+            let mut pkg = pkg();
+            pkg.config.post_release_commit_message = Some(
+                "chore({{crate_name}}): start {{next_version}} after {{version}} ({{prev_version}}, {{tag_name}})"
+                    .to_owned(),
+            );
+            let next_version =
+                version::Version::from(semver::Version::parse("0.2.1-alpha.0").unwrap());
+            let template = post_release_template(&pkg, &next_version);
+            assert_eq!(
+                template.render(&pkg.config.post_release_commit_message()),
+                "chore(simple): start 0.2.1-alpha.0 after 0.2.0 (0.1.0, v0.2.0)"
+            );
+        }
+    }
+
+    mod squash_dev_versions {
+        use super::*;
+
+        fn ws_config() -> config::Config {
+            config::Config {
+                squash_dev_version: Some(true),
+                ..Default::default()
+            }
+        }
+
+        fn pkgs(consolidate_commits: bool) -> Vec<plan::PackageRelease> {
+            crate::test_support::pkgs("pure_ws")
+                .into_iter()
+                .map(|mut pkg| {
+                    pkg.config.consolidate_commits = Some(consolidate_commits);
+                    pkg.post_version =
+                        Some(semver::Version::parse("0.1.1-alpha.0").unwrap().into());
+                    pkg
+                })
+                .collect()
+        }
+
+        #[test]
+        fn consolidated() {
+            assert!(squash_dev_versions(&ws_config(), &pkgs(true), true, false));
+        }
+
+        #[test]
+        fn separate_commits() {
+            assert!(!squash_dev_versions(
+                &ws_config(),
+                &pkgs(false),
+                true,
+                false
+            ));
+        }
+
+        #[test]
+        fn single_separate_commit() {
+            let pkgs = &pkgs(false)[..1];
+            assert!(squash_dev_versions(&ws_config(), pkgs, true, false));
+        }
+
+        #[test]
+        fn no_release_commit() {
+            assert!(!squash_dev_versions(
+                &ws_config(),
+                &pkgs(true),
+                false,
+                false
+            ));
+        }
+
+        #[test]
+        fn no_dev_versions() {
+            let mut pkgs = pkgs(true);
+            for pkg in &mut pkgs {
+                pkg.post_version = None;
+            }
+            assert!(!squash_dev_versions(&ws_config(), &pkgs, true, false));
+        }
+
+        #[test]
+        fn disabled() {
+            assert!(!squash_dev_versions(
+                &Default::default(),
+                &pkgs(true),
+                true,
+                false
+            ));
+            assert!(!squash_dev_versions(&ws_config(), &pkgs(true), true, true));
+            let ws_config = config::Config {
+                dev_version_commit: Some(false),
+                ..ws_config()
             };
-            if !git::commit_all(
-                ws_meta.workspace_root.as_std_path(),
-                &shared_commit_msg,
-                ws_config.sign_commit(),
-                dry_run,
-            )? {
-                // commit failed, abort release
-                return Err(101.into());
+            assert!(!squash_dev_versions(&ws_config, &pkgs(true), true, false));
+        }
+
+        #[test]
+        fn listed() {
+            let amend = "amend the release commit".to_owned();
+            let steps = list_steps(&ws_config(), &pkgs(true), None, true);
+            assert!(steps.contains(&amend), "{:?}", steps);
+            let steps = list_steps(&ws_config(), &pkgs(false), None, true);
+            assert!(!steps.contains(&amend), "{:?}", steps);
+            let commits: Vec<_> = steps.iter().filter(|s| s.starts_with("commit")).collect();
+            assert_eq!(commits, ["commit (a, b, c)", "commit (a, b, c)"]);
+            let steps = list_steps(&ws_config(), &pkgs(true), Some(ResumeFrom::Publish), true);
+            assert!(!steps.contains(&amend), "{:?}", steps);
+        }
+    }
+
+    mod unchanged_by_hook {
+        use super::*;
+
+        /// A repo with `file.txt` committed
+        fn init(temp: &assert_fs::TempDir) -> std::path::PathBuf {
+            let root = temp.path().canonicalize().unwrap();
+            std::fs::write(root.join("file.txt"), "one\n").unwrap();
+            crate::test_support::init_repo(&root);
+            crate::test_support::git(&root, &["add", "."]);
+            crate::test_support::git(&root, &["commit", "-m", "Initial"]);
+            root
+        }
+
+        #[test]
+        fn strict_untouched() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init(&temp);
+            assert!(unchanged_by_hook(true, &root, "pre-release", || Ok(true)).unwrap());
+            assert!(!unchanged_by_hook(true, &root, "pre-release", || Ok(false)).unwrap());
+        }
+
+        #[test]
+        fn strict_edits_tracked() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init(&temp);
+            let edited = unchanged_by_hook(true, &root, "pre-release", || {
+                std::fs::write(root.join("file.txt"), "two\n").unwrap();
+                Ok(true)
+            });
+            assert!(edited.is_err());
+        }
+
+        #[test]
+        fn strict_edits_dirty() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init(&temp);
+            std::fs::write(root.join("file.txt"), "two\n").unwrap();
+            let edited = unchanged_by_hook(true, &root, "pre-release", || {
+                std::fs::write(root.join("file.txt"), "three\n").unwrap();
+                Ok(true)
+            });
+            assert!(edited.is_err());
+        }
+
+        #[test]
+        fn strict_adds_untracked() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init(&temp);
+            let added = unchanged_by_hook(true, &root, "pre-release", || {
+                std::fs::write(root.join("new.txt"), "").unwrap();
+                Ok(true)
+            });
+            assert!(added.unwrap());
+        }
+
+        #[test]
+        fn not_strict() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let root = init(&temp);
+            let edited = unchanged_by_hook(false, &root, "pre-release", || {
+                std::fs::write(root.join("file.txt"), "two\n").unwrap();
+                Ok(true)
+            });
+            assert!(edited.unwrap());
+        }
+    }
+
+    mod write_preview {
+        use super::*;
+
+        #[test]
+        fn bumped_copy() {
+            use assert_fs::prelude::*;
+
+            let src = assert_fs::TempDir::new().unwrap();
+            src.copy_from("tests/fixtures/mixed_ws", &["**"]).unwrap();
+            #[cfg(unix)]
+            {
+                std::os::unix::fs::symlink(".", src.path().join("a/loop")).unwrap();
+                std::os::unix::fs::symlink("missing", src.path().join("dangling")).unwrap();
+            }
+            let ws_meta = cargo_metadata::MetadataCommand::new()
+                .manifest_path(src.path().join("Cargo.toml"))
+                .exec()
+                .unwrap();
+            let mut pkgs: Vec<_> = plan::load(&Default::default(), &ws_meta)
+                .unwrap()
+                .into_iter()
+                .map(|(_, pkg)| pkg)
+                .collect();
+            for pkg in &mut pkgs {
+                if pkg.meta.name == "a" {
+                    pkg.planned_version = Some(semver::Version::parse("0.2.0").unwrap().into());
+                }
+            }
+
+            let dest = assert_fs::TempDir::new().unwrap();
+            write_preview(
+                dest.path(),
+                &ws_meta,
+                &Default::default(),
+                &pkgs,
+                &Default::default(),
+            )
+            .unwrap();
+
+            let manifest = std::fs::read_to_string(dest.path().join("a/Cargo.toml")).unwrap();
+            assert!(manifest.contains("version = \"0.2.0\""), "{}", manifest);
+            let manifest = std::fs::read_to_string(src.path().join("a/Cargo.toml")).unwrap();
+            assert!(manifest.contains("version = \"0.1.0\""), "{}", manifest);
+            #[cfg(unix)]
+            {
+                assert_eq!(
+                    std::fs::read_link(dest.path().join("a/loop")).unwrap(),
+                    Path::new(".")
+                );
+                assert_eq!(
+                    std::fs::read_link(dest.path().join("dangling")).unwrap(),
+                    Path::new("missing")
+                );
+            }
+        }
+    }
+
+    mod commit_order {
+        use super::*;
+
+        /// `mixed_ws` with only `b` sharing the release commit
+        fn pkgs() -> Vec<plan::PackageRelease> {
+            planned(crate::test_support::pkgs("mixed_ws"))
+        }
+
+        fn planned(pkgs: Vec<plan::PackageRelease>) -> Vec<plan::PackageRelease> {
+            let mut pkgs: Vec<_> = pkgs
+                .into_iter()
+                .map(|mut pkg| {
+                    pkg.config.consolidate_commits = Some(pkg.meta.name == "b");
+                    pkg.config.dev_version = Some(true);
+                    pkg
+                })
+                .collect();
+            pkgs.sort_by(|a, b| a.meta.name.cmp(&b.meta.name));
+            for pkg in &mut pkgs {
+                pkg.plan().unwrap();
+            }
+            pkgs
+        }
+
+        #[test]
+        fn separate_first() {
+            let pkgs = pkgs();
+            let order: Vec<_> = commit_order(&pkgs).map(|p| p.meta.name.as_str()).collect();
+            assert_eq!(order, ["a", "c", "b"]);
+        }
+
+        #[test]
+        fn listed_commits() {
+            let pkgs = pkgs();
+            let commits: Vec<_> = list_steps(&config::Config::default(), &pkgs, None, true)
+                .into_iter()
+                .filter(|step| step.starts_with("commit") || step.starts_with("publish"))
+                .collect();
+            assert_eq!(
+                commits,
+                [
+                    "commit (a, c)",
+                    "commit",
+                    "publish (a, b, c)",
+                    "commit (a, c)",
+                    "commit",
+                ]
+            );
+        }
+
+        #[test]
+        fn created_commits() {
+            let (temp, ws_meta) = crate::test_support::fixture_repo("mixed_ws");
+            let pkgs = plan::load(&Default::default(), &ws_meta)
+                .unwrap()
+                .into_iter()
+                .map(|(_, mut pkg)| {
+                    pkg.planned_version = Some(semver::Version::parse("0.2.0").unwrap().into());
+                    pkg
+                })
+                .collect();
+            let pkgs = planned(pkgs);
+
+            let ws_config = config::Config {
+                consolidate_commits: Some(true),
+                ..Default::default()
+            };
+            let committed =
+                commit_release(&ws_meta, &ws_config, &pkgs, &Default::default(), None).unwrap();
+            assert!(committed);
+
+            assert_eq!(
+                crate::test_support::log(temp.path(), "HEAD"),
+                [
+                    "Initial",
+                    "chore: Release a version 0.2.0",
+                    "chore: Release c version 0.2.0",
+                    "chore: Release",
+                ]
+            );
+        }
+    }
+
+    mod commit_dev_versions {
+        use super::*;
+
+        /// Release the packages of `ws_meta` as 0.2.0, tagging them, and start 0.2.1
+        fn release(
+            ws_meta: &cargo_metadata::Metadata,
+            ws_config: &config::Config,
+        ) -> Vec<plan::PackageRelease> {
+            let mut pkgs: Vec<_> = plan::load(&Default::default(), ws_meta)
+                .unwrap()
+                .into_iter()
+                .map(|(_, mut pkg)| {
+                    pkg.config.consolidate_commits = ws_config.consolidate_commits;
+                    pkg.config.dev_version = Some(true);
+                    pkg.planned_version = Some(semver::Version::parse("0.2.0").unwrap().into());
+                    pkg.plan().unwrap();
+                    pkg
+                })
+                .collect();
+            pkgs.sort_by(|a, b| a.meta.name.cmp(&b.meta.name));
+
+            let opts = ReleaseOptions::default();
+            let committed = commit_release(ws_meta, ws_config, &pkgs, &opts, None).unwrap();
+            let root = ws_meta.workspace_root.as_std_path();
+            for pkg in &pkgs {
+                let tag = pkg.planned_tag.as_deref().unwrap();
+                assert!(git::tag(root, tag, "", false, false).unwrap());
+            }
+            commit_dev_versions(ws_meta, ws_config, &pkgs, &opts, None, committed).unwrap();
+            pkgs
+        }
+
+        #[test]
+        fn squashed() {
+            let (temp, ws_meta) = crate::test_support::fixture_repo("simple");
+            let ws_config = config::Config {
+                squash_dev_version: Some(true),
+                ..Default::default()
+            };
+            let pkgs = release(&ws_meta, &ws_config);
+            let tag = pkgs[0].planned_tag.as_deref().unwrap();
+            let root = temp.path();
+
+            // One commit, with the development version, on the branch
+            assert_eq!(
+                crate::test_support::log(root, "HEAD"),
+                ["Initial", "chore: Release simple version 0.2.0"]
+            );
+            let manifest = git::file_at(root, "HEAD", Path::new("Cargo.toml"))
+                .unwrap()
+                .unwrap();
+            assert!(
+                manifest.contains("version = \"0.2.1-alpha.0\""),
+                "{}",
+                manifest
+            );
+            assert!(!git::has_changes_to_commit(root).unwrap());
+
+            // The tag is on its sibling, with the released version
+            assert_ne!(
+                git::rev_id(root, tag).unwrap(),
+                Some(git::head_id(root).unwrap())
+            );
+            assert_eq!(
+                git::rev_id(root, &format!("{}~", tag)).unwrap(),
+                git::rev_id(root, "HEAD~").unwrap()
+            );
+            let manifest = git::file_at(root, tag, Path::new("Cargo.toml"))
+                .unwrap()
+                .unwrap();
+            assert!(manifest.contains("version = \"0.2.0\""), "{}", manifest);
+        }
+
+        #[test]
+        fn several_release_commits() {
+            let (temp, ws_meta) = crate::test_support::fixture_repo("mixed_ws");
+            let ws_config = config::Config {
+                consolidate_commits: Some(false),
+                squash_dev_version: Some(true),
+                ..Default::default()
+            };
+            let pkgs = release(&ws_meta, &ws_config);
+            let root = temp.path();
+
+            assert_eq!(
+                crate::test_support::log(root, "HEAD"),
+                [
+                    "Initial",
+                    "chore: Release a version 0.2.0",
+                    "chore: Release b version 0.2.0",
+                    "chore: Release c version 0.2.0",
+                    "chore: Start development of 0.2.1-alpha.0",
+                    "chore: Start development of 0.2.1-alpha.0",
+                    "chore: Start development of 0.2.1-alpha.0",
+                ]
+            );
+            // The tags stay on the branch, at the last release commit
+            for pkg in &pkgs {
+                let tag = pkg.planned_tag.as_deref().unwrap();
+                assert_eq!(
+                    git::rev_id(root, tag).unwrap(),
+                    git::rev_id(root, "HEAD~3").unwrap()
+                );
+            }
+        }
+    }
+
+    mod copy_dir {
+        use super::*;
+
+        #[test]
+        fn skips() {
+            let src = assert_fs::TempDir::new().unwrap();
+            std::fs::create_dir_all(src.path().join("a/b")).unwrap();
+            std::fs::write(src.path().join("a/b/c.txt"), "c").unwrap();
+            std::fs::create_dir_all(src.path().join("target")).unwrap();
+            std::fs::write(src.path().join("target/t.txt"), "t").unwrap();
+            let dest = assert_fs::TempDir::new().unwrap();
+
+            copy_dir(src.path(), dest.path(), &[src.path().join("target")]).unwrap();
+
+            assert_eq!(
+                std::fs::read_to_string(dest.path().join("a/b/c.txt")).unwrap(),
+                "c"
+            );
+            assert!(!dest.path().join("target").exists());
+        }
+    }
+
+    mod resume_from {
+        use super::*;
+
+        #[test]
+        fn publish_first() {
+            assert!(ResumeFrom::Publish.runs(ResumeFrom::Tag, false));
+            assert!(!ResumeFrom::Tag.runs(ResumeFrom::Publish, false));
+            assert!(ResumeFrom::Tag.runs(ResumeFrom::Push, false));
+            assert!(!ResumeFrom::Push.runs(ResumeFrom::Tag, false));
+        }
+
+        #[test]
+        fn tag_first() {
+            assert!(!ResumeFrom::Publish.runs(ResumeFrom::Tag, true));
+            assert!(ResumeFrom::Tag.runs(ResumeFrom::Publish, true));
+            assert!(ResumeFrom::Publish.runs(ResumeFrom::Push, true));
+            assert!(!ResumeFrom::Push.runs(ResumeFrom::Publish, true));
+        }
+
+        /// `simple`, committed at `version` in a repo tagged `v0.2.0`
+        fn pkg(temp: &assert_fs::TempDir, version: &str) -> plan::PackageRelease {
+            use assert_fs::prelude::*;
+
+            temp.copy_from("tests/fixtures/simple", &["**"]).unwrap();
+            let manifest_path = temp.path().join("Cargo.toml");
+            cargo::set_package_version(&manifest_path, version, false, false).unwrap();
+            crate::test_support::init_repo(temp.path());
+            crate::test_support::git(temp.path(), &["add", "."]);
+            crate::test_support::git(temp.path(), &["commit", "-m", "Release"]);
+            crate::test_support::git(temp.path(), &["tag", "v0.2.0"]);
+
+            let meta = cargo_metadata::MetadataCommand::new()
+                .manifest_path(&manifest_path)
+                .exec()
+                .unwrap();
+            let (_, mut pkg) = plan::load(&Default::default(), &meta)
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap();
+            pkg.config.dev_version = Some(true);
+            pkg
+        }
+
+        #[test]
+        fn already_bumped() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut pkg = pkg(&temp, "0.2.0");
+            pkg.bump(
+                &version::TargetVersion::Relative(version::BumpLevel::Minor),
+                None,
+            )
+            .unwrap();
+            pkg.plan().unwrap();
+            assert_eq!(pkg.planned_tag.as_deref(), Some("v0.3.0"));
+
+            let mut pkgs = [pkg];
+            resume(&mut pkgs, ResumeFrom::Publish).unwrap();
+            let pkg = &pkgs[0];
+            assert!(pkg.planned_version.is_none());
+            assert_eq!(pkg.initial_version.full_version_string, "0.2.0");
+            assert_eq!(pkg.planned_tag.as_deref(), Some("v0.2.0"));
+            assert_eq!(
+                pkg.post_version.as_ref().unwrap().full_version_string,
+                "0.2.1-alpha.0"
+            );
+        }
+
+        #[test]
+        fn dev_version_pushes_prior_tag() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut pkg = pkg(&temp, "0.2.1-alpha.0");
+            pkg.plan().unwrap();
+            assert_eq!(pkg.prior_tag.as_deref(), Some("v0.2.0"));
+
+            let mut pkgs = [pkg];
+            resume(&mut pkgs, ResumeFrom::Push).unwrap();
+            let pkg = &pkgs[0];
+            assert!(pkg.planned_version.is_none());
+            assert_eq!(pkg.planned_tag.as_deref(), Some("v0.2.0"));
+            assert!(pkg.post_version.is_none());
+        }
+
+        #[test]
+        fn dev_version_before_push() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let mut pkg = pkg(&temp, "0.2.1-alpha.0");
+            pkg.plan().unwrap();
+
+            let mut pkgs = [pkg];
+            assert!(resume(&mut pkgs, ResumeFrom::Publish).is_err());
+            assert!(resume(&mut pkgs, ResumeFrom::Tag).is_err());
+        }
+
+        #[test]
+        fn resumed_step_runs() {
+            for step in [ResumeFrom::Publish, ResumeFrom::Tag, ResumeFrom::Push] {
+                assert!(step.runs(step, false));
+                assert!(step.runs(step, true));
+            }
+        }
+    }
+
+    mod publish_tag_order {
+        use super::*;
+
+        #[test]
+        fn publish_first() {
+            assert_eq!(
+                publish_tag_order(false),
+                [PublishTag::Publish, PublishTag::Tag]
+            );
+        }
+
+        #[test]
+        fn tag_first() {
+            assert_eq!(
+                publish_tag_order(true),
+                [PublishTag::Tag, PublishTag::PushTags, PublishTag::Publish]
+            );
+        }
+
+        fn listed(tag_before_publish: bool, resume_from: Option<ResumeFrom>) -> Vec<String> {
+            let mut pkgs = crate::test_support::pkgs("simple");
+            for pkg in &mut pkgs {
+                pkg.plan().unwrap();
             }
+            let ws_config = config::Config {
+                tag_before_publish: Some(tag_before_publish),
+                ..Default::default()
+            };
+            list_steps(&ws_config, &pkgs, resume_from, true)
+                .into_iter()
+                .filter(|step| !step.starts_with("commit") && !step.starts_with("bump"))
+                .collect()
         }
 
-        // STEP 7: git push
-        super::push::push(&ws_config, &ws_meta, &pkgs, dry_run)?;
+        #[test]
+        fn tags_pushed_before_publish() {
+            assert_eq!(
+                listed(true, None),
+                [
+                    "tag (simple-v0.1.0)",
+                    "push tags to origin (simple-v0.1.0)",
+                    "publish (simple)",
+                    "push to origin",
+                ]
+            );
+            assert_eq!(
+                listed(true, Some(ResumeFrom::Publish)),
+                ["publish (simple)", "push to origin"]
+            );
+        }
 
-        super::finish(failed, dry_run)
+        #[test]
+        fn tags_pushed_with_branch() {
+            assert_eq!(
+                listed(false, None),
+                ["publish (simple)", "tag (simple-v0.1.0)", "push to origin"]
+            );
+        }
+    }
+
+    mod include_packages {
+        use super::*;
+
+        fn meta() -> cargo_metadata::Metadata {
+            crate::test_support::metadata("mixed_ws")
+        }
+
+        fn names(pkgs: &[&cargo_metadata::Package]) -> Vec<String> {
+            let mut names: Vec<_> = pkgs.iter().map(|p| p.name.clone()).collect();
+            names.sort();
+            names
+        }
+
+        #[test]
+        fn include_then_exclude() {
+            let meta = meta();
+            let mut workspace = clap_cargo::Workspace::default();
+            workspace.exclude = vec!["b".to_owned()];
+            let include = [globset::Glob::new("[ab]").unwrap()];
+            let (selected, excluded) = include_packages(&workspace, &include, &meta).unwrap();
+            assert_eq!(names(&selected), ["a"]);
+            assert_eq!(names(&excluded), ["b", "c"]);
+        }
+
+        #[test]
+        fn no_match() {
+            let meta = meta();
+            let include = [
+                globset::Glob::new("a").unwrap(),
+                globset::Glob::new("api-*").unwrap(),
+            ];
+            let err = include_packages(&Default::default(), &include, &meta).unwrap_err();
+            assert!(matches!(err, FatalError::NoIncludeMatch(pattern) if pattern == "api-*"));
+        }
     }
 }
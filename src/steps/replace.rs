@@ -1,4 +1,3 @@
-use crate::error::FatalError;
 use crate::error::ProcessError;
 use crate::ops::git;
 use crate::ops::replace::{do_file_replacements, Template, NOW};
@@ -25,10 +24,18 @@ pub struct ReplaceStep {
     #[arg(long, value_delimiter = ',')]
     allow_branch: Option<Vec<String>>,
 
+    /// Don't check whether the branch is behind its remote
+    #[arg(long)]
+    allow_behind: bool,
+
     /// Actually perform a release. Dry-run mode is the default
     #[arg(short = 'x', long)]
     execute: bool,
 
+    /// Only show what would be done, the default.  Conflicts with `--execute`
+    #[arg(long, conflicts_with = "execute")]
+    dry_run: bool,
+
     /// Skip release confirmation and version preview
     #[arg(long)]
     no_confirm: bool,
@@ -38,14 +45,8 @@ impl ReplaceStep {
     pub fn run(&self) -> Result<(), ProcessError> {
         git::git_version()?;
 
-        let ws_meta = self
-            .manifest
-            .metadata()
-            // When evaluating dependency ordering, we need to consider optional dependencies
-            .features(cargo_metadata::CargoOpt::AllFeatures)
-            .exec()
-            .map_err(FatalError::from)?;
         let config = self.to_config();
+        let ws_meta = super::load_metadata(&self.manifest, &config)?;
         let ws_config = crate::config::load_workspace_config(&config, &ws_meta)?;
         let mut pkgs = plan::load(&config, &ws_meta)?;
 
@@ -73,12 +74,13 @@ impl ReplaceStep {
             return Err(2.into());
         }
 
-        let dry_run = !self.execute;
+        let dry_run = self.dry_run || !self.execute;
         let mut failed = false;
 
         // STEP 0: Help the user make the right decisions.
         failed |= !super::verify_git_is_clean(
             ws_meta.workspace_root.as_std_path(),
+            &ws_config,
             dry_run,
             log::Level::Warn,
         )?;
@@ -92,12 +94,14 @@ impl ReplaceStep {
             log::Level::Warn,
         )?;
 
-        failed |= !super::verify_if_behind(
-            ws_meta.workspace_root.as_std_path(),
-            &ws_config,
-            dry_run,
-            log::Level::Warn,
-        )?;
+        if !self.allow_behind {
+            failed |= !super::verify_if_behind(
+                ws_meta.workspace_root.as_std_path(),
+                &ws_config,
+                dry_run,
+                super::if_behind_level(&ws_config),
+            )?;
+        }
 
         // STEP 1: Release Confirmation
         super::confirm("Bump", &pkgs, self.no_confirm, dry_run)?;
@@ -105,7 +109,7 @@ impl ReplaceStep {
         // STEP 2: update current version, save and commit
         for pkg in &pkgs {
             let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
-            if !pkg.config.pre_release_replacements().is_empty() {
+            if !pkg.pre_release_replacements().is_empty() {
                 let cwd = &pkg.package_root;
                 let crate_name = pkg.meta.name.as_str();
                 let prev_version_var = pkg.initial_version.bare_version_string.as_str();
@@ -120,19 +124,22 @@ impl ReplaceStep {
                     metadata: Some(metadata_var),
                     crate_name: Some(crate_name),
                     date: Some(NOW.as_str()),
-                    tag_name: pkg.planned_tag.as_deref(),
+                    tag_name: Some(pkg.planned_tag.as_deref().unwrap_or_default()),
+                    delimiters: Some(pkg.config.template_delimiters()),
                     ..Default::default()
                 };
                 let prerelease = version.is_prerelease();
                 let noisy = true;
-                do_file_replacements(
-                    pkg.config.pre_release_replacements(),
+                if !do_file_replacements(
+                    &pkg.pre_release_replacements(),
                     &template,
                     cwd,
                     prerelease,
                     noisy,
                     dry_run,
-                )?;
+                )? {
+                    return Err(101.into());
+                }
             }
         }
 
@@ -1,6 +1,5 @@
 use std::collections::HashSet;
 
-use crate::error::FatalError;
 use crate::error::ProcessError;
 use crate::ops::git;
 use crate::ops::replace::Template;
@@ -30,10 +29,18 @@ pub struct TagStep {
     #[arg(long, value_delimiter = ',')]
     allow_branch: Option<Vec<String>>,
 
+    /// Don't check whether the branch is behind its remote
+    #[arg(long)]
+    allow_behind: bool,
+
     /// Actually perform a release. Dry-run mode is the default
     #[arg(short = 'x', long)]
     execute: bool,
 
+    /// Only show what would be done, the default.  Conflicts with `--execute`
+    #[arg(long, conflicts_with = "execute")]
+    dry_run: bool,
+
     /// Skip release confirmation and version preview
     #[arg(long)]
     no_confirm: bool,
@@ -46,14 +53,8 @@ impl TagStep {
     pub fn run(&self) -> Result<(), ProcessError> {
         git::git_version()?;
 
-        let ws_meta = self
-            .manifest
-            .metadata()
-            // When evaluating dependency ordering, we need to consider optional dependencies
-            .features(cargo_metadata::CargoOpt::AllFeatures)
-            .exec()
-            .map_err(FatalError::from)?;
         let config = self.to_config();
+        let ws_meta = super::load_metadata(&self.manifest, &config)?;
         let ws_config = crate::config::load_workspace_config(&config, &ws_meta)?;
         let mut pkgs = plan::load(&config, &ws_meta)?;
 
@@ -99,12 +100,13 @@ impl TagStep {
             return Err(2.into());
         }
 
-        let dry_run = !self.execute;
+        let dry_run = self.dry_run || !self.execute;
         let mut failed = false;
 
         // STEP 0: Help the user make the right decisions.
         failed |= !super::verify_git_is_clean(
             ws_meta.workspace_root.as_std_path(),
+            &ws_config,
             dry_run,
             log::Level::Error,
         )?;
@@ -116,13 +118,25 @@ impl TagStep {
             log::Level::Error,
         )?;
 
-        failed |= !super::verify_if_behind(
-            ws_meta.workspace_root.as_std_path(),
-            &ws_config,
+        failed |= !super::verify_tags_unique(&pkgs, dry_run, log::Level::Error)?;
+
+        failed |= !super::verify_literal_tag_name(
+            &pkgs,
+            self.tag.literal_tag_name(ws_config.template_delimiters().0),
+            ws_config.template_delimiters(),
             dry_run,
-            log::Level::Warn,
+            log::Level::Error,
         )?;
 
+        if !self.allow_behind {
+            failed |= !super::verify_if_behind(
+                ws_meta.workspace_root.as_std_path(),
+                &ws_config,
+                dry_run,
+                super::if_behind_level(&ws_config),
+            )?;
+        }
+
         // STEP 1: Release Confirmation
         super::confirm("Tag", &pkgs, self.no_confirm, dry_run)?;
 
@@ -151,6 +165,27 @@ pub fn tag(pkgs: &[plan::PackageRelease], dry_run: bool) -> Result<(), ProcessEr
                 let cwd = &pkg.package_root;
                 let crate_name = pkg.meta.name.as_str();
 
+                if pkg.config.allow_existing_tag() && git::tag_exists(cwd, tag_name)? {
+                    if pkg.config.sign_tag() && !git::is_tag_signed(cwd, tag_name)? {
+                        log::error!(
+                            "Tag `{}` already exists but its signature can't be verified (for `{}`)",
+                            tag_name,
+                            crate_name
+                        );
+                        return Err(101.into());
+                    } else if git::tag_points_at_head(cwd, tag_name)? {
+                        log::debug!("Reusing existing git tag {}", tag_name);
+                        continue;
+                    } else {
+                        log::error!(
+                            "Tag `{}` already exists on a different commit (for `{}`)",
+                            tag_name,
+                            crate_name
+                        );
+                        return Err(101.into());
+                    }
+                }
+
                 let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
                 let prev_version_var = pkg.initial_version.bare_version_string.as_str();
                 let prev_metadata_var = pkg.initial_version.full_version.build.as_str();
@@ -164,9 +199,10 @@ pub fn tag(pkgs: &[plan::PackageRelease], dry_run: bool) -> Result<(), ProcessEr
                     crate_name: Some(crate_name),
                     tag_name: Some(tag_name),
                     date: Some(NOW.as_str()),
+                    delimiters: Some(pkg.config.template_delimiters()),
                     ..Default::default()
                 };
-                let tag_message = template.render(pkg.config.tag_message());
+                let tag_message = template.render(&pkg.config.tag_message());
 
                 log::debug!("Creating git tag {}", tag_name);
                 if !git::tag(cwd, tag_name, &tag_message, pkg.config.sign_tag(), dry_run)? {
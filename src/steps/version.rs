@@ -6,6 +6,7 @@ use crate::steps::plan;
 
 /// Bump crate versions
 #[derive(Debug, Clone, clap::Args)]
+#[command(group = clap::ArgGroup::new("target_version").args(["level_or_version", "bump", "set_version"]).required(true))]
 pub struct VersionStep {
     #[command(flatten)]
     manifest: clap_cargo::Manifest,
@@ -25,17 +26,38 @@ pub struct VersionStep {
     #[arg(long, value_delimiter = ',')]
     allow_branch: Option<Vec<String>>,
 
+    /// Don't check whether the branch is behind its remote
+    #[arg(long)]
+    allow_behind: bool,
+
     /// Actually perform a release. Dry-run mode is the default
     #[arg(short = 'x', long)]
     execute: bool,
 
+    /// Only show what would be done, the default.  Conflicts with `--execute`
+    #[arg(long, conflicts_with = "execute")]
+    dry_run: bool,
+
     /// Skip release confirmation and version preview
     #[arg(long)]
     no_confirm: bool,
 
     /// Either bump by LEVEL or set the VERSION for all selected packages
     #[arg(value_name = "LEVEL|VERSION", help_heading = "Version")]
-    level_or_version: crate::ops::version::TargetVersion,
+    level_or_version: Option<crate::ops::version::TargetVersion>,
+
+    /// Bump all selected packages by LEVEL
+    #[arg(long, value_name = "LEVEL", help_heading = "Version")]
+    bump: Option<crate::ops::version::BumpLevel>,
+
+    /// Set all selected packages to VERSION, or the lowest unpublished version matching a REQ
+    #[arg(
+        long,
+        value_name = "VERSION|REQ",
+        help_heading = "Version",
+        value_parser = crate::ops::version::TargetVersion::parse_set_version
+    )]
+    set_version: Option<crate::ops::version::TargetVersion>,
 
     /// Semver metadata
     #[arg(short, long, help_heading = "Version")]
@@ -44,30 +66,36 @@ pub struct VersionStep {
     /// The name of tag for the previous release.
     #[arg(long, help_heading = "Version")]
     prev_tag_name: Option<String>,
+
+    /// Show the full diff of manifest edits in dry-run
+    #[arg(long)]
+    verbose_diff: bool,
 }
 
 impl VersionStep {
     pub fn run(&self) -> Result<(), ProcessError> {
         git::git_version()?;
 
-        let ws_meta = self
-            .manifest
-            .metadata()
-            // When evaluating dependency ordering, we need to consider optional dependencies
-            .features(cargo_metadata::CargoOpt::AllFeatures)
-            .exec()
-            .map_err(FatalError::from)?;
         let config = self.to_config();
+        let ws_meta = super::load_metadata(&self.manifest, &config)?;
         let ws_config = crate::config::load_workspace_config(&config, &ws_meta)?;
         let mut pkgs = plan::load(&config, &ws_meta)?;
 
+        let level_or_version = self.target_version();
+        let published_index = if level_or_version.is_matching() {
+            Some(crate::ops::cargo::open_index(ws_config.index_freshness())?)
+        } else {
+            None
+        };
         for pkg in pkgs.values_mut() {
             if let Some(prev_tag) = self.prev_tag_name.as_ref() {
                 // Trust the user that the tag passed in is the latest tag for the workspace and that
                 // they don't care about any changes from before this tag.
                 pkg.set_prior_tag(prev_tag.to_owned());
             }
-            pkg.bump(&self.level_or_version, self.metadata.as_deref())?;
+            let level_or_version =
+                super::resolve_target_version(&level_or_version, pkg, published_index.as_ref())?;
+            pkg.bump(&level_or_version, self.metadata.as_deref())?;
         }
 
         let (_selected_pkgs, excluded_pkgs) = self.workspace.partition_packages(&ws_meta);
@@ -83,16 +111,15 @@ impl VersionStep {
 
             let crate_name = pkg.meta.name.as_str();
             if let Some(prior_tag_name) = &pkg.prior_tag {
-                if let Some((changed, lock_changed)) = changed_since(&ws_meta, pkg, prior_tag_name)
-                {
-                    if !changed.is_empty() {
+                if let Some(changes) = changed_since(&ws_meta, pkg, prior_tag_name) {
+                    if !changes.files.is_empty() {
                         log::warn!(
                             "Disabled by user, skipping {} which has files changed since {}: {:#?}",
                             crate_name,
                             prior_tag_name,
-                            changed
+                            changes.files
                         );
-                    } else if lock_changed {
+                    } else if changes.lock_changed {
                         log::warn!(
                         "Disabled by user, skipping {} despite lock file being changed since {}",
                         crate_name,
@@ -129,12 +156,13 @@ impl VersionStep {
             return Err(2.into());
         }
 
-        let dry_run = !self.execute;
+        let dry_run = self.dry_run || !self.execute;
         let mut failed = false;
 
         // STEP 0: Help the user make the right decisions.
         failed |= !super::verify_git_is_clean(
             ws_meta.workspace_root.as_std_path(),
+            &ws_config,
             dry_run,
             log::Level::Warn,
         )?;
@@ -148,12 +176,16 @@ impl VersionStep {
             log::Level::Warn,
         )?;
 
-        failed |= !super::verify_if_behind(
-            ws_meta.workspace_root.as_std_path(),
-            &ws_config,
-            dry_run,
-            log::Level::Warn,
-        )?;
+        if !self.allow_behind {
+            failed |= !super::verify_if_behind(
+                ws_meta.workspace_root.as_std_path(),
+                &ws_config,
+                dry_run,
+                super::if_behind_level(&ws_config),
+            )?;
+        }
+
+        failed |= !super::verify_version_edits(&ws_meta, &pkgs, dry_run, log::Level::Error)?;
 
         // STEP 1: Release Confirmation
         super::confirm("Bump", &pkgs, self.no_confirm, dry_run)?;
@@ -170,13 +202,14 @@ impl VersionStep {
                 crate::ops::cargo::set_package_version(
                     &pkg.manifest_path,
                     version.full_version_string.as_str(),
+                    self.verbose_diff,
                     dry_run,
                 )?;
-                update_dependent_versions(pkg, version, dry_run)?;
+                update_dependent_versions(pkg, version, self.verbose_diff, dry_run)?;
                 if dry_run {
                     log::debug!("Updating lock file");
                 } else {
-                    crate::ops::cargo::update_lock(&pkg.manifest_path)?;
+                    crate::ops::cargo::update_lock(&pkg.manifest_path, pkg.config.cargo_bin())?;
                 }
             }
         }
@@ -184,6 +217,14 @@ impl VersionStep {
         super::finish(failed, dry_run)
     }
 
+    fn target_version(&self) -> crate::ops::version::TargetVersion {
+        self.level_or_version
+            .clone()
+            .or_else(|| self.bump.map(crate::ops::version::TargetVersion::Relative))
+            .or_else(|| self.set_version.clone())
+            .expect("clap requires one of the version arguments")
+    }
+
     fn to_config(&self) -> crate::config::ConfigArgs {
         crate::config::ConfigArgs {
             custom_config: self.custom_config.clone(),
@@ -194,11 +235,31 @@ impl VersionStep {
     }
 }
 
-pub fn changed_since<'m>(
+/// Changes to a package since a prior release
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Changes {
+    /// The commit range that was examined, like `v1.0.0..HEAD`
+    pub range: String,
+    /// Files belonging to the package that changed within `range`, excluding `Cargo.lock`
+    pub files: Vec<std::path::PathBuf>,
+    /// Whether `Cargo.lock` changed in a way that affects the package
+    ///
+    /// Only packages with a `[[bin]]` are affected by the lock file.
+    pub lock_changed: bool,
+    /// Whether the manifest only changed in its dependencies' version requirements
+    ///
+    /// Only checked with `skip-dependency-bumps`, leaving the manifest out of `files`.
+    pub dependency_bump: bool,
+}
+
+/// Find what changed in `pkg` between `since_ref` and `HEAD`
+///
+/// Returns `None` if git can't diff against `since_ref`, like when the tag doesn't exist.
+pub fn changed_since(
     ws_meta: &cargo_metadata::Metadata,
-    pkg: &'m plan::PackageRelease,
+    pkg: &plan::PackageRelease,
     since_ref: &str,
-) -> Option<(Vec<std::path::PathBuf>, bool)> {
+) -> Option<Changes> {
     let lock_path = ws_meta.workspace_root.join("Cargo.lock");
     let changed_root = if pkg.bin {
         ws_meta.workspace_root.as_std_path()
@@ -212,6 +273,50 @@ pub fn changed_since<'m>(
         .filter(|p| pkg.package_content.contains(p))
         .collect();
 
+    let ignored = changed_since_ignore(&pkg.package_root, pkg.config.changed_since_ignore());
+    changed.retain(|path| {
+        let ignore = path
+            .strip_prefix(&pkg.package_root)
+            .map(|rel| ignored.matched_path_or_any_parents(rel, false).is_ignore())
+            .unwrap_or(false);
+        if ignore {
+            log::trace!(
+                "Ignoring change to {} per `changed-since-ignore`",
+                path.display()
+            );
+        }
+        !ignore
+    });
+
+    let mut dependency_bump = false;
+    if pkg.config.skip_dependency_bumps() {
+        if let Some(manifest_index) = changed.iter().position(|p| p == &pkg.manifest_path) {
+            let old = git::file_at(
+                &pkg.package_root,
+                since_ref,
+                std::path::Path::new("Cargo.toml"),
+            )
+            .ok()
+            .flatten();
+            let new = std::fs::read_to_string(&pkg.manifest_path).ok();
+            if let (Some(old), Some(new)) = (old, new) {
+                // A development version bump is also cargo-release's doing
+                if crate::ops::cargo::only_dependency_versions_changed(
+                    &old,
+                    &new,
+                    pkg.config.dev_version(),
+                ) {
+                    log::trace!(
+                        "Ignoring manifest change since {}; only dependency versions changed",
+                        since_ref
+                    );
+                    let _ = changed.swap_remove(manifest_index);
+                    dependency_bump = true;
+                }
+            }
+        }
+    }
+
     let mut lock_changed = false;
     if let Some(lock_index) =
         changed.iter().enumerate().find_map(
@@ -242,83 +347,130 @@ pub fn changed_since<'m>(
         }
     }
 
-    Some((changed, lock_changed))
+    Some(Changes {
+        range: format!("{}..HEAD", since_ref),
+        files: changed,
+        lock_changed,
+        dependency_bump,
+    })
+}
+
+fn changed_since_ignore(
+    root: &std::path::Path,
+    patterns: &[String],
+) -> ignore::gitignore::Gitignore {
+    let mut builder = ignore::gitignore::GitignoreBuilder::new(root);
+    for pattern in patterns {
+        if let Err(err) = builder.add_line(None, pattern) {
+            log::warn!(
+                "Skipping `changed-since-ignore` entry {:?}: {}",
+                pattern,
+                err
+            );
+        }
+    }
+    builder.build().unwrap_or_else(|err| {
+        log::warn!("Skipping `changed-since-ignore`: {}", err);
+        ignore::gitignore::Gitignore::empty()
+    })
+}
+
+/// What bumping a package changes about a dependent's requirement on it, per `dependent-version`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DependentEdit {
+    /// Leave the requirement as is
+    Keep,
+    /// Report that the requirement excludes the new version
+    Warn,
+    /// Report that the requirement excludes the new version and fail
+    Fail,
+    /// Rewrite the requirement, which excludes the new version
+    Fix(String),
+    /// Rewrite the requirement to the new version
+    Upgrade(String),
+}
+
+/// How bumping `pkg` to `version` changes each of its dependents' requirements
+pub fn dependent_edits<'p>(
+    pkg: &'p plan::PackageRelease,
+    version: &crate::ops::version::Version,
+) -> Result<Vec<(&'p plan::Dependency, DependentEdit)>, FatalError> {
+    let mut edits = Vec::new();
+    for dep in pkg.dependents.iter() {
+        let edit = match pkg.config.dependent_version() {
+            config::DependentVersion::Ignore => DependentEdit::Keep,
+            config::DependentVersion::Upgrade => {
+                crate::ops::version::set_requirement(&dep.req, &version.bare_version)?
+                    .map_or(DependentEdit::Keep, DependentEdit::Upgrade)
+            }
+            _ if dep.req.matches(&version.bare_version) => DependentEdit::Keep,
+            config::DependentVersion::Warn => DependentEdit::Warn,
+            config::DependentVersion::Error => DependentEdit::Fail,
+            config::DependentVersion::Fix => {
+                crate::ops::version::set_requirement(&dep.req, &version.bare_version)?
+                    .map_or(DependentEdit::Keep, DependentEdit::Fix)
+            }
+        };
+        edits.push((dep, edit));
+    }
+    Ok(edits)
+}
+
+/// The manifests bumping `pkg` to `version` edits: its own and those of the dependents that
+/// [`update_dependent_versions`] updates
+pub fn edited_manifests<'p>(
+    pkg: &'p plan::PackageRelease,
+    version: &crate::ops::version::Version,
+) -> Result<Vec<&'p std::path::Path>, FatalError> {
+    let mut manifests = vec![pkg.manifest_path.as_path()];
+    for (dep, edit) in dependent_edits(pkg, version)? {
+        if let DependentEdit::Fix(_) | DependentEdit::Upgrade(_) = edit {
+            manifests.push(dep.pkg.manifest_path.as_std_path());
+        }
+    }
+    Ok(manifests)
 }
 
 pub fn update_dependent_versions(
     pkg: &plan::PackageRelease,
     version: &crate::ops::version::Version,
+    noisy: bool,
     dry_run: bool,
 ) -> Result<(), FatalError> {
     let new_version_string = version.bare_version_string.as_str();
     let mut dependents_failed = false;
-    for dep in pkg.dependents.iter() {
-        match pkg.config.dependent_version() {
-            config::DependentVersion::Ignore => (),
-            config::DependentVersion::Warn => {
-                if !dep.req.matches(&version.bare_version) {
-                    log::warn!(
-                        "{}'s dependency on {} `{}` is incompatible with {}",
-                        dep.pkg.name,
-                        pkg.meta.name,
-                        dep.req,
-                        new_version_string
-                    );
-                }
-            }
-            config::DependentVersion::Error => {
-                if !dep.req.matches(&version.bare_version) {
-                    log::warn!(
-                        "{}'s dependency on {} `{}` is incompatible with {}",
-                        dep.pkg.name,
-                        pkg.meta.name,
-                        dep.req,
-                        new_version_string
-                    );
-                    dependents_failed = true;
-                }
-            }
-            config::DependentVersion::Fix => {
-                if !dep.req.matches(&version.bare_version) {
-                    let new_req =
-                        crate::ops::version::set_requirement(&dep.req, &version.bare_version)?;
-                    if let Some(new_req) = new_req {
-                        log::info!(
-                            "Fixing {}'s dependency on {} to `{}` (from `{}`)",
-                            dep.pkg.name,
-                            pkg.meta.name,
-                            new_req,
-                            dep.req
-                        );
-                        crate::ops::cargo::set_dependency_version(
-                            dep.pkg.manifest_path.as_std_path(),
-                            &pkg.meta.name,
-                            &new_req,
-                            dry_run,
-                        )?;
-                    }
-                }
-            }
-            config::DependentVersion::Upgrade => {
-                let new_req =
-                    crate::ops::version::set_requirement(&dep.req, &version.bare_version)?;
-                if let Some(new_req) = new_req {
-                    log::info!(
-                        "Upgrading {}'s dependency on {} to `{}` (from `{}`)",
-                        dep.pkg.name,
-                        pkg.meta.name,
-                        new_req,
-                        dep.req
-                    );
-                    crate::ops::cargo::set_dependency_version(
-                        dep.pkg.manifest_path.as_std_path(),
-                        &pkg.meta.name,
-                        &new_req,
-                        dry_run,
-                    )?;
-                }
+    for (dep, edit) in dependent_edits(pkg, version)? {
+        let (action, new_req) = match edit {
+            DependentEdit::Keep => continue,
+            DependentEdit::Warn | DependentEdit::Fail => {
+                log::warn!(
+                    "{}'s dependency on {} `{}` is incompatible with {}",
+                    dep.pkg.name,
+                    pkg.meta.name,
+                    dep.req,
+                    new_version_string
+                );
+                dependents_failed |= edit == DependentEdit::Fail;
+                continue;
             }
-        }
+            DependentEdit::Fix(new_req) => ("Fixing", new_req),
+            DependentEdit::Upgrade(new_req) => ("Upgrading", new_req),
+        };
+        log::info!(
+            "{} {}'s dependency on {} to `{}` (from `{}`)",
+            action,
+            dep.pkg.name,
+            pkg.meta.name,
+            new_req,
+            dep.req
+        );
+        crate::ops::cargo::set_dependency_version(
+            dep.pkg.manifest_path.as_std_path(),
+            &pkg.meta.name,
+            &new_req,
+            noisy,
+            dry_run,
+        )?;
     }
     if dependents_failed {
         Err(FatalError::DependencyVersionConflict)
@@ -326,3 +478,129 @@ pub fn update_dependent_versions(
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    mod changed_since_ignore {
+        use super::*;
+
+        #[test]
+        fn matches_files_and_dirs() {
+            let ignored = changed_since_ignore(
+                std::path::Path::new("/pkg"),
+                &["CHANGELOG.md".to_owned(), "docs/".to_owned()],
+            );
+            let is_ignored = |p: &str| ignored.matched_path_or_any_parents(p, false).is_ignore();
+            assert!(is_ignored("CHANGELOG.md"));
+            assert!(is_ignored("docs/guide.md"));
+            assert!(!is_ignored("src/lib.rs"));
+        }
+    }
+
+    mod edited_manifests {
+        use super::*;
+
+        /// `mixed_ws`'s `a`, which `b` depends on as `a = "0.1"`
+        fn pkg_a(dependent_version: config::DependentVersion) -> plan::PackageRelease {
+            let mut pkg = crate::test_support::pkg("mixed_ws", "a");
+            pkg.config.dependent_version = Some(dependent_version);
+            pkg.dependents[0].req = semver::VersionReq::parse("0.1").unwrap();
+            pkg
+        }
+
+        fn names(manifests: Vec<&std::path::Path>) -> Vec<String> {
+            manifests
+                .iter()
+                .map(|m| m.parent().unwrap().file_name().unwrap())
+                .map(|n| n.to_string_lossy().into_owned())
+                .collect()
+        }
+
+        #[test]
+        fn fix_incompatible() {
+            let pkg = pkg_a(config::DependentVersion::Fix);
+            let version = semver::Version::new(0, 2, 0).into();
+            assert_eq!(
+                names(edited_manifests(&pkg, &version).unwrap()),
+                ["a", "mixed_ws"]
+            );
+        }
+
+        #[test]
+        fn fix_compatible() {
+            let pkg = pkg_a(config::DependentVersion::Fix);
+            let version = semver::Version::new(0, 1, 1).into();
+            assert_eq!(names(edited_manifests(&pkg, &version).unwrap()), ["a"]);
+        }
+
+        #[test]
+        fn ignore() {
+            let pkg = pkg_a(config::DependentVersion::Ignore);
+            let version = semver::Version::new(0, 2, 0).into();
+            assert_eq!(names(edited_manifests(&pkg, &version).unwrap()), ["a"]);
+        }
+    }
+
+    mod dependent_edits {
+        use super::*;
+
+        fn edit(
+            dependent_version: config::DependentVersion,
+            version: (u64, u64, u64),
+        ) -> DependentEdit {
+            let mut pkg = crate::test_support::pkg("mixed_ws", "a");
+            pkg.config.dependent_version = Some(dependent_version);
+            pkg.dependents[0].req = semver::VersionReq::parse("0.1").unwrap();
+            let version = semver::Version::new(version.0, version.1, version.2).into();
+            let edits = dependent_edits(&pkg, &version).unwrap();
+            assert_eq!(edits.len(), 1);
+            edits.into_iter().next().unwrap().1
+        }
+
+        #[test]
+        fn compatible() {
+            assert_eq!(
+                edit(config::DependentVersion::Warn, (0, 1, 1)),
+                DependentEdit::Keep
+            );
+            assert_eq!(
+                edit(config::DependentVersion::Error, (0, 1, 1)),
+                DependentEdit::Keep
+            );
+            assert_eq!(
+                edit(config::DependentVersion::Fix, (0, 1, 1)),
+                DependentEdit::Keep
+            );
+            assert_eq!(
+                edit(config::DependentVersion::Upgrade, (0, 1, 1)),
+                DependentEdit::Keep
+            );
+        }
+
+        #[test]
+        fn incompatible() {
+            assert_eq!(
+                edit(config::DependentVersion::Ignore, (0, 2, 0)),
+                DependentEdit::Keep
+            );
+            assert_eq!(
+                edit(config::DependentVersion::Warn, (0, 2, 0)),
+                DependentEdit::Warn
+            );
+            assert_eq!(
+                edit(config::DependentVersion::Error, (0, 2, 0)),
+                DependentEdit::Fail
+            );
+            assert_eq!(
+                edit(config::DependentVersion::Fix, (0, 2, 0)),
+                DependentEdit::Fix("^0.2".to_owned())
+            );
+            assert_eq!(
+                edit(config::DependentVersion::Upgrade, (0, 2, 0)),
+                DependentEdit::Upgrade("^0.2".to_owned())
+            );
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use crate::error::FatalError;
+use crate::error::ProcessError;
+use crate::steps::plan;
+
+/// Yank a published version of the specified packages
+///
+/// Yanking doesn't delete the version; it only stops new dependents from resolving to it
+#[derive(Debug, Clone, clap::Args)]
+pub struct YankStep {
+    #[command(flatten)]
+    manifest: clap_cargo::Manifest,
+
+    #[command(flatten)]
+    workspace: clap_cargo::Workspace,
+
+    /// Custom config file
+    #[arg(short, long = "config")]
+    custom_config: Option<String>,
+
+    /// Ignore implicit configuration files.
+    #[arg(long)]
+    isolated: bool,
+
+    /// The published version to yank
+    #[arg(long, value_name = "VERSION")]
+    version: semver::Version,
+
+    /// Actually yank. Dry-run mode is the default
+    #[arg(short = 'x', long)]
+    execute: bool,
+
+    /// Only show what would be done, the default.  Conflicts with `--execute`
+    #[arg(long, conflicts_with = "execute")]
+    dry_run: bool,
+
+    /// Skip confirmation
+    #[arg(long)]
+    no_confirm: bool,
+}
+
+impl YankStep {
+    pub fn run(&self) -> Result<(), ProcessError> {
+        let config = self.to_config();
+        let ws_meta = super::load_metadata(&self.manifest, &config)?;
+        let ws_config = crate::config::load_workspace_config(&config, &ws_meta)?;
+        let pkgs = self.pkgs(&config, &ws_meta)?;
+        if pkgs.is_empty() {
+            log::info!("No packages selected.");
+            return Err(2.into());
+        }
+
+        let dry_run = self.dry_run();
+
+        // STEP 0: Help the user make the right decisions.
+        let index = crate::ops::cargo::open_index(ws_config.index_freshness())?;
+        for pkg in unpublished(&pkgs, ws_meta.workspace_root.as_std_path(), &index)? {
+            log::warn!(
+                "{} {} was not found in the {} index, `cargo yank` may fail",
+                pkg.meta.name,
+                self.version,
+                pkg.config.registry().unwrap_or("crates.io")
+            );
+        }
+
+        log::info!(
+            "Yanking doesn't delete a version; existing lock files can still use it, but new dependents won't resolve to it"
+        );
+
+        // STEP 1: Confirmation
+        super::confirm("Yank", &pkgs, self.no_confirm, dry_run)?;
+
+        // STEP 2: Yank
+        yank(&pkgs, dry_run)?;
+
+        super::finish(false, dry_run)
+    }
+
+    fn dry_run(&self) -> bool {
+        self.dry_run || !self.execute
+    }
+
+    /// The selected packages that are released and published, each planned at `--version`
+    fn pkgs(
+        &self,
+        config: &crate::config::ConfigArgs,
+        ws_meta: &cargo_metadata::Metadata,
+    ) -> Result<Vec<plan::PackageRelease>, ProcessError> {
+        let mut pkgs = plan::load(config, ws_meta)?;
+
+        let (_selected_pkgs, excluded_pkgs) = self.workspace.partition_packages(ws_meta);
+        for excluded_pkg in excluded_pkgs {
+            let pkg = if let Some(pkg) = pkgs.get_mut(&excluded_pkg.id) {
+                pkg
+            } else {
+                // Either not in workspace or marked as `release = false`.
+                continue;
+            };
+            pkg.config.release = Some(false);
+
+            let crate_name = pkg.meta.name.as_str();
+            log::debug!("Disabled by user, skipping {}", crate_name,);
+        }
+
+        let version = crate::ops::version::Version::from(self.version.clone());
+        Ok(pkgs
+            .into_iter()
+            .map(|(_, pkg)| pkg)
+            .filter(|p| p.config.release() && p.config.publish())
+            .map(|mut pkg| {
+                pkg.planned_version = Some(version.clone());
+                pkg
+            })
+            .collect())
+    }
+
+    fn to_config(&self) -> crate::config::ConfigArgs {
+        crate::config::ConfigArgs {
+            custom_config: self.custom_config.clone(),
+            isolated: self.isolated,
+            ..Default::default()
+        }
+    }
+}
+
+/// Packages whose planned version is missing from their registry's index
+///
+/// `index` is the crates.io index.  Alternative registries whose index can't be read are assumed
+/// to have the version.
+fn unpublished<'p>(
+    pkgs: &'p [plan::PackageRelease],
+    ws_root: &std::path::Path,
+    index: &crates_index::Index,
+) -> Result<Vec<&'p plan::PackageRelease>, FatalError> {
+    let mut unpublished = Vec::new();
+    for pkg in pkgs {
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        let registry_index;
+        let index = match pkg.config.registry() {
+            Some(registry) => {
+                registry_index = crate::ops::cargo::open_registry_index(ws_root, registry)?;
+                registry_index.as_ref()
+            }
+            None => Some(index),
+        };
+        if let Some(index) = index {
+            if !crate::ops::cargo::is_published(
+                index,
+                pkg.meta.name.as_str(),
+                &version.full_version_string,
+            ) {
+                unpublished.push(pkg);
+            }
+        }
+    }
+    Ok(unpublished)
+}
+
+pub fn yank(pkgs: &[plan::PackageRelease], dry_run: bool) -> Result<(), ProcessError> {
+    for pkg in pkgs {
+        let crate_name = pkg.meta.name.as_str();
+        let version = pkg.planned_version.as_ref().unwrap_or(&pkg.initial_version);
+        log::info!("Yanking {} {}", crate_name, version.full_version_string);
+        if !crate::ops::cargo::yank(
+            crate_name,
+            &version.full_version_string,
+            pkg.config.registry(),
+            pkg.config.cargo_bin(),
+            dry_run,
+        )? {
+            return Err(101.into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use clap::Parser;
+
+    #[derive(Debug, Parser)]
+    struct Cli {
+        #[command(flatten)]
+        step: YankStep,
+    }
+
+    fn step(args: &[&str]) -> YankStep {
+        Cli::try_parse_from(std::iter::once("yank").chain(args.iter().copied()))
+            .unwrap()
+            .step
+    }
+
+    fn names(pkgs: &[plan::PackageRelease]) -> Vec<&str> {
+        let mut names: Vec<_> = pkgs.iter().map(|p| p.meta.name.as_str()).collect();
+        names.sort_unstable();
+        names
+    }
+
+    mod pkgs {
+        use super::*;
+
+        fn pkgs(args: &[&str]) -> Vec<plan::PackageRelease> {
+            let step = step(args);
+            step.pkgs(
+                &step.to_config(),
+                &crate::test_support::metadata("mixed_ws"),
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn selected() {
+            let pkgs = pkgs(&["--version", "0.1.0", "-p", "a", "-p", "c"]);
+            assert_eq!(names(&pkgs), ["a", "c"]);
+            for pkg in &pkgs {
+                assert_eq!(
+                    pkg.planned_version.as_ref().unwrap().full_version_string,
+                    "0.1.0"
+                );
+            }
+        }
+
+        #[test]
+        fn workspace() {
+            let pkgs = pkgs(&["--version", "0.1.0", "--workspace", "--exclude", "b"]);
+            assert_eq!(names(&pkgs), ["a", "c"]);
+        }
+
+        #[test]
+        fn version_required() {
+            assert!(Cli::try_parse_from(["yank", "-p", "a"]).is_err());
+        }
+    }
+
+    mod dry_run {
+        use super::*;
+
+        #[test]
+        fn default() {
+            assert!(step(&["--version", "0.1.0"]).dry_run());
+            assert!(!step(&["--version", "0.1.0", "--execute"]).dry_run());
+            assert!(
+                Cli::try_parse_from(["yank", "--version", "0.1.0", "-x", "--dry-run"]).is_err()
+            );
+        }
+    }
+
+    mod unpublished {
+        use super::*;
+
+        #[test]
+        fn missing_version() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let index = crate::test_support::index(temp.path(), &[("a", "0.1.0"), ("b", "0.2.0")]);
+            let meta = crate::test_support::metadata("mixed_ws");
+            let mut pkgs = crate::test_support::pkgs("mixed_ws");
+            for pkg in &mut pkgs {
+                pkg.planned_version = Some(semver::Version::new(0, 1, 0).into());
+            }
+
+            let unpublished =
+                unpublished(&pkgs, meta.workspace_root.as_std_path(), &index).unwrap();
+            let mut unpublished: Vec<_> =
+                unpublished.iter().map(|p| p.meta.name.as_str()).collect();
+            unpublished.sort_unstable();
+            assert_eq!(unpublished, ["b", "c"]);
+        }
+
+        #[test]
+        fn unknown_registry() {
+            let temp = assert_fs::TempDir::new().unwrap();
+            let index = crate::test_support::index(temp.path(), &[]);
+            let meta = crate::test_support::metadata("mixed_ws");
+            let mut pkgs = crate::test_support::pkgs("mixed_ws");
+            for pkg in &mut pkgs {
+                pkg.config.registry = Some("cargo-release-test-unconfigured".to_owned());
+            }
+
+            let unpublished =
+                unpublished(&pkgs, meta.workspace_root.as_std_path(), &index).unwrap();
+            assert!(unpublished.is_empty());
+        }
+    }
+}
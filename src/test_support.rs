@@ -0,0 +1,131 @@
+//! Fixtures shared by the unit tests
+
+use std::path::Path;
+
+use crate::steps::plan;
+
+/// Run `git` in `dir`, failing the test if it fails
+pub fn git(dir: &Path, args: &[&str]) {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Create a repo on `main` in `dir`, ready to commit to
+pub fn init_repo(dir: &Path) {
+    git(dir, &["init", "--initial-branch", "main"]);
+    configure_repo(dir);
+}
+
+/// Set an identity and turn off commit signing for the repo at `dir`
+pub fn configure_repo(dir: &Path) {
+    git(dir, &["config", "user.name", "Test"]);
+    git(dir, &["config", "user.email", "test@example.com"]);
+    git(dir, &["config", "commit.gpgsign", "false"]);
+}
+
+/// A repo at `dir` with one commit pushed to a bare `origin` at `remote`
+pub fn init_pushed_repo(dir: &Path, remote: &Path) {
+    git(remote, &["init", "--bare", "--initial-branch", "main"]);
+    init_repo(dir);
+    std::fs::write(dir.join("file.txt"), "one\n").unwrap();
+    git(dir, &["add", "."]);
+    git(dir, &["commit", "-m", "Initial"]);
+    git(dir, &["remote", "add", "origin", remote.to_str().unwrap()]);
+    git(dir, &["push", "-u", "origin", "main"]);
+}
+
+/// A repo with `tests/fixtures/<fixture>` committed, and its `cargo metadata`
+pub fn fixture_repo(fixture: &str) -> (assert_fs::TempDir, cargo_metadata::Metadata) {
+    use assert_fs::prelude::*;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.copy_from(format!("tests/fixtures/{}", fixture), &["**"])
+        .unwrap();
+    std::fs::write(temp.path().join(".gitignore"), "target\n").unwrap();
+    init_repo(temp.path());
+    git(temp.path(), &["add", "."]);
+    git(temp.path(), &["commit", "-m", "Initial"]);
+    let meta = cargo_metadata::MetadataCommand::new()
+        .manifest_path(temp.path().join("Cargo.toml"))
+        .exec()
+        .unwrap();
+    (temp, meta)
+}
+
+/// The subjects of the commits reachable from `rev` in `dir`, oldest first
+pub fn log(dir: &Path, rev: &str) -> Vec<String> {
+    let output = std::process::Command::new("git")
+        .args(["log", "--reverse", "--format=%s", rev])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+/// `cargo metadata` of `tests/fixtures/<fixture>`
+pub fn metadata(fixture: &str) -> cargo_metadata::Metadata {
+    cargo_metadata::MetadataCommand::new()
+        .manifest_path(format!("tests/fixtures/{}/Cargo.toml", fixture))
+        .exec()
+        .unwrap()
+}
+
+/// The packages of `tests/fixtures/<fixture>` with the default config, not yet planned
+pub fn pkgs(fixture: &str) -> Vec<plan::PackageRelease> {
+    plan::load(&Default::default(), &metadata(fixture))
+        .unwrap()
+        .into_iter()
+        .map(|(_, pkg)| pkg)
+        .collect()
+}
+
+/// The package `name` of `tests/fixtures/<fixture>`, not yet planned
+pub fn pkg(fixture: &str, name: &str) -> plan::PackageRelease {
+    pkgs(fixture)
+        .into_iter()
+        .find(|p| p.meta.name == name)
+        .unwrap()
+}
+
+/// A git index at `dir` with one entry per `(name, version)`, like the crates.io index
+pub fn index(dir: &Path, crates: &[(&str, &str)]) -> crates_index::Index {
+    init_repo(dir);
+    for (name, version) in crates {
+        let prefix = match name.len() {
+            1 => "1".to_owned(),
+            2 => "2".to_owned(),
+            3 => format!("3/{}", &name[..1]),
+            _ => format!("{}/{}", &name[..2], &name[2..4]),
+        };
+        let path = dir.join(prefix).join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let entry = serde_json::json!({
+            "name": name,
+            "vers": version,
+            "deps": [],
+            "cksum": "0".repeat(64),
+            "features": {},
+            "yanked": false,
+        });
+        let mut content = std::fs::read_to_string(&path).unwrap_or_default();
+        content.push_str(&format!("{}\n", entry));
+        std::fs::write(&path, content).unwrap();
+    }
+    git(dir, &["add", "."]);
+    git(dir, &["commit", "--allow-empty", "-m", "Index"]);
+    crates_index::Index::with_path(dir, "https://example.com/index").unwrap()
+}